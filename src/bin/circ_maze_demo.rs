@@ -1,25 +1,44 @@
 use anyhow::Error as AnyError;
 use clap::{Args, Parser, Subcommand};
 use try_mazes::{
-    cli::{self, GeneralMazeAction, GeneralMazeLoadArgs},
+    cli::{
+        self, GeneralImagePassageWeightArgs, GeneralMazeAction, GeneralMazeCodeArgs,
+        GeneralMazeLoadArgs,
+    },
+    file::{GeneratorInfo, MazeHeader, MazeKind, SavedMaze},
     gene::{
-        AldousBroderMazeGenerator, EllerMazeGenerator, GrowingTreeMazeGenerator,
-        HuntAndKillMazeGenerator, KruskalMazeGenerator, PrimMazeGenerator,
-        RecursiveBacktrackerMazeGenerator, WilsonMazeGenerator,
+        AldousBroderMazeGenerator, BTreeMazeGenerator, DiagonalDirection, EllerMazeGenerator,
+        GrowingTreeMazeGenerator, GrowingTreeStrategy, HuntAndKillMazeGenerator,
+        KruskalMazeGenerator, OriginShiftMazeGenerator, PrimMazeGenerator,
+        RecursiveBacktrackerMazeGenerator, RegionDivisionMazeGenerator, SidewinderMazeGenerator,
+        WeightedKruskalMazeGenerator, WeightedPrimMazeGenerator, WilsonMazeGenerator,
         circ::{CircLayerMazeGenerator, CircMaze2dGenerator, CircMazeGenerator},
     },
+    graph::MazeGraph,
     maze::circ::CircGrid,
+    mesh::{MazeMesh, circ::CircMazeMesher},
     show::{MazePicture, circ::CircMazePainter},
+    stats::MazeStats,
+    validate::ValidationReport,
 };
 
 const DEF_WALL_THICKNESS: usize = 5;
 const DEF_RING_INTERVAL_WIDTH: usize = 50;
 fn main() -> Result<(), AnyError> {
     let maze_input = CircMazeInputArgs::parse();
-    let maze = match &maze_input.action {
-        DemoAction::Create(CircMazeCreateArgs {
-            rings_n, algorithm, ..
-        }) => {
+    let (header, maze) = match &maze_input.action {
+        DemoAction::Create(
+            create_args @ CircMazeCreateArgs {
+                rings_n,
+                algorithm,
+                con_dir,
+                room_max_cells_n,
+                growing_tree_strategy,
+                weight,
+                ..
+            },
+        ) => {
+            let seed = cli::seed_rng(create_args.seed);
             let grid = CircGrid::new(*rings_n);
             let generator: &dyn CircMazeGenerator = match algorithm {
                 CircMazeAlgorithm {
@@ -43,20 +62,55 @@ fn main() -> Result<(), AnyError> {
                 CircMazeAlgorithm { prim: true, .. } => {
                     &CircMaze2dGenerator::new(PrimMazeGenerator)
                 }
+                CircMazeAlgorithm {
+                    weighted_prim: true,
+                    ..
+                } => &CircMaze2dGenerator::new(WeightedPrimMazeGenerator::new(
+                    weight.passage_weight(&grid)?,
+                )),
+                CircMazeAlgorithm {
+                    weighted_kruskal: true,
+                    ..
+                } => &CircMaze2dGenerator::new(WeightedKruskalMazeGenerator::new(
+                    weight.passage_weight(&grid)?,
+                )),
                 CircMazeAlgorithm {
                     growing_tree: true, ..
-                } => &CircMaze2dGenerator::new(GrowingTreeMazeGenerator),
+                } => &CircMaze2dGenerator::new(GrowingTreeMazeGenerator::new(
+                    growing_tree_strategy.clone(),
+                )),
                 CircMazeAlgorithm { eller: true, .. } => {
                     &CircLayerMazeGenerator::new(EllerMazeGenerator)
                 }
-                other_algorithm => unreachable!(
-                    "Invalid circular maze algorithm({:?}), should be refused by clap.",
-                    other_algorithm
-                ),
+                CircMazeAlgorithm { btree: true, .. } => {
+                    &CircLayerMazeGenerator::new(BTreeMazeGenerator::new(con_dir.unwrap()))
+                }
+                CircMazeAlgorithm {
+                    sidewinder: true, ..
+                } => &CircLayerMazeGenerator::new(SidewinderMazeGenerator::new(con_dir.unwrap())),
+                CircMazeAlgorithm {
+                    recursive_division: true,
+                    ..
+                } => &CircMaze2dGenerator::new(RegionDivisionMazeGenerator::new(*room_max_cells_n)),
+                CircMazeAlgorithm {
+                    origin_shift: true, ..
+                } => &CircMaze2dGenerator::new(OriginShiftMazeGenerator),
+                other_algorithm => {
+                    return Err(cli::Error::InvalidArgs(format!("{:?}", other_algorithm)).into());
+                }
             };
-            generator.generate(grid)
+            let header =
+                MazeHeader::new(MazeKind::Circ).with_generator(generator_info(create_args), seed);
+            (header, generator.generate(grid)?)
+        }
+        DemoAction::Load(GeneralMazeLoadArgs { load_path, .. }) => {
+            let SavedMaze { header, maze } = cli::load_maze_file(load_path)?;
+            (header, maze)
+        }
+        DemoAction::ImportCode(GeneralMazeCodeArgs { code, .. }) => {
+            let SavedMaze { header, maze } = SavedMaze::from_code(code)?;
+            (header, maze)
         }
-        DemoAction::Load(GeneralMazeLoadArgs { load_path, .. }) => cli::load_from_json(load_path)?,
     };
 
     let painter = CircMazePainter::new(
@@ -67,7 +121,8 @@ fn main() -> Result<(), AnyError> {
     let picture = MazePicture::new(&painter);
     match &maze_input.action {
         DemoAction::Create(CircMazeCreateArgs { action, .. })
-        | DemoAction::Load(GeneralMazeLoadArgs { action, .. }) => match action {
+        | DemoAction::Load(GeneralMazeLoadArgs { action, .. })
+        | DemoAction::ImportCode(GeneralMazeCodeArgs { action, .. }) => match action {
             GeneralMazeAction::Show {
                 wnd_width,
                 wnd_height,
@@ -80,11 +135,36 @@ fn main() -> Result<(), AnyError> {
             } => picture.save(path, *pic_format)?,
             GeneralMazeAction::Save {
                 json: true, path, ..
-            } => cli::save_to_json(path, &maze)?,
-            other_action => unreachable!(
-                "Invalid maze action({:?}), should be refused by clap.",
-                other_action
-            ),
+            } => cli::save_to_json(path, &header, &maze)?,
+            GeneralMazeAction::Save {
+                binary: true,
+                compress,
+                path,
+                ..
+            } => cli::save_to_binary(path, &header, &maze, *compress)?,
+            GeneralMazeAction::ExportCode => cli::print_code(&header, &maze)?,
+            GeneralMazeAction::Mesh {
+                path,
+                format,
+                settings,
+            } => CircMazeMesher::new(&maze, *settings)
+                .mesh()?
+                .save(path, *format)?,
+            GeneralMazeAction::Graph {
+                path,
+                format,
+                settings,
+            } => MazeGraph::new(&maze, settings, header.start).save(path, *format)?,
+            GeneralMazeAction::Edit { .. } => {
+                return Err(cli::Error::NotSupportEdit("circular".to_string()).into());
+            }
+            GeneralMazeAction::Stats { json } => cli::print_stats(&MazeStats::new(&maze), *json)?,
+            GeneralMazeAction::Validate { json } => {
+                cli::print_validation(&ValidationReport::new(maze.grid()), *json)?
+            }
+            other_action => {
+                return Err(cli::Error::InvalidArgs(format!("{:?}", other_action)).into());
+            }
         },
     }
 
@@ -109,6 +189,7 @@ struct CircMazeInputArgs {
 enum DemoAction {
     Create(CircMazeCreateArgs),
     Load(GeneralMazeLoadArgs),
+    ImportCode(GeneralMazeCodeArgs),
 }
 
 #[derive(Debug, Clone, Args)]
@@ -119,6 +200,21 @@ struct CircMazeCreateArgs {
     /// Algorithm used by generator
     #[command(flatten)]
     algorithm: CircMazeAlgorithm,
+    /// Passage weights, used by weighted algorithms
+    #[command(flatten)]
+    weight: GeneralImagePassageWeightArgs,
+    /// Strategy to select active cell, used by growing tree algorithm, e.g. newest, middle or "newest:3,random:1"
+    #[arg(long, default_value_t)]
+    growing_tree_strategy: GrowingTreeStrategy,
+    /// Candidate directions to connect, used by binary tree and sidewinder algorithm
+    #[arg(short, long, group = "connect direction")]
+    con_dir: Option<DiagonalDirection>,
+    /// Max cells number of room, used by recursive division algorithm
+    #[arg(long, default_value_t = 1)]
+    room_max_cells_n: usize,
+    /// Seed of random generation, the same seed with the same arguments makes the same maze(random if not given)
+    #[arg(long)]
+    seed: Option<u64>,
     /// What to do with circular maze
     #[command(subcommand)]
     action: GeneralMazeAction,
@@ -127,6 +223,12 @@ struct CircMazeCreateArgs {
 #[derive(Debug, Clone, Args)]
 #[group(required = true, multiple = false)]
 pub struct CircMazeAlgorithm {
+    /// Using binary tree algorithm
+    #[arg(long, requires = "connect direction")]
+    pub btree: bool,
+    /// Using sidewinder algorithm
+    #[arg(long, requires = "connect direction")]
+    pub sidewinder: bool,
     /// Using Aldous-Broder algorithm
     #[arg(long)]
     pub aldous_broder: bool,
@@ -145,10 +247,73 @@ pub struct CircMazeAlgorithm {
     /// Using Prim's algorithm
     #[arg(long)]
     pub prim: bool,
+    /// Using weighted Prim's algorithm
+    #[arg(long)]
+    pub weighted_prim: bool,
+    /// Using weighted Kruskal's algorithm
+    #[arg(long)]
+    pub weighted_kruskal: bool,
     /// Using growing tree algorithm
     #[arg(long)]
     pub growing_tree: bool,
+    /// Using origin shift algorithm
+    #[arg(long)]
+    pub origin_shift: bool,
     /// Using Eller's algorithm
     #[arg(long)]
     pub eller: bool,
+    /// Using recursive division algorithm
+    #[arg(long)]
+    pub recursive_division: bool,
+}
+
+impl CircMazeAlgorithm {
+    fn name(&self) -> &'static str {
+        [
+            (self.btree, "binary tree"),
+            (self.sidewinder, "sidewinder"),
+            (self.aldous_broder, "Aldous-Broder"),
+            (self.wilson, "Wilson's"),
+            (self.hunt_and_kill, "Hunt-and-Kill"),
+            (self.recursive_backtracker, "recursive backtracker"),
+            (self.kruskal, "Kruskal's"),
+            (self.prim, "Prim's"),
+            (self.weighted_prim, "weighted Prim's"),
+            (self.weighted_kruskal, "weighted Kruskal's"),
+            (self.growing_tree, "growing tree"),
+            (self.origin_shift, "origin shift"),
+            (self.eller, "Eller's"),
+            (self.recursive_division, "recursive division"),
+        ]
+        .into_iter()
+        .find_map(|(is_chosen, name)| is_chosen.then_some(name))
+        .unwrap_or_default()
+    }
+}
+
+fn generator_info(input: &CircMazeCreateArgs) -> GeneratorInfo {
+    let algorithm = &input.algorithm;
+    let info = GeneratorInfo::new(algorithm.name());
+    match algorithm {
+        CircMazeAlgorithm { btree: true, .. }
+        | CircMazeAlgorithm {
+            sidewinder: true, ..
+        } => info.with_param("con_dir", format!("{:?}", input.con_dir.unwrap())),
+        CircMazeAlgorithm {
+            weighted_prim: true,
+            ..
+        }
+        | CircMazeAlgorithm {
+            weighted_kruskal: true,
+            ..
+        } => input.weight.with_params(info),
+        CircMazeAlgorithm {
+            growing_tree: true, ..
+        } => info.with_param("strategy", &input.growing_tree_strategy),
+        CircMazeAlgorithm {
+            recursive_division: true,
+            ..
+        } => info.with_param("room_max_cells_n", input.room_max_cells_n),
+        _ => info,
+    }
 }
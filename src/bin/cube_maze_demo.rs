@@ -0,0 +1,263 @@
+use anyhow::Error as AnyError;
+use clap::{Args, Parser, Subcommand};
+use try_mazes::{
+    cli::{
+        self, GeneralImagePassageWeightArgs, GeneralMazeAction, GeneralMazeCodeArgs,
+        GeneralMazeLoadArgs,
+    },
+    file::{GeneratorInfo, MazeHeader, MazeKind, SavedMaze},
+    gene::{
+        AldousBroderMazeGenerator, GrowingTreeMazeGenerator, GrowingTreeStrategy,
+        HuntAndKillMazeGenerator, KruskalMazeGenerator, OriginShiftMazeGenerator,
+        PrimMazeGenerator, RecursiveBacktrackerMazeGenerator, WeightedKruskalMazeGenerator,
+        WeightedPrimMazeGenerator, WilsonMazeGenerator, cube::CubeMazeGenerator,
+    },
+    graph::MazeGraph,
+    maze::cube::CubeGrid,
+    mesh::{MazeMesh, cube::CubeMazeMesher},
+    show::{MazePicture, cube::CubeMazePainter},
+    stats::MazeStats,
+    validate::ValidationReport,
+};
+
+const DEF_WALL_THICKNESS: usize = 5;
+const DEF_CELL_WIDTH: usize = 30;
+
+fn main() -> Result<(), AnyError> {
+    let maze_input = CubeMazeInputArgs::parse();
+    let (header, maze) = match &maze_input.action {
+        DemoAction::Create(
+            create_args @ CubeMazeCreateArgs {
+                size,
+                algorithm,
+                growing_tree_strategy,
+                weight,
+                ..
+            },
+        ) => {
+            let seed = cli::seed_rng(create_args.seed);
+            let grid = CubeGrid::new(*size);
+            let generator: &dyn CubeMazeGenerator = match algorithm {
+                CubeMazeAlgorithm {
+                    aldous_broder: true,
+                    ..
+                } => &AldousBroderMazeGenerator,
+                CubeMazeAlgorithm { wilson: true, .. } => &WilsonMazeGenerator,
+                CubeMazeAlgorithm {
+                    hunt_and_kill: true,
+                    ..
+                } => &HuntAndKillMazeGenerator,
+                CubeMazeAlgorithm {
+                    recursive_backtracker: true,
+                    ..
+                } => &RecursiveBacktrackerMazeGenerator,
+                CubeMazeAlgorithm { kruskal: true, .. } => &KruskalMazeGenerator,
+                CubeMazeAlgorithm { prim: true, .. } => &PrimMazeGenerator,
+                CubeMazeAlgorithm {
+                    weighted_prim: true,
+                    ..
+                } => &WeightedPrimMazeGenerator::new(
+                    weight.passage_weight_at(&grid, |pos| grid.net_point(pos))?,
+                ),
+                CubeMazeAlgorithm {
+                    weighted_kruskal: true,
+                    ..
+                } => &WeightedKruskalMazeGenerator::new(
+                    weight.passage_weight_at(&grid, |pos| grid.net_point(pos))?,
+                ),
+                CubeMazeAlgorithm {
+                    growing_tree: true, ..
+                } => &GrowingTreeMazeGenerator::new(growing_tree_strategy.clone()),
+                CubeMazeAlgorithm {
+                    origin_shift: true, ..
+                } => &OriginShiftMazeGenerator,
+                other_algorithm => {
+                    return Err(cli::Error::InvalidArgs(format!("{:?}", other_algorithm)).into());
+                }
+            };
+            let header =
+                MazeHeader::new(MazeKind::Cube).with_generator(generator_info(create_args), seed);
+            (header, generator.generate(grid)?)
+        }
+        DemoAction::Load(GeneralMazeLoadArgs { load_path, .. }) => {
+            let SavedMaze { header, maze } = cli::load_maze_file(load_path)?;
+            (header, maze)
+        }
+        DemoAction::ImportCode(GeneralMazeCodeArgs { code, .. }) => {
+            let SavedMaze { header, maze } = SavedMaze::from_code(code)?;
+            (header, maze)
+        }
+    };
+
+    let painter = CubeMazePainter::new(&maze, maze_input.wall_thickness, maze_input.cell_width);
+    let picture = MazePicture::new(&painter);
+    match &maze_input.action {
+        DemoAction::Create(CubeMazeCreateArgs { action, .. })
+        | DemoAction::Load(GeneralMazeLoadArgs { action, .. })
+        | DemoAction::ImportCode(GeneralMazeCodeArgs { action, .. }) => match action {
+            GeneralMazeAction::Show {
+                wnd_width,
+                wnd_height,
+            } => picture.show(*wnd_width, *wnd_height)?,
+            GeneralMazeAction::Save {
+                picture: true,
+                path,
+                format: Some(pic_format),
+                ..
+            } => picture.save(path, *pic_format)?,
+            GeneralMazeAction::Save {
+                json: true, path, ..
+            } => cli::save_to_json(path, &header, &maze)?,
+            GeneralMazeAction::Save {
+                binary: true,
+                compress,
+                path,
+                ..
+            } => cli::save_to_binary(path, &header, &maze, *compress)?,
+            GeneralMazeAction::ExportCode => cli::print_code(&header, &maze)?,
+            GeneralMazeAction::Mesh {
+                path,
+                format,
+                settings,
+            } => CubeMazeMesher::new(&maze, *settings)
+                .mesh()?
+                .save(path, *format)?,
+            GeneralMazeAction::Graph {
+                path,
+                format,
+                settings,
+            } => MazeGraph::new(&maze, settings, header.start).save(path, *format)?,
+            GeneralMazeAction::Edit { .. } => {
+                return Err(cli::Error::NotSupportEdit("cube".to_string()).into());
+            }
+            GeneralMazeAction::Stats { json } => {
+                cli::print_stats(&MazeStats::new(maze.grid()), *json)?
+            }
+            GeneralMazeAction::Validate { json } => {
+                cli::print_validation(&ValidationReport::new(maze.grid()), *json)?
+            }
+            other_action => {
+                return Err(cli::Error::InvalidArgs(format!("{:?}", other_action)).into());
+            }
+        },
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Parser)]
+#[command(flatten_help = true)]
+struct CubeMazeInputArgs {
+    /// Width of each cell empty space in the unfolded net
+    #[arg(short, long, default_value_t = DEF_CELL_WIDTH)]
+    cell_width: usize,
+    /// Thickness of the maze wall(the stroke)
+    #[arg(short, long, default_value_t = DEF_WALL_THICKNESS)]
+    wall_thickness: usize,
+    /// What to do in demo
+    #[command(subcommand)]
+    action: DemoAction,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum DemoAction {
+    Create(CubeMazeCreateArgs),
+    Load(GeneralMazeLoadArgs),
+    ImportCode(GeneralMazeCodeArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+struct CubeMazeCreateArgs {
+    /// Number of cells along the edge of cube
+    #[arg(short, long)]
+    size: usize,
+    /// Algorithm used by generator
+    #[command(flatten)]
+    algorithm: CubeMazeAlgorithm,
+    /// Passage weights, used by weighted algorithms
+    #[command(flatten)]
+    weight: GeneralImagePassageWeightArgs,
+    /// Strategy to select active cell, used by growing tree algorithm, e.g. newest, middle or "newest:3,random:1"
+    #[arg(long, default_value_t)]
+    growing_tree_strategy: GrowingTreeStrategy,
+    /// Seed of random generation, the same seed with the same arguments makes the same maze(random if not given)
+    #[arg(long)]
+    seed: Option<u64>,
+    /// What to do with cube maze
+    #[command(subcommand)]
+    action: GeneralMazeAction,
+}
+
+#[derive(Debug, Clone, Args)]
+#[group(required = true, multiple = false)]
+pub struct CubeMazeAlgorithm {
+    /// Using Aldous-Broder algorithm
+    #[arg(long)]
+    pub aldous_broder: bool,
+    /// Using Wilson's algorithm
+    #[arg(long)]
+    pub wilson: bool,
+    /// Using Hunt-and-Kill algorithm
+    #[arg(long)]
+    pub hunt_and_kill: bool,
+    /// Using recursive backtracker algorithm
+    #[arg(long)]
+    pub recursive_backtracker: bool,
+    /// Using Kruskal's algorithm
+    #[arg(long)]
+    pub kruskal: bool,
+    /// Using Prim's algorithm
+    #[arg(long)]
+    pub prim: bool,
+    /// Using weighted Prim's algorithm
+    #[arg(long)]
+    pub weighted_prim: bool,
+    /// Using weighted Kruskal's algorithm
+    #[arg(long)]
+    pub weighted_kruskal: bool,
+    /// Using growing tree algorithm
+    #[arg(long)]
+    pub growing_tree: bool,
+    /// Using origin shift algorithm
+    #[arg(long)]
+    pub origin_shift: bool,
+}
+
+impl CubeMazeAlgorithm {
+    fn name(&self) -> &'static str {
+        [
+            (self.aldous_broder, "Aldous-Broder"),
+            (self.wilson, "Wilson's"),
+            (self.hunt_and_kill, "Hunt-and-Kill"),
+            (self.recursive_backtracker, "recursive backtracker"),
+            (self.kruskal, "Kruskal's"),
+            (self.prim, "Prim's"),
+            (self.weighted_prim, "weighted Prim's"),
+            (self.weighted_kruskal, "weighted Kruskal's"),
+            (self.growing_tree, "growing tree"),
+            (self.origin_shift, "origin shift"),
+        ]
+        .into_iter()
+        .find_map(|(is_chosen, name)| is_chosen.then_some(name))
+        .unwrap_or_default()
+    }
+}
+
+fn generator_info(input: &CubeMazeCreateArgs) -> GeneratorInfo {
+    let algorithm = &input.algorithm;
+    let info = GeneratorInfo::new(algorithm.name());
+    match algorithm {
+        CubeMazeAlgorithm {
+            weighted_prim: true,
+            ..
+        }
+        | CubeMazeAlgorithm {
+            weighted_kruskal: true,
+            ..
+        } => input.weight.with_params(info),
+        CubeMazeAlgorithm {
+            growing_tree: true, ..
+        } => info.with_param("strategy", &input.growing_tree_strategy),
+        _ => info,
+    }
+}
@@ -1,15 +1,26 @@
 use anyhow::Error as AnyError;
 use clap::{Args, Parser, Subcommand};
 use try_mazes::{
-    cli::{self, Error, GeneralMazeAction, GeneralMazeLoadArgs, GeneralRectMazeShape},
+    cli::{
+        self, GeneralImagePassageWeightArgs, GeneralMazeAction, GeneralMazeCodeArgs,
+        GeneralMazeLoadArgs, GeneralRectMazeShape,
+    },
+    edit::{MazeEditor, hexa::HexaEditLayout},
+    file::{GeneratorInfo, MazeHeader, MazeKind, SavedMaze},
     gene::{
-        AldousBroderMazeGenerator, EllerMazeGenerator, GrowingTreeMazeGenerator,
-        HuntAndKillMazeGenerator, KruskalMazeGenerator, PrimMazeGenerator,
-        RecursiveBacktrackerMazeGenerator, WilsonMazeGenerator,
+        self, AldousBroderMazeGenerator, BTreeMazeGenerator, DiagonalDirection, EllerMazeGenerator,
+        GrowingTreeMazeGenerator, GrowingTreeStrategy, HuntAndKillMazeGenerator,
+        KruskalMazeGenerator, OriginShiftMazeGenerator, PassageWeight, PrimMazeGenerator,
+        RecursiveBacktrackerMazeGenerator, RegionDivisionMazeGenerator, SidewinderMazeGenerator,
+        WeightedKruskalMazeGenerator, WeightedPrimMazeGenerator, WilsonMazeGenerator,
         hexa::{HexaLayerMazeGenerator, HexaMaze2dGenerator, HexaMazeGenerator},
     },
+    graph::MazeGraph,
     maze::{NoMask, WithMask, hexa::HexaGrid, rect::RectMask},
-    show::{MazePicture, hexa::HexaMazePainter},
+    mesh::{MazeMesh, hexa::HexaMazeMesher},
+    show::{MazePaint, MazePicture, hexa::HexaMazePainter},
+    stats::MazeStats,
+    validate::ValidationReport,
 };
 
 const DEF_CELL_WIDTH: u16 = 50;
@@ -17,33 +28,51 @@ const DEF_WALL_THICKNESS: u16 = 5;
 
 fn main() -> Result<(), AnyError> {
     let maze_input = HexaMazeInputArgs::parse();
-    let maze = match &maze_input.action {
-        DemoAction::Create(HexaMazeCreateArgs {
-            algorithm, shape, ..
-        }) => match shape {
-            GeneralRectMazeShape::Size { width, height, .. } => {
-                let grid = HexaGrid::<NoMask>::new(*width, *height);
-                let generator = make_generator_no_mask(algorithm);
-                generator.generate(grid)
-            }
-            mask_shape => {
-                let grid = match mask_shape {
-                    GeneralRectMazeShape::Mask {
-                        text: true, path, ..
-                    } => HexaGrid::<WithMask>::new(&RectMask::try_from_text_file(path)?),
-                    GeneralRectMazeShape::Mask {
-                        image: true, path, ..
-                    } => HexaGrid::<WithMask>::new(&RectMask::try_from_image_file(path)?),
-                    other_shape => unreachable!(
-                        "Invalid maze shape({:?}), should be refused by clap.",
-                        other_shape
-                    ),
-                };
-                let generator = make_generator_with_mask(algorithm)?;
-                generator.generate(grid)
-            }
-        },
-        DemoAction::Load(GeneralMazeLoadArgs { load_path, .. }) => cli::load_from_json(load_path)?,
+    let (header, maze) = match &maze_input.action {
+        DemoAction::Create(create_args) => {
+            let seed = cli::seed_rng(create_args.seed);
+            let maze = match &create_args.shape {
+                GeneralRectMazeShape::Size { width, height, .. } => {
+                    let grid = HexaGrid::<NoMask>::new(*width, *height);
+                    let generator = make_generator_no_mask(
+                        create_args,
+                        create_args.weight.passage_weight(&grid)?,
+                    )?;
+                    generator.generate(grid)?
+                }
+                mask_shape => {
+                    let grid = match mask_shape {
+                        GeneralRectMazeShape::Mask {
+                            text: true, path, ..
+                        } => HexaGrid::<WithMask>::new(&RectMask::try_from_text_file(path)?),
+                        GeneralRectMazeShape::Mask {
+                            image: true, path, ..
+                        } => HexaGrid::<WithMask>::new(&RectMask::try_from_image_file(path)?),
+                        other_shape => {
+                            return Err(
+                                cli::Error::InvalidArgs(format!("{:?}", other_shape)).into()
+                            );
+                        }
+                    };
+                    let generator = make_generator_with_mask(
+                        create_args,
+                        create_args.weight.passage_weight(&grid)?,
+                    )?;
+                    generator.generate(grid)?
+                }
+            };
+            let header =
+                MazeHeader::new(MazeKind::Hexa).with_generator(generator_info(create_args), seed);
+            (header, maze)
+        }
+        DemoAction::Load(GeneralMazeLoadArgs { load_path, .. }) => {
+            let SavedMaze { header, maze } = cli::load_maze_file(load_path)?;
+            (header, maze)
+        }
+        DemoAction::ImportCode(GeneralMazeCodeArgs { code, .. }) => {
+            let SavedMaze { header, maze } = SavedMaze::from_code(code)?;
+            (header, maze)
+        }
     };
     let painter = HexaMazePainter::new(&maze, maze_input.cell_height, maze_input.wall_thickness);
     let picture = MazePicture::new(&painter);
@@ -56,7 +85,8 @@ fn main() -> Result<(), AnyError> {
             shape: GeneralRectMazeShape::Mask { action, .. },
             ..
         })
-        | DemoAction::Load(GeneralMazeLoadArgs { action, .. }) => match action {
+        | DemoAction::Load(GeneralMazeLoadArgs { action, .. })
+        | DemoAction::ImportCode(GeneralMazeCodeArgs { action, .. }) => match action {
             GeneralMazeAction::Show {
                 wnd_width,
                 wnd_height,
@@ -69,11 +99,52 @@ fn main() -> Result<(), AnyError> {
             } => picture.save(path, *pic_format)?,
             GeneralMazeAction::Save {
                 json: true, path, ..
-            } => cli::save_to_json(path, &maze)?,
-            other_action => unreachable!(
-                "Invalid maze action({:?}), should be refused by clap.",
-                other_action
-            ),
+            } => cli::save_to_json(path, &header, &maze)?,
+            GeneralMazeAction::Save {
+                binary: true,
+                compress,
+                path,
+                ..
+            } => cli::save_to_binary(path, &header, &maze, *compress)?,
+            GeneralMazeAction::ExportCode => cli::print_code(&header, &maze)?,
+            GeneralMazeAction::Mesh {
+                path,
+                format,
+                settings,
+            } => HexaMazeMesher::new(&maze, *settings)
+                .mesh()?
+                .save(path, *format)?,
+            GeneralMazeAction::Graph {
+                path,
+                format,
+                settings,
+            } => MazeGraph::new(&maze, settings, header.start).save(path, *format)?,
+            GeneralMazeAction::Edit { path } => {
+                let layout =
+                    HexaEditLayout::new(&maze, maze_input.cell_height, maze_input.wall_thickness);
+                MazeEditor::new(maze.clone())
+                    .with_endpoints(header.start, header.goal)
+                    .run(
+                        &layout,
+                        |maze| {
+                            HexaMazePainter::new(
+                                maze,
+                                maze_input.cell_height,
+                                maze_input.wall_thickness,
+                            )
+                            .paint()
+                        },
+                        path,
+                        &header,
+                    )?;
+            }
+            GeneralMazeAction::Stats { json } => cli::print_stats(&MazeStats::new(&maze), *json)?,
+            GeneralMazeAction::Validate { json } => {
+                cli::print_validation(&ValidationReport::new(maze.grid()), *json)?
+            }
+            other_action => {
+                return Err(cli::Error::InvalidArgs(format!("{:?}", other_action)).into());
+            }
         },
     }
 
@@ -98,6 +169,7 @@ struct HexaMazeInputArgs {
 enum DemoAction {
     Create(HexaMazeCreateArgs),
     Load(GeneralMazeLoadArgs),
+    ImportCode(GeneralMazeCodeArgs),
 }
 
 #[derive(Debug, Clone, Args)]
@@ -105,6 +177,21 @@ struct HexaMazeCreateArgs {
     // Maze generation algorithm
     #[command(flatten)]
     algorithm: HexaMazeAlgorithm,
+    /// Passage weights, used by weighted algorithms
+    #[command(flatten)]
+    weight: GeneralImagePassageWeightArgs,
+    /// Strategy to select active cell, used by growing tree algorithm, e.g. newest, middle or "newest:3,random:1"
+    #[arg(long, default_value_t)]
+    growing_tree_strategy: GrowingTreeStrategy,
+    /// Candidate directions to connect, used by binary tree and sidewinder algorithm(north is to any cell in the upper row)
+    #[arg(short, long, group = "connect direction")]
+    con_dir: Option<DiagonalDirection>,
+    /// Max cells number of room, used by recursive division algorithm
+    #[arg(long, default_value_t = 1)]
+    room_max_cells_n: usize,
+    /// Seed of random generation, the same seed with the same arguments makes the same maze(random if not given)
+    #[arg(long)]
+    seed: Option<u64>,
     /// Maze shape, by size or from mask
     #[command(subcommand)]
     shape: GeneralRectMazeShape,
@@ -113,6 +200,12 @@ struct HexaMazeCreateArgs {
 #[derive(Debug, Clone, Args)]
 #[group(required = true, multiple = false)]
 struct HexaMazeAlgorithm {
+    /// Using binary tree algorithm
+    #[arg(long, requires = "connect direction")]
+    pub btree: bool,
+    /// Using sidewinder algorithm
+    #[arg(long, requires = "connect direction")]
+    pub sidewinder: bool,
     /// Using Aldous-Broder algorithm
     #[arg(long)]
     pub aldous_broder: bool,
@@ -131,54 +224,126 @@ struct HexaMazeAlgorithm {
     /// Using Prim's algorithm
     #[arg(long)]
     pub prim: bool,
+    /// Using weighted Prim's algorithm
+    #[arg(long)]
+    pub weighted_prim: bool,
+    /// Using weighted Kruskal's algorithm
+    #[arg(long)]
+    pub weighted_kruskal: bool,
     /// Using growing tree algorithm
     #[arg(long)]
     pub growing_tree: bool,
+    /// Using origin shift algorithm
+    #[arg(long)]
+    pub origin_shift: bool,
     /// Using Eller's algorithm
     #[arg(long)]
     pub eller: bool,
+    /// Using recursive division algorithm
+    #[arg(long)]
+    pub recursive_division: bool,
 }
 
-fn make_generator_no_mask(algorithm: &HexaMazeAlgorithm) -> Box<dyn HexaMazeGenerator<NoMask>> {
-    match algorithm {
+impl HexaMazeAlgorithm {
+    fn name(&self) -> &'static str {
+        [
+            (self.btree, "binary tree"),
+            (self.sidewinder, "sidewinder"),
+            (self.aldous_broder, "Aldous-Broder"),
+            (self.wilson, "Wilson's"),
+            (self.hunt_and_kill, "Hunt-and-Kill"),
+            (self.recursive_backtracker, "recursive backtracker"),
+            (self.kruskal, "Kruskal's"),
+            (self.prim, "Prim's"),
+            (self.weighted_prim, "weighted Prim's"),
+            (self.weighted_kruskal, "weighted Kruskal's"),
+            (self.growing_tree, "growing tree"),
+            (self.origin_shift, "origin shift"),
+            (self.eller, "Eller's"),
+            (self.recursive_division, "recursive division"),
+        ]
+        .into_iter()
+        .find_map(|(is_chosen, name)| is_chosen.then_some(name))
+        .unwrap_or_default()
+    }
+}
+
+fn make_generator_no_mask(
+    input: &HexaMazeCreateArgs,
+    weight: Box<dyn PassageWeight>,
+) -> Result<Box<dyn HexaMazeGenerator<NoMask>>, AnyError> {
+    match &input.algorithm {
         HexaMazeAlgorithm {
             aldous_broder: true,
             ..
-        } => Box::new(HexaMaze2dGenerator::new(AldousBroderMazeGenerator)),
+        } => Ok(Box::new(HexaMaze2dGenerator::new(
+            AldousBroderMazeGenerator,
+        ))),
         HexaMazeAlgorithm { wilson: true, .. } => {
-            Box::new(HexaMaze2dGenerator::new(WilsonMazeGenerator))
+            Ok(Box::new(HexaMaze2dGenerator::new(WilsonMazeGenerator)))
         }
         HexaMazeAlgorithm {
             hunt_and_kill: true,
             ..
-        } => Box::new(HexaMaze2dGenerator::new(HuntAndKillMazeGenerator)),
+        } => Ok(Box::new(HexaMaze2dGenerator::new(HuntAndKillMazeGenerator))),
         HexaMazeAlgorithm {
             recursive_backtracker: true,
             ..
-        } => Box::new(HexaMaze2dGenerator::new(RecursiveBacktrackerMazeGenerator)),
+        } => Ok(Box::new(HexaMaze2dGenerator::new(
+            RecursiveBacktrackerMazeGenerator,
+        ))),
         HexaMazeAlgorithm { kruskal: true, .. } => {
-            Box::new(HexaMaze2dGenerator::new(KruskalMazeGenerator))
+            Ok(Box::new(HexaMaze2dGenerator::new(KruskalMazeGenerator)))
         }
         HexaMazeAlgorithm { prim: true, .. } => {
-            Box::new(HexaMaze2dGenerator::new(PrimMazeGenerator))
+            Ok(Box::new(HexaMaze2dGenerator::new(PrimMazeGenerator)))
         }
+        HexaMazeAlgorithm {
+            weighted_prim: true,
+            ..
+        } => Ok(Box::new(HexaMaze2dGenerator::new(
+            WeightedPrimMazeGenerator::new(weight),
+        ))),
+        HexaMazeAlgorithm {
+            weighted_kruskal: true,
+            ..
+        } => Ok(Box::new(HexaMaze2dGenerator::new(
+            WeightedKruskalMazeGenerator::new(weight),
+        ))),
         HexaMazeAlgorithm {
             growing_tree: true, ..
-        } => Box::new(HexaMaze2dGenerator::new(GrowingTreeMazeGenerator)),
+        } => Ok(Box::new(HexaMaze2dGenerator::new(
+            GrowingTreeMazeGenerator::new(input.growing_tree_strategy.clone()),
+        ))),
+        HexaMazeAlgorithm {
+            origin_shift: true, ..
+        } => Ok(Box::new(HexaMaze2dGenerator::new(OriginShiftMazeGenerator))),
         HexaMazeAlgorithm { eller: true, .. } => {
-            Box::new(HexaLayerMazeGenerator::new(EllerMazeGenerator))
+            Ok(Box::new(HexaLayerMazeGenerator::new(EllerMazeGenerator)))
         }
-        other_algorithm => unreachable!(
-            "Invalid algorithm({:?}), should be refused by clap.",
-            other_algorithm
-        ),
+        HexaMazeAlgorithm { btree: true, .. } => Ok(Box::new(HexaLayerMazeGenerator::new(
+            BTreeMazeGenerator::new(input.con_dir.unwrap()),
+        ))),
+        HexaMazeAlgorithm {
+            sidewinder: true, ..
+        } => Ok(Box::new(HexaLayerMazeGenerator::new(
+            SidewinderMazeGenerator::new(input.con_dir.unwrap()),
+        ))),
+        HexaMazeAlgorithm {
+            recursive_division: true,
+            ..
+        } => Ok(Box::new(HexaMaze2dGenerator::new(
+            RegionDivisionMazeGenerator::new(input.room_max_cells_n),
+        ))),
+        other_algorithm => Err(cli::Error::InvalidArgs(format!("{:?}", other_algorithm)).into()),
     }
 }
 
 fn make_generator_with_mask(
-    algorithm: &HexaMazeAlgorithm,
+    input: &HexaMazeCreateArgs,
+    weight: Box<dyn PassageWeight>,
 ) -> Result<Box<dyn HexaMazeGenerator<WithMask>>, AnyError> {
-    match algorithm {
+    match &input.algorithm {
         HexaMazeAlgorithm {
             aldous_broder: true,
             ..
@@ -204,15 +369,72 @@ fn make_generator_with_mask(
         HexaMazeAlgorithm { prim: true, .. } => {
             Ok(Box::new(HexaMaze2dGenerator::new(PrimMazeGenerator)))
         }
+        HexaMazeAlgorithm {
+            weighted_prim: true,
+            ..
+        } => Ok(Box::new(HexaMaze2dGenerator::new(
+            WeightedPrimMazeGenerator::new(weight),
+        ))),
+        HexaMazeAlgorithm {
+            weighted_kruskal: true,
+            ..
+        } => Ok(Box::new(HexaMaze2dGenerator::new(
+            WeightedKruskalMazeGenerator::new(weight),
+        ))),
         HexaMazeAlgorithm {
             growing_tree: true, ..
-        } => Ok(Box::new(HexaMaze2dGenerator::new(GrowingTreeMazeGenerator))),
+        } => Ok(Box::new(HexaMaze2dGenerator::new(
+            GrowingTreeMazeGenerator::new(input.growing_tree_strategy.clone()),
+        ))),
+        HexaMazeAlgorithm {
+            origin_shift: true, ..
+        } => Ok(Box::new(HexaMaze2dGenerator::new(OriginShiftMazeGenerator))),
         HexaMazeAlgorithm { eller: true, .. } => {
-            Err(Error::NotSupportMask("Eller".to_string()).into())
+            Ok(Box::new(HexaLayerMazeGenerator::new(EllerMazeGenerator)))
         }
-        other_algorithm => unreachable!(
-            "Invalid algorithm({:?}), should be refused by clap.",
-            other_algorithm
-        ),
+        HexaMazeAlgorithm { btree: true, .. } => Ok(Box::new(HexaLayerMazeGenerator::new(
+            BTreeMazeGenerator::new(input.con_dir.unwrap()),
+        ))),
+        HexaMazeAlgorithm {
+            sidewinder: true, ..
+        } => Ok(Box::new(HexaLayerMazeGenerator::new(
+            SidewinderMazeGenerator::new(input.con_dir.unwrap()),
+        ))),
+        HexaMazeAlgorithm {
+            recursive_division: true,
+            ..
+        } => Ok(Box::new(HexaMaze2dGenerator::new(
+            RegionDivisionMazeGenerator::new(input.room_max_cells_n),
+        ))),
+        other_algorithm => {
+            Err(gene::Error::NotSupportMask(other_algorithm.name().to_string()).into())
+        }
+    }
+}
+
+fn generator_info(input: &HexaMazeCreateArgs) -> GeneratorInfo {
+    let algorithm = &input.algorithm;
+    let info = GeneratorInfo::new(algorithm.name());
+    match algorithm {
+        HexaMazeAlgorithm { btree: true, .. }
+        | HexaMazeAlgorithm {
+            sidewinder: true, ..
+        } => info.with_param("con_dir", format!("{:?}", input.con_dir.unwrap())),
+        HexaMazeAlgorithm {
+            weighted_prim: true,
+            ..
+        }
+        | HexaMazeAlgorithm {
+            weighted_kruskal: true,
+            ..
+        } => input.weight.with_params(info),
+        HexaMazeAlgorithm {
+            growing_tree: true, ..
+        } => info.with_param("strategy", &input.growing_tree_strategy),
+        HexaMazeAlgorithm {
+            recursive_division: true,
+            ..
+        } => info.with_param("room_max_cells_n", input.room_max_cells_n),
+        _ => info,
     }
 }
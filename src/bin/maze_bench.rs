@@ -0,0 +1,379 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use anyhow::Error as AnyError;
+use clap::{ArgGroup, Parser, Subcommand};
+use try_mazes::{
+    cli,
+    gene::{
+        self, AldousBroderMazeGenerator, BTreeMazeGenerator, DiagonalDirection, EllerMazeGenerator,
+        GrowingTreeMazeGenerator, GrowingTreeStrategy, HuntAndKillMazeGenerator,
+        KruskalMazeGenerator, LayerMazeGenerator, Maze2dGenerator, OriginShiftMazeGenerator,
+        PrimMazeGenerator, RandomWeight, RecursiveBacktrackerMazeGenerator,
+        RegionDivisionMazeGenerator, SidewinderMazeGenerator, WeightedKruskalMazeGenerator,
+        WeightedPrimMazeGenerator, WilsonMazeGenerator, rect::RecursiveDivisionMazeGenerator,
+    },
+    maze::{
+        Grid2d, LayerGrid, NoMask, WithMask,
+        circ::CircGrid,
+        hexa::HexaGrid,
+        rect::{RectGrid, RectMask},
+        tri::TriGrid,
+    },
+    stats::{MazeStats, SolutionStats},
+};
+
+const DEF_RUNS_N: usize = 10;
+const MAX_PASSAGE_WEIGHT: u32 = 100;
+
+type Generate<G> = Box<dyn Fn(&mut G) -> Result<(), gene::Error>>;
+
+fn main() -> Result<(), AnyError> {
+    let bench_input = BenchInputArgs::parse();
+    let runs_n = bench_input.runs_n;
+    let records = match &bench_input.grid {
+        BenchGrid::Rect {
+            shape: BenchRectShape::Size { width, height },
+        } => {
+            let mut generators = general_generators();
+            // Recursive division on the rectangular grid without mask divides by straight walls.
+            generators.push((
+                "Recursive Division",
+                Box::new(|grid: &mut RectGrid<NoMask>| {
+                    RecursiveDivisionMazeGenerator::new(1, 1).divide_grid(grid)
+                }),
+            ));
+            bench(
+                &RectGrid::<NoMask>::new(*width, *height),
+                generators,
+                runs_n,
+            )
+        }
+        BenchGrid::Rect { shape } => {
+            let grid = RectGrid::<WithMask>::new(&shape.mask()?);
+            bench(&grid, with_region_division(general_generators()), runs_n)
+        }
+        BenchGrid::Hexa {
+            shape: BenchRectShape::Size { width, height },
+        } => bench(
+            &HexaGrid::<NoMask>::new(*width, *height),
+            with_region_division(general_generators()),
+            runs_n,
+        ),
+        BenchGrid::Hexa { shape } => bench(
+            &HexaGrid::<WithMask>::new(&shape.mask()?),
+            with_region_division(general_generators()),
+            runs_n,
+        ),
+        BenchGrid::Tri {
+            shape: BenchRectShape::Size { width, height },
+        } => bench(
+            &TriGrid::new(*width, *height),
+            with_region_division(general_generators()),
+            runs_n,
+        ),
+        BenchGrid::Tri { shape } => bench(
+            &TriGrid::with_mask(&shape.mask()?),
+            with_region_division(general_generators()),
+            runs_n,
+        ),
+        BenchGrid::Circ { rings_n } => bench(
+            &CircGrid::new(*rings_n),
+            with_region_division(general_generators()),
+            runs_n,
+        ),
+    }?;
+
+    if bench_input.csv {
+        print_csv(&records);
+    } else {
+        print_table(&records);
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Parser)]
+#[command(flatten_help = true)]
+struct BenchInputArgs {
+    /// Times to generate maze by each algorithm
+    #[arg(short, long, default_value_t = DEF_RUNS_N)]
+    runs_n: usize,
+    /// Print report in CSV format instead of a table
+    #[arg(long)]
+    csv: bool,
+    /// Grid to generate mazes on
+    #[command(subcommand)]
+    grid: BenchGrid,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum BenchGrid {
+    /// Rectangular grid
+    Rect {
+        #[command(subcommand)]
+        shape: BenchRectShape,
+    },
+    /// Hexagonal grid
+    Hexa {
+        #[command(subcommand)]
+        shape: BenchRectShape,
+    },
+    /// Triangular grid
+    Tri {
+        #[command(subcommand)]
+        shape: BenchRectShape,
+    },
+    /// Circular grid
+    Circ {
+        /// Number of rings
+        rings_n: usize,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum BenchRectShape {
+    Size {
+        /// column count of maze
+        width: usize,
+        /// row count of maze
+        height: usize,
+    },
+    #[command(group(ArgGroup::new("mask type").required(true).multiple(false)))]
+    Mask {
+        /// Using text mask(x or X is for not cell, other characters are for cell)
+        #[arg(long, group = "mask type")]
+        text: bool,
+        /// Using image mask(black pixel is for not cell, other colors are for cell)
+        #[arg(long, group = "mask type")]
+        image: bool,
+        /// Path of mask file
+        path: PathBuf,
+    },
+}
+
+impl BenchRectShape {
+    fn mask(&self) -> Result<RectMask, AnyError> {
+        match self {
+            BenchRectShape::Mask {
+                text: true, path, ..
+            } => RectMask::try_from_text_file(path),
+            BenchRectShape::Mask {
+                image: true, path, ..
+            } => RectMask::try_from_image_file(path),
+            other_shape => Err(cli::Error::InvalidArgs(format!("{:?}", other_shape)).into()),
+        }
+    }
+}
+
+/// Generation times and metrics of mazes generated by one algorithm.
+#[derive(Debug, Clone)]
+struct BenchRecord {
+    algorithm: &'static str,
+    times: Vec<Duration>,
+    stats: Vec<MazeStats>,
+}
+
+impl BenchRecord {
+    fn time_ms(&self) -> (f64, f64) {
+        mean_and_std_dev(self.times.iter().map(|time| time.as_secs_f64() * 1000.0))
+    }
+
+    fn stats_mean<F: Fn(&MazeStats) -> f64>(&self, metric: F) -> f64 {
+        mean_and_std_dev(self.stats.iter().map(metric)).0
+    }
+
+    fn columns(&self) -> [String; 9] {
+        let (time_mean, time_std_dev) = self.time_ms();
+        let solution_metric = |metric: fn(&SolutionStats) -> f64| {
+            move |stats: &MazeStats| stats.solution.as_ref().map(metric).unwrap_or(0.0)
+        };
+        [
+            self.algorithm.to_string(),
+            self.times.len().to_string(),
+            format!("{:.3}", time_mean),
+            format!("{:.3}", time_std_dev),
+            format!(
+                "{:.3}",
+                self.stats_mean(|stats| f64::from(stats.dead_end_ratio))
+            ),
+            format!(
+                "{:.1}",
+                self.stats_mean(solution_metric(|solution| solution.length as f64))
+            ),
+            format!(
+                "{:.3}",
+                self.stats_mean(solution_metric(|solution| f64::from(solution.directness)))
+            ),
+            format!(
+                "{:.2}",
+                self.stats_mean(|stats| f64::from(stats.average_corridor_length))
+            ),
+            format!(
+                "{:.3}",
+                self.stats_mean(|stats| f64::from(stats.river_factor))
+            ),
+        ]
+    }
+}
+
+const REPORT_HEADERS: [&str; 9] = [
+    "algorithm",
+    "runs",
+    "time_mean_ms",
+    "time_std_dev_ms",
+    "dead_end_ratio",
+    "longest_path",
+    "directness",
+    "corridor_length",
+    "river_factor",
+];
+
+/// Generators can be applied to any grid consisting of layers.
+fn general_generators<G: LayerGrid + 'static>() -> Vec<(&'static str, Generate<G>)> {
+    fn general<G: LayerGrid, M: Maze2dGenerator + 'static>(generator: M) -> Generate<G> {
+        Box::new(move |grid: &mut G| generator.generate_2d(grid))
+    }
+
+    fn layer<G: LayerGrid, M: LayerMazeGenerator + 'static>(generator: M) -> Generate<G> {
+        Box::new(move |grid: &mut G| generator.generate_layer(grid))
+    }
+
+    vec![
+        ("Aldous-Broder", general(AldousBroderMazeGenerator)),
+        (
+            "Binary Tree",
+            layer(BTreeMazeGenerator::new(DiagonalDirection::Northeast)),
+        ),
+        ("Eller's", layer(EllerMazeGenerator)),
+        (
+            "Growing Tree",
+            general(GrowingTreeMazeGenerator::new(GrowingTreeStrategy::default())),
+        ),
+        ("Hunt-and-Kill", general(HuntAndKillMazeGenerator)),
+        ("Kruskal's", general(KruskalMazeGenerator)),
+        ("Origin Shift", general(OriginShiftMazeGenerator)),
+        ("Prim's", general(PrimMazeGenerator)),
+        (
+            "Recursive Backtracker",
+            general(RecursiveBacktrackerMazeGenerator),
+        ),
+        (
+            "Sidewinder",
+            layer(SidewinderMazeGenerator::new(DiagonalDirection::Northeast)),
+        ),
+        (
+            "Weighted Kruskal's",
+            general(WeightedKruskalMazeGenerator::new(RandomWeight::uniform(
+                MAX_PASSAGE_WEIGHT,
+            ))),
+        ),
+        (
+            "Weighted Prim's",
+            general(WeightedPrimMazeGenerator::new(RandomWeight::uniform(
+                MAX_PASSAGE_WEIGHT,
+            ))),
+        ),
+        ("Wilson's", general(WilsonMazeGenerator)),
+    ]
+}
+
+/// Recursive division on grids other than the rectangular one without mask divides by regions.
+fn with_region_division<G: LayerGrid + 'static>(
+    mut generators: Vec<(&'static str, Generate<G>)>,
+) -> Vec<(&'static str, Generate<G>)> {
+    let generator = RegionDivisionMazeGenerator::new(1);
+    generators.push((
+        "Recursive Division",
+        Box::new(move |grid: &mut G| generator.generate_2d(grid)),
+    ));
+    generators
+}
+
+fn bench<G: LayerGrid + Clone>(
+    grid: &G,
+    mut generators: Vec<(&'static str, Generate<G>)>,
+    runs_n: usize,
+) -> Result<Vec<BenchRecord>, gene::Error> {
+    generators.sort_by_key(|(algorithm, _)| *algorithm);
+    generators
+        .iter()
+        .map(|(algorithm, generate)| {
+            let mut record = BenchRecord {
+                algorithm,
+                times: Vec::with_capacity(runs_n),
+                stats: Vec::with_capacity(runs_n),
+            };
+            for _ in 0..runs_n {
+                let mut maze_grid = grid.clone();
+                let start_time = Instant::now();
+                generate(&mut maze_grid)?;
+                record.times.push(start_time.elapsed());
+                record.stats.push(MazeStats::new(&maze_grid as &dyn Grid2d));
+            }
+
+            Ok(record)
+        })
+        .collect()
+}
+
+fn mean_and_std_dev<I: Iterator<Item = f64>>(values: I) -> (f64, f64) {
+    let values: Vec<_> = values.collect();
+    if values.is_empty() {
+        return (0.0, 0.0);
+    }
+
+    let values_n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / values_n;
+    let variance = values
+        .iter()
+        .map(|value| (value - mean).powi(2))
+        .sum::<f64>()
+        / values_n;
+    (mean, variance.sqrt())
+}
+
+fn print_csv(records: &[BenchRecord]) {
+    println!("{}", REPORT_HEADERS.join(","));
+    for record in records {
+        println!("{}", record.columns().join(","));
+    }
+}
+
+fn print_table(records: &[BenchRecord]) {
+    let rows: Vec<_> = records.iter().map(BenchRecord::columns).collect();
+    let widths: Vec<_> = REPORT_HEADERS
+        .iter()
+        .enumerate()
+        .map(|(ind, header)| {
+            rows.iter()
+                .map(|row| row[ind].len())
+                .chain([header.len()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let format_row = |cells: &mut dyn Iterator<Item = &str>| {
+        let cells: Vec<_> = cells
+            .zip(widths.iter())
+            .enumerate()
+            .map(|(ind, (cell, width))| {
+                // Left align the algorithm names, and right align the numbers.
+                if ind == 0 {
+                    format!("{:<width$}", cell, width = width)
+                } else {
+                    format!("{:>width$}", cell, width = width)
+                }
+            })
+            .collect();
+        format!("| {} |", cells.join(" | "))
+    };
+    println!("{}", format_row(&mut REPORT_HEADERS.iter().copied()));
+    let separators: Vec<_> = widths.iter().map(|width| "-".repeat(*width)).collect();
+    println!("{}", format_row(&mut separators.iter().map(String::as_str)));
+    for row in rows.iter() {
+        println!("{}", format_row(&mut row.iter().map(String::as_str)));
+    }
+}
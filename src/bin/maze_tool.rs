@@ -0,0 +1,156 @@
+use std::path::PathBuf;
+
+use anyhow::Error as AnyError;
+use clap::{Parser, Subcommand};
+use try_mazes::{
+    cli::{self, Error},
+    graph::{GraphFormat, GraphSettings, MazeGraph},
+    maze::{Position2d, any::AnyMaze},
+    show::{
+        MazePicture, SavePictureFormat,
+        any::AnyMazePainter,
+        rect::{AsciiBoxCharset, UnicodeBoxCharset},
+    },
+    validate::ValidationReport,
+};
+
+const DEF_CELL_WIDTH: u16 = 40;
+const DEF_WALL_THICKNESS: u16 = 5;
+const DEF_SHOW_WND_WIDTH: usize = 800;
+const DEF_SHOW_WND_HEIGHT: usize = 600;
+
+fn main() -> Result<(), AnyError> {
+    let tool_input = ToolInputArgs::parse();
+    let saved = AnyMaze::load(&tool_input.load_path)?;
+    let (header, maze) = (&saved.header, &saved.maze);
+    let painter = AnyMazePainter::new(maze, tool_input.cell_width, tool_input.wall_thickness);
+    let picture = MazePicture::new(&painter);
+    match tool_input.action {
+        ToolAction::Info => println!("{}", serde_json::to_string_pretty(header)?),
+        ToolAction::Show {
+            wnd_width,
+            wnd_height,
+        } => picture.show(wnd_width, wnd_height)?,
+        ToolAction::Text { unicode } => {
+            let text = if unicode {
+                maze.text(UnicodeBoxCharset)
+            } else {
+                maze.text(AsciiBoxCharset)
+            };
+            println!(
+                "{}",
+                text.ok_or_else(|| Error::NotSupportText(maze.kind().to_string()))?
+            );
+        }
+        ToolAction::Save { path, format } => picture.save(path, format)?,
+        ToolAction::Graph {
+            path,
+            format,
+            settings,
+        } => MazeGraph::new(maze, &settings, header.start).save(path, format)?,
+        ToolAction::Solve { json } => {
+            // Solve between the endpoints in file, or the ends of the longest path if they aren't set.
+            let (start, goal) = match (header.start, header.goal) {
+                (Some(start), Some(goal)) => (start, goal),
+                _ => maze
+                    .stats()
+                    .solution
+                    .map(|solution| (solution.start, solution.goal))
+                    .ok_or(Error::NoSolution)?,
+            };
+            let path = maze.solve(&start, &goal).ok_or(Error::NoSolution)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&path)?);
+            } else {
+                let path: Vec<_> = path
+                    .iter()
+                    .map(|Position2d(row, col)| format!("({}, {})", row, col))
+                    .collect();
+                println!("Solution of {} steps:", path.len() - 1);
+                println!("{}", path.join(" -> "));
+            }
+        }
+        ToolAction::Stats { json } => cli::print_stats(&maze.stats(), json)?,
+        ToolAction::Validate { json } => {
+            cli::print_validation(&ValidationReport::new(maze.grid()), json)?
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Parser)]
+#[command(name = "MazeTool", version)]
+#[command(about = "Open maze of any kind saved by demos, and show, solve or analyze it.", long_about = None)]
+#[command(flatten_help = true)]
+struct ToolInputArgs {
+    /// Width of cell space(the ring interval of circular maze, the cell height of hexagonal and triangular maze, the unit width of Voronoi maze)
+    #[arg(short, long, default_value_t = DEF_CELL_WIDTH)]
+    cell_width: u16,
+    /// Thickness of the maze wall(the stroke)
+    #[arg(short, long, default_value_t = DEF_WALL_THICKNESS)]
+    wall_thickness: u16,
+    /// Path to load maze(saved as json or binary format before)
+    load_path: PathBuf,
+    /// What to do with loaded maze
+    #[command(subcommand)]
+    action: ToolAction,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum ToolAction {
+    /// Print header of maze file, like the kind, the algorithm and the seed
+    Info,
+    /// Show maze picture in GUI
+    Show {
+        /// Width of window for showing maze
+        #[arg(long, default_value_t = DEF_SHOW_WND_WIDTH)]
+        wnd_width: usize,
+        /// Height of window for showing maze
+        #[arg(long, default_value_t = DEF_SHOW_WND_HEIGHT)]
+        wnd_height: usize,
+    },
+    /// Print maze in text(only for rectangular maze)
+    Text {
+        /// Using unicode box characters instead of ascii characters
+        #[arg(long)]
+        unicode: bool,
+    },
+    /// Save maze picture in file
+    Save {
+        /// Path to save maze picture
+        path: PathBuf,
+        /// Format to save maze picture
+        #[arg(short, long, default_value = "png")]
+        format: SavePictureFormat,
+    },
+    /// Export the graph of maze(cells as nodes, passages as edges) for analysis in external tools
+    Graph {
+        /// Path to save graph
+        path: PathBuf,
+        /// Format to save graph
+        #[arg(short, long)]
+        format: GraphFormat,
+        /// Settings of graph
+        #[command(flatten)]
+        settings: GraphSettings,
+    },
+    /// Print the path from start to goal(the ends of the longest path if they aren't set in file)
+    Solve {
+        /// Print in json format
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print metrics of maze, like dead ends, junctions, corridors and the solution
+    Stats {
+        /// Print in json format
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check maze is perfect(every cell is reachable, no loop) and its grid is consistent
+    Validate {
+        /// Print report in json format
+        #[arg(long)]
+        json: bool,
+    },
+}
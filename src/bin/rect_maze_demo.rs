@@ -1,66 +1,118 @@
-use std::{fmt::Display, fs::File, io::Write, path::PathBuf};
+use std::{fmt::Display, fs::File, io::Write, path::PathBuf, time::Duration};
 
 use anyhow::Error as AnyError;
-use clap::{Args, Parser, Subcommand, command};
+use clap::{ArgGroup, Args, Parser, Subcommand};
 
 use try_mazes::{
-    cli::{self, Error},
+    cli::{self, GeneralImagePassageWeightArgs},
+    edit::{MazeEditor, rect::RectEditLayout},
+    file::{GeneratorInfo, MazeHeader, MazeKind, SavedMaze},
     gene::{
-        AldousBroderMazeGenerator, EllerMazeGenerator, GrowingTreeMazeGenerator,
-        HuntAndKillMazeGenerator, KruskalMazeGenerator, PrimMazeGenerator,
-        RecursiveBacktrackerMazeGenerator, WilsonMazeGenerator,
+        self, AldousBroderMazeGenerator, BTreeMazeGenerator, DiagonalDirection, EllerMazeGenerator,
+        GrowingTreeMazeGenerator, GrowingTreeStrategy, HuntAndKillMazeGenerator,
+        KruskalMazeGenerator, OriginShift, OriginShiftMazeGenerator, PassageWeight,
+        PrimMazeGenerator, RecursiveBacktrackerMazeGenerator, RegionDivisionMazeGenerator,
+        SidewinderMazeGenerator, WeightedKruskalMazeGenerator, WeightedPrimMazeGenerator,
+        WilsonMazeGenerator,
         rect::{
-            BTreeMazeGenerator, DiagonalDirection, RectLayerMazeGenerator, RectMaze2dGenerator,
-            RectMazeGenerator, RecursiveDivisionMazeGenerator, SidewinderMazeGenerator,
+            RectLayerMazeGenerator, RectMaze2dGenerator, RectMazeGenerator,
+            RecursiveDivisionMazeGenerator,
         },
     },
+    graph::{GraphFormat, GraphSettings, MazeGraph},
     maze::{
         NoMask, WithMask,
-        rect::{RectGrid, RectMask},
+        rect::{RectGrid, RectMask, RectMaze},
     },
+    mesh::{MazeMesh, MeshFormat, MeshSettings, rect::RectMazeMesher},
     show::{
-        MazePicture, SavePictureFormat,
+        self, MazePaint, MazePicture, SavePictureFormat,
         rect::{AsciiBoxCharset, RectMazeCmdDisplay, RectMazePainter, UnicodeBoxCharset},
     },
+    stats::MazeStats,
+    tile::{TileMap, TileMapFormat, TileSettings},
+    validate::ValidationReport,
 };
 
 const DEF_WALL_THICKNESS: usize = 5;
 const DEF_CELL_WIDTH: usize = 50;
 const DEF_SHOW_WND_WIDTH: usize = 800;
+const DEF_SHIFTS_N_PER_FRAME: usize = 1;
+const DEF_FRAME_INTERVAL_MS: u64 = 100;
 const DEF_SHOW_WND_HEIGHT: usize = 600;
 
 fn main() -> Result<(), AnyError> {
     let maze_input = RectMazeInputArgs::parse();
-    let maze = match &maze_input.action {
-        DemoAction::Create(create_args) => match &create_args.shape {
-            RectMazeShape::Size(MazeSizeArgs { width, height, .. }) => {
-                let grid = RectGrid::<NoMask>::new(*width, *height);
-                let generator = make_generator_no_mask(create_args);
-                generator.generate(grid)
-            }
-            RectMazeShape::Mask(mask_args) => {
-                let grid = match mask_args {
-                    MazeMaskArgs {
-                        text: true,
-                        path: Some(mask_path),
-                        ..
-                    } => RectGrid::<WithMask>::new(&RectMask::try_from_text_file(mask_path)?),
-                    MazeMaskArgs {
-                        image: true,
-                        path: Some(mask_path),
-                        ..
-                    } => RectGrid::<WithMask>::new(&RectMask::try_from_image_file(mask_path)?),
-                    other_shape => unreachable!(
-                        "Given invalid shape information({:?}), should be refused by clap.",
-                        other_shape
-                    ),
-                };
+    let (header, maze) = match &maze_input.action {
+        DemoAction::Create(create_args) => {
+            let seed = cli::seed_rng(create_args.seed);
+            let maze = match &create_args.shape {
+                RectMazeShape::Size(MazeSizeArgs { width, height, .. }) => {
+                    let grid = RectGrid::<NoMask>::new(*width, *height);
+                    let generator = make_generator_no_mask(
+                        create_args,
+                        create_args.weight.passage_weight(&grid)?,
+                    )?;
+                    generator.generate(grid)?
+                }
+                RectMazeShape::Mask(mask_args) => {
+                    let grid = match mask_args {
+                        MazeMaskArgs {
+                            text: true,
+                            path: Some(mask_path),
+                            ..
+                        } => RectGrid::<WithMask>::new(&RectMask::try_from_text_file(mask_path)?),
+                        MazeMaskArgs {
+                            image: true,
+                            path: Some(mask_path),
+                            ..
+                        } => RectGrid::<WithMask>::new(&RectMask::try_from_image_file(mask_path)?),
+                        other_shape => {
+                            return Err(
+                                cli::Error::InvalidArgs(format!("{:?}", other_shape)).into()
+                            );
+                        }
+                    };
 
-                let generator = make_generator_with_mask(create_args)?;
-                generator.generate(grid)
-            }
-        },
-        DemoAction::Load(RectMazeLoadArgs { load_path, .. }) => cli::load_from_json(load_path)?,
+                    let generator = make_generator_with_mask(
+                        create_args,
+                        create_args.weight.passage_weight(&grid)?,
+                    )?;
+                    generator.generate(grid)?
+                }
+            };
+            let header =
+                MazeHeader::new(MazeKind::Rect).with_generator(generator_info(create_args), seed);
+            (header, maze)
+        }
+        DemoAction::Load(RectMazeLoadArgs { load_path, .. }) => {
+            let SavedMaze { header, maze } = cli::load_maze_file(load_path)?;
+            (header, maze)
+        }
+        DemoAction::ImportCode(RectMazeCodeArgs { code, .. }) => {
+            let SavedMaze { header, maze } = SavedMaze::from_code(code)?;
+            (header, maze)
+        }
+        DemoAction::ImportTiles(RectMazeTilesArgs {
+            tiles_path,
+            settings,
+            ..
+        }) => {
+            let maze = TileMap::try_from_grid_file(tiles_path, settings)?.to_rect_maze()?;
+            (MazeHeader::new(MazeKind::Rect), maze)
+        }
+        DemoAction::ImportDrawing(RectMazeDrawingArgs {
+            format,
+            drawing_path,
+            ..
+        }) => {
+            let maze = if format.text {
+                RectMaze::try_from_text_file(drawing_path)?
+            } else {
+                RectMaze::try_from_image_file(drawing_path)?
+            };
+            (MazeHeader::new(MazeKind::Rect), maze)
+        }
     };
 
     match maze_input.action {
@@ -72,7 +124,10 @@ fn main() -> Result<(), AnyError> {
             shape: RectMazeShape::Size(MazeSizeArgs { action, .. }),
             ..
         })
-        | DemoAction::Load(RectMazeLoadArgs { action, .. }) => match action {
+        | DemoAction::Load(RectMazeLoadArgs { action, .. })
+        | DemoAction::ImportCode(RectMazeCodeArgs { action, .. })
+        | DemoAction::ImportTiles(RectMazeTilesArgs { action, .. })
+        | DemoAction::ImportDrawing(RectMazeDrawingArgs { action, .. }) => match action {
             RectMazeAction::Show(ShowArgs { ascii: true, .. }) => {
                 println!("{}", RectMazeCmdDisplay(&maze, AsciiBoxCharset))
             }
@@ -111,7 +166,14 @@ fn main() -> Result<(), AnyError> {
             }
             RectMazeAction::Save(SaveArgs {
                 json: true, path, ..
-            }) => cli::save_to_json(path, &maze)?,
+            }) => cli::save_to_json(path, &header, &maze)?,
+            RectMazeAction::Save(SaveArgs {
+                binary: true,
+                compress,
+                path,
+                ..
+            }) => cli::save_to_binary(path, &header, &maze, compress)?,
+            RectMazeAction::ExportCode => cli::print_code(&header, &maze)?,
             RectMazeAction::Save(SaveArgs {
                 picture: true,
                 pic_format: Some(format),
@@ -127,9 +189,79 @@ fn main() -> Result<(), AnyError> {
                 let picture = MazePicture::new(&painter);
                 picture.save(path, format)?
             }
-            _ => unreachable!(
-                "Given unknown action or missing arguments of action, should be checked by clap."
-            ),
+            RectMazeAction::Mesh(MeshArgs {
+                path,
+                format,
+                settings,
+            }) => RectMazeMesher::new(&maze, settings)
+                .mesh()?
+                .save(path, format)?,
+            RectMazeAction::Graph(GraphArgs {
+                path,
+                format,
+                settings,
+            }) => MazeGraph::new(&maze, &settings, header.start).save(path, format)?,
+            RectMazeAction::Tiles(TileArgs {
+                path,
+                format,
+                settings,
+            }) => TileMap::new(&maze, &settings, header.start, header.goal).save(path, format)?,
+            RectMazeAction::Stats(StatsArgs { json }) => {
+                cli::print_stats(&MazeStats::new_rect(&maze), json)?
+            }
+            RectMazeAction::Validate(ValidateArgs { json }) => {
+                cli::print_validation(&ValidationReport::new(maze.grid()), json)?
+            }
+            RectMazeAction::Edit(EditArgs { path, pic_settings }) => {
+                let layout = RectEditLayout::new(
+                    &maze,
+                    pic_settings.wall_thickness,
+                    pic_settings.cell_width,
+                );
+                MazeEditor::new(maze)
+                    .with_endpoints(header.start, header.goal)
+                    .run(
+                        &layout,
+                        |maze| {
+                            RectMazePainter::new(
+                                maze,
+                                pic_settings.wall_thickness,
+                                pic_settings.cell_width,
+                            )
+                            .paint()
+                        },
+                        path,
+                        &header,
+                    )?;
+            }
+            RectMazeAction::Shift(ShiftArgs {
+                shifts_n,
+                frame_interval_ms,
+                gui_wnd_width,
+                gui_wnd_height,
+                pic_settings,
+            }) => {
+                let mut maze = maze;
+                if let Some(mut origin_shift) = OriginShift::new(maze.grid_mut()) {
+                    show::show_animation(
+                        gui_wnd_width,
+                        gui_wnd_height,
+                        Duration::from_millis(frame_interval_ms),
+                        || {
+                            origin_shift.shift(maze.grid_mut(), shifts_n);
+                            RectMazePainter::new(
+                                &maze,
+                                pic_settings.wall_thickness,
+                                pic_settings.cell_width,
+                            )
+                            .paint()
+                        },
+                    )?
+                }
+            }
+            other_action => {
+                return Err(cli::Error::InvalidArgs(format!("{:?}", other_action)).into());
+            }
         },
     }
 
@@ -149,22 +281,75 @@ struct RectMazeInputArgs {
 enum DemoAction {
     Create(RectMazeCreateArgs),
     Load(RectMazeLoadArgs),
+    ImportCode(RectMazeCodeArgs),
+    ImportTiles(RectMazeTilesArgs),
+    ImportDrawing(RectMazeDrawingArgs),
 }
 
 #[derive(Debug, Clone, Args)]
 struct RectMazeLoadArgs {
-    /// Path to load rectangular maze(saved as json format before)
+    /// Path to load rectangular maze(saved as json or binary format before)
     load_path: PathBuf,
     /// What to do with loaded maze
     #[command(subcommand)]
     action: RectMazeAction,
 }
 
+#[derive(Debug, Clone, Args)]
+struct RectMazeCodeArgs {
+    /// Code of rectangular maze(printed by export-code before)
+    code: String,
+    /// What to do with imported maze
+    #[command(subcommand)]
+    action: RectMazeAction,
+}
+
+#[derive(Debug, Clone, Args)]
+struct RectMazeTilesArgs {
+    /// Path to tile grid of rectangular maze(0 for the floor, 1 for the wall, exported by tiles before)
+    tiles_path: PathBuf,
+    /// Widths of the corridor and the wall in tile grid
+    #[command(flatten)]
+    settings: TileSettings,
+    /// What to do with imported maze
+    #[command(subcommand)]
+    action: RectMazeAction,
+}
+
+#[derive(Debug, Clone, Args)]
+struct RectMazeDrawingArgs {
+    /// Format of drawing
+    #[command(flatten)]
+    format: DrawingFormat,
+    /// Path to drawing of rectangular maze
+    drawing_path: PathBuf,
+    /// What to do with imported maze
+    #[command(subcommand)]
+    action: RectMazeAction,
+}
+
+#[derive(Debug, Clone, Args)]
+#[group(required = true, multiple = false)]
+struct DrawingFormat {
+    /// Maze drawn in ascii or unicode box characters(shown or saved in text before)
+    #[arg(long)]
+    text: bool,
+    /// Maze drawn in block bitmap, every cell and wall is a pixel, and the dark pixel is the wall
+    #[arg(long)]
+    image: bool,
+}
+
 #[derive(Debug, Clone, Args)]
 struct RectMazeCreateArgs {
     /// Generation algorithm
     #[command(flatten)]
     algorithm: RectMazeGenAlgorithm,
+    /// Passage weights, used by weighted algorithms
+    #[command(flatten)]
+    weight: GeneralImagePassageWeightArgs,
+    /// Strategy to select active cell, used by growing tree algorithm, e.g. newest, middle or "newest:3,random:1"
+    #[arg(long, default_value_t)]
+    growing_tree_strategy: GrowingTreeStrategy,
     /// Candidate directions to connect
     #[arg(short, long, group = "connect direction")]
     con_dir: Option<DiagonalDirection>,
@@ -174,6 +359,9 @@ struct RectMazeCreateArgs {
     /// Max columns number of room, used by recursive division algorithm
     #[arg(long, default_value_t = 1)]
     room_max_cols_n: usize,
+    /// Seed of random generation, the same seed with the same arguments makes the same maze(random if not given)
+    #[arg(long)]
+    seed: Option<u64>,
     /// What to do with generated maze
     #[command(subcommand)]
     shape: RectMazeShape,
@@ -206,6 +394,12 @@ struct RectMazeGenAlgorithm {
     /// Using Prim's algorithm
     #[arg(long)]
     prim: bool,
+    /// Using weighted Prim's algorithm
+    #[arg(long)]
+    weighted_prim: bool,
+    /// Using weighted Kruskal's algorithm
+    #[arg(long)]
+    weighted_kruskal: bool,
     /// Using growing tree algorithm
     #[arg(long)]
     growing_tree: bool,
@@ -215,6 +409,33 @@ struct RectMazeGenAlgorithm {
     /// Using recursive division algorithm
     #[arg(long)]
     recursive_division: bool,
+    /// Using origin shift algorithm
+    #[arg(long)]
+    origin_shift: bool,
+}
+
+impl RectMazeGenAlgorithm {
+    fn name(&self) -> &'static str {
+        [
+            (self.btree, "binary tree"),
+            (self.sidewinder, "sidewinder"),
+            (self.aldous_broder, "Aldous-Broder"),
+            (self.wilson, "Wilson's"),
+            (self.hunt_and_kill, "Hunt-and-Kill"),
+            (self.recursive_backtracker, "recursive backtracker"),
+            (self.kruskal, "Kruskal's"),
+            (self.prim, "Prim's"),
+            (self.weighted_prim, "weighted Prim's"),
+            (self.weighted_kruskal, "weighted Kruskal's"),
+            (self.growing_tree, "growing tree"),
+            (self.eller, "Eller's"),
+            (self.recursive_division, "recursive division"),
+            (self.origin_shift, "origin shift"),
+        ]
+        .into_iter()
+        .find_map(|(is_chosen, name)| is_chosen.then_some(name))
+        .unwrap_or_default()
+    }
 }
 
 #[derive(Debug, Clone, Subcommand)]
@@ -223,6 +444,22 @@ enum RectMazeAction {
     Show(ShowArgs),
     /// Save maze by given settings
     Save(SaveArgs),
+    /// Export the 3D model of maze, walls are extruded on a base plate
+    Mesh(MeshArgs),
+    /// Export the graph of maze(cells as nodes, passages as edges) for analysis in external tools
+    Graph(GraphArgs),
+    /// Export the tile map of maze(Tiled map or plain grid of floors and walls) for game levels
+    Tiles(TileArgs),
+    /// Print the short url-safe code of maze(with its start and goal) to share, which can be imported by import-code
+    ExportCode,
+    /// Show maze changing continuously by origin shift algorithm in GUI
+    Shift(ShiftArgs),
+    /// Edit maze in GUI, click walls to toggle them, click cells to set start and goal
+    Edit(EditArgs),
+    /// Print metrics of maze, like dead ends, junctions, corridors and the solution
+    Stats(StatsArgs),
+    /// Check maze is perfect(every cell is reachable, no loop) and its grid is consistent, useful for loaded mazes
+    Validate(ValidateArgs),
 }
 
 #[derive(Debug, Clone, Args)]
@@ -248,6 +485,7 @@ struct ShowArgs {
 }
 
 #[derive(Debug, Clone, Args)]
+#[command(group(ArgGroup::new("save category").required(true).multiple(false)))]
 struct SaveArgs {
     /// Using ascii characters to paint maze
     #[arg(long, group = "save category")]
@@ -258,6 +496,12 @@ struct SaveArgs {
     /// Using json format to save maze
     #[arg(long, group = "save category")]
     json: bool,
+    /// Using compact binary format to save maze, the walls are packed in bits
+    #[arg(long, group = "save category", group = "binary format")]
+    binary: bool,
+    /// Compress the binary format by deflate
+    #[arg(long, requires = "binary format")]
+    compress: bool,
     /// Using graphics to paint maze
     #[arg(long, group = "save category", requires = "picture format")]
     picture: bool,
@@ -272,6 +516,88 @@ struct SaveArgs {
     pic_settings: PictureSettings,
 }
 
+#[derive(Debug, Clone, Args)]
+struct StatsArgs {
+    /// Print in json format
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+struct ValidateArgs {
+    /// Print report in json format
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Clone, Args)]
+struct EditArgs {
+    /// Path to save edited maze as json(by Ctrl+S)
+    #[arg(long = "save-path")]
+    path: PathBuf,
+    /// Settings to paint maze picture
+    #[command(flatten)]
+    pic_settings: PictureSettings,
+}
+
+#[derive(Debug, Clone, Args)]
+struct ShiftArgs {
+    /// Count of origin shifts between two frames
+    #[arg(long, default_value_t = DEF_SHIFTS_N_PER_FRAME)]
+    shifts_n: usize,
+    /// Interval between two frames in milliseconds
+    #[arg(long, default_value_t = DEF_FRAME_INTERVAL_MS)]
+    frame_interval_ms: u64,
+    /// Width of window for showing maze
+    #[arg(long, default_value_t = DEF_SHOW_WND_WIDTH)]
+    gui_wnd_width: usize,
+    /// Height of window for showing maze
+    #[arg(long, default_value_t = DEF_SHOW_WND_HEIGHT)]
+    gui_wnd_height: usize,
+    /// Settings to paint maze picture
+    #[command(flatten)]
+    pic_settings: PictureSettings,
+}
+
+#[derive(Debug, Clone, Args)]
+struct GraphArgs {
+    /// Graph file format to save
+    #[arg(long)]
+    format: GraphFormat,
+    /// Path to save
+    #[arg(long = "save-path")]
+    path: PathBuf,
+    /// Settings of graph
+    #[command(flatten)]
+    settings: GraphSettings,
+}
+
+#[derive(Debug, Clone, Args)]
+struct TileArgs {
+    /// Tile map file format to save
+    #[arg(long)]
+    format: TileMapFormat,
+    /// Path to save
+    #[arg(long = "save-path")]
+    path: PathBuf,
+    /// Settings of tile map
+    #[command(flatten)]
+    settings: TileSettings,
+}
+
+#[derive(Debug, Clone, Args)]
+struct MeshArgs {
+    /// Model file format to save
+    #[arg(long)]
+    format: MeshFormat,
+    /// Path to save
+    #[arg(long = "save-path")]
+    path: PathBuf,
+    /// Settings to build maze model
+    #[command(flatten)]
+    settings: MeshSettings,
+}
+
 #[derive(Debug, Clone, Args)]
 struct PictureSettings {
     /// Width of each cell empty space
@@ -300,6 +626,7 @@ struct MazeSizeArgs {
 }
 
 #[derive(Debug, Clone, Args)]
+#[command(group(ArgGroup::new("mask category").required(true).multiple(false)))]
 struct MazeMaskArgs {
     /// Mask given in text
     #[arg(long, group = "mask category", requires = "mask info")]
@@ -315,57 +642,110 @@ struct MazeMaskArgs {
     action: RectMazeAction,
 }
 
-fn make_generator_no_mask(input: &RectMazeCreateArgs) -> Box<dyn RectMazeGenerator<NoMask>> {
+fn generator_info(input: &RectMazeCreateArgs) -> GeneratorInfo {
+    let algorithm = &input.algorithm;
+    let info = GeneratorInfo::new(algorithm.name());
+    match algorithm {
+        RectMazeGenAlgorithm { btree: true, .. }
+        | RectMazeGenAlgorithm {
+            sidewinder: true, ..
+        } => info.with_param("con_dir", format!("{:?}", input.con_dir.unwrap())),
+        RectMazeGenAlgorithm {
+            weighted_prim: true,
+            ..
+        }
+        | RectMazeGenAlgorithm {
+            weighted_kruskal: true,
+            ..
+        } => input.weight.with_params(info),
+        RectMazeGenAlgorithm {
+            growing_tree: true, ..
+        } => info.with_param("strategy", &input.growing_tree_strategy),
+        RectMazeGenAlgorithm {
+            recursive_division: true,
+            ..
+        } => info
+            .with_param("room_max_rows_n", input.room_max_rows_n)
+            .with_param("room_max_cols_n", input.room_max_cols_n),
+        _ => info,
+    }
+}
+
+fn make_generator_no_mask(
+    input: &RectMazeCreateArgs,
+    weight: Box<dyn PassageWeight>,
+) -> Result<Box<dyn RectMazeGenerator<NoMask>>, AnyError> {
     match input.algorithm {
         RectMazeGenAlgorithm {
             aldous_broder: true,
             ..
-        } => Box::new(RectMaze2dGenerator::new(AldousBroderMazeGenerator)),
+        } => Ok(Box::new(RectMaze2dGenerator::new(
+            AldousBroderMazeGenerator,
+        ))),
         RectMazeGenAlgorithm { wilson: true, .. } => {
-            Box::new(RectMaze2dGenerator::new(WilsonMazeGenerator))
+            Ok(Box::new(RectMaze2dGenerator::new(WilsonMazeGenerator)))
         }
         RectMazeGenAlgorithm {
             hunt_and_kill: true,
             ..
-        } => Box::new(RectMaze2dGenerator::new(HuntAndKillMazeGenerator)),
+        } => Ok(Box::new(RectMaze2dGenerator::new(HuntAndKillMazeGenerator))),
         RectMazeGenAlgorithm {
             recursive_backtracker: true,
             ..
-        } => Box::new(RectMaze2dGenerator::new(RecursiveBacktrackerMazeGenerator)),
+        } => Ok(Box::new(RectMaze2dGenerator::new(
+            RecursiveBacktrackerMazeGenerator,
+        ))),
         RectMazeGenAlgorithm { kruskal: true, .. } => {
-            Box::new(RectMaze2dGenerator::new(KruskalMazeGenerator))
+            Ok(Box::new(RectMaze2dGenerator::new(KruskalMazeGenerator)))
         }
         RectMazeGenAlgorithm { prim: true, .. } => {
-            Box::new(RectMaze2dGenerator::new(PrimMazeGenerator))
+            Ok(Box::new(RectMaze2dGenerator::new(PrimMazeGenerator)))
         }
+        RectMazeGenAlgorithm {
+            weighted_prim: true,
+            ..
+        } => Ok(Box::new(RectMaze2dGenerator::new(
+            WeightedPrimMazeGenerator::new(weight),
+        ))),
+        RectMazeGenAlgorithm {
+            weighted_kruskal: true,
+            ..
+        } => Ok(Box::new(RectMaze2dGenerator::new(
+            WeightedKruskalMazeGenerator::new(weight),
+        ))),
         RectMazeGenAlgorithm {
             growing_tree: true, ..
-        } => Box::new(RectMaze2dGenerator::new(GrowingTreeMazeGenerator)),
+        } => Ok(Box::new(RectMaze2dGenerator::new(
+            GrowingTreeMazeGenerator::new(input.growing_tree_strategy.clone()),
+        ))),
         RectMazeGenAlgorithm { eller: true, .. } => {
-            Box::new(RectLayerMazeGenerator::new(EllerMazeGenerator))
-        }
-        RectMazeGenAlgorithm { btree: true, .. } => {
-            Box::new(BTreeMazeGenerator::new(input.con_dir.unwrap()))
+            Ok(Box::new(RectLayerMazeGenerator::new(EllerMazeGenerator)))
         }
+        RectMazeGenAlgorithm {
+            origin_shift: true, ..
+        } => Ok(Box::new(RectMaze2dGenerator::new(OriginShiftMazeGenerator))),
+        RectMazeGenAlgorithm { btree: true, .. } => Ok(Box::new(RectLayerMazeGenerator::new(
+            BTreeMazeGenerator::new(input.con_dir.unwrap()),
+        ))),
         RectMazeGenAlgorithm {
             sidewinder: true, ..
-        } => Box::new(SidewinderMazeGenerator::new(input.con_dir.unwrap())),
+        } => Ok(Box::new(RectLayerMazeGenerator::new(
+            SidewinderMazeGenerator::new(input.con_dir.unwrap()),
+        ))),
         RectMazeGenAlgorithm {
             recursive_division: true,
             ..
-        } => Box::new(RecursiveDivisionMazeGenerator::new(
+        } => Ok(Box::new(RecursiveDivisionMazeGenerator::new(
             input.room_max_rows_n,
             input.room_max_cols_n,
-        )),
-        other_algorithm => unreachable!(
-            "Given unknown algorithm or missing arguments of algorithm({:?}), should be refused by clap.",
-            other_algorithm
-        ),
+        ))),
+        other_algorithm => Err(cli::Error::InvalidArgs(format!("{:?}", other_algorithm)).into()),
     }
 }
 
 fn make_generator_with_mask(
     input: &RectMazeCreateArgs,
+    weight: Box<dyn PassageWeight>,
 ) -> Result<Box<dyn RectMazeGenerator<WithMask>>, AnyError> {
     match input.algorithm {
         RectMazeGenAlgorithm {
@@ -393,21 +773,46 @@ fn make_generator_with_mask(
         RectMazeGenAlgorithm { prim: true, .. } => {
             Ok(Box::new(RectMaze2dGenerator::new(PrimMazeGenerator)))
         }
+        RectMazeGenAlgorithm {
+            weighted_prim: true,
+            ..
+        } => Ok(Box::new(RectMaze2dGenerator::new(
+            WeightedPrimMazeGenerator::new(weight),
+        ))),
+        RectMazeGenAlgorithm {
+            weighted_kruskal: true,
+            ..
+        } => Ok(Box::new(RectMaze2dGenerator::new(
+            WeightedKruskalMazeGenerator::new(weight),
+        ))),
         RectMazeGenAlgorithm {
             growing_tree: true, ..
-        } => Ok(Box::new(RectMaze2dGenerator::new(GrowingTreeMazeGenerator))),
+        } => Ok(Box::new(RectMaze2dGenerator::new(
+            GrowingTreeMazeGenerator::new(input.growing_tree_strategy.clone()),
+        ))),
         RectMazeGenAlgorithm { eller: true, .. } => {
-            Err(Error::NotSupportMask("Eller".to_string()).into())
-        }
-        RectMazeGenAlgorithm { btree: true, .. } => {
-            Err(Error::NotSupportMask("BTree".to_string()).into())
+            Ok(Box::new(RectLayerMazeGenerator::new(EllerMazeGenerator)))
         }
+        RectMazeGenAlgorithm {
+            origin_shift: true, ..
+        } => Ok(Box::new(RectMaze2dGenerator::new(OriginShiftMazeGenerator))),
+        RectMazeGenAlgorithm { btree: true, .. } => Ok(Box::new(RectLayerMazeGenerator::new(
+            BTreeMazeGenerator::new(input.con_dir.unwrap()),
+        ))),
         RectMazeGenAlgorithm {
             sidewinder: true, ..
-        } => Err(Error::NotSupportMask("Sidewinder".to_string()).into()),
-        other_algorithm => unreachable!(
-            "Given unknown algorithm or missing arguments of algorithm({:?}), should be refused by clap.",
-            other_algorithm
-        ),
+        } => Ok(Box::new(RectLayerMazeGenerator::new(
+            SidewinderMazeGenerator::new(input.con_dir.unwrap()),
+        ))),
+        RectMazeGenAlgorithm {
+            recursive_division: true,
+            ..
+        } => Ok(Box::new(RectMaze2dGenerator::new(
+            // The room of masked maze is limited by its cells number.
+            RegionDivisionMazeGenerator::new(input.room_max_rows_n * input.room_max_cols_n),
+        ))),
+        other_algorithm => {
+            Err(gene::Error::NotSupportMask(other_algorithm.name().to_string()).into())
+        }
     }
 }
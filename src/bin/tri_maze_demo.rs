@@ -1,14 +1,25 @@
 use anyhow::Error as AnyError;
-use clap::{Args, Parser, Subcommand, arg};
+use clap::{Args, Parser, Subcommand};
 use try_mazes::{
-    cli::{self, GeneralMazeAction, GeneralMazeLoadArgs, GeneralRectMazeShape},
+    cli::{
+        self, GeneralImagePassageWeightArgs, GeneralMazeAction, GeneralMazeCodeArgs,
+        GeneralMazeLoadArgs, GeneralRectMazeShape,
+    },
+    file::{GeneratorInfo, MazeHeader, MazeKind, SavedMaze},
     gene::{
-        AldousBroderMazeGenerator, GrowingTreeMazeGenerator, HuntAndKillMazeGenerator,
-        KruskalMazeGenerator, PrimMazeGenerator, RecursiveBacktrackerMazeGenerator,
-        WilsonMazeGenerator, tri::TriMazeGenerator,
+        AldousBroderMazeGenerator, BTreeMazeGenerator, DiagonalDirection, EllerMazeGenerator,
+        GrowingTreeMazeGenerator, GrowingTreeStrategy, HuntAndKillMazeGenerator,
+        KruskalMazeGenerator, OriginShiftMazeGenerator, PrimMazeGenerator,
+        RecursiveBacktrackerMazeGenerator, RegionDivisionMazeGenerator, SidewinderMazeGenerator,
+        WeightedKruskalMazeGenerator, WeightedPrimMazeGenerator, WilsonMazeGenerator,
+        tri::{TriLayerMazeGenerator, TriMazeGenerator},
     },
+    graph::MazeGraph,
     maze::{rect::RectMask, tri::TriGrid},
+    mesh::{MazeMesh, tri::TriMazeMesher},
     show::{MazePicture, tri::TriMazePainter},
+    stats::MazeStats,
+    validate::ValidationReport,
 };
 
 const DEF_TRI_CELL_HEIGHT: u16 = 50;
@@ -16,8 +27,19 @@ const DEF_WALL_THICKNESS: u16 = 5;
 
 fn main() -> Result<(), AnyError> {
     let maze_input = TriMazeInputArgs::parse();
-    let maze = match &maze_input.action {
-        DemoAction::Create(TriMazeCreateArgs { algorithm, shape }) => {
+    let (header, maze) = match &maze_input.action {
+        DemoAction::Create(
+            create_args @ TriMazeCreateArgs {
+                algorithm,
+                con_dir,
+                room_max_cells_n,
+                growing_tree_strategy,
+                weight,
+                shape,
+                ..
+            },
+        ) => {
+            let seed = cli::seed_rng(create_args.seed);
             let grid = match shape {
                 GeneralRectMazeShape::Size { width, height, .. } => TriGrid::new(*width, *height),
                 GeneralRectMazeShape::Mask {
@@ -26,10 +48,9 @@ fn main() -> Result<(), AnyError> {
                 GeneralRectMazeShape::Mask {
                     image: true, path, ..
                 } => TriGrid::with_mask(&RectMask::try_from_image_file(path)?),
-                other_shape => unreachable!(
-                    "Invalid maze shape({:?}), should be refused by clap.",
-                    other_shape
-                ),
+                other_shape => {
+                    return Err(cli::Error::InvalidArgs(format!("{:?}", other_shape)).into());
+                }
             };
             let generator: &dyn TriMazeGenerator = match algorithm {
                 TriMazeAlgorithm {
@@ -47,17 +68,49 @@ fn main() -> Result<(), AnyError> {
                 } => &RecursiveBacktrackerMazeGenerator,
                 TriMazeAlgorithm { kruskal: true, .. } => &KruskalMazeGenerator,
                 TriMazeAlgorithm { prim: true, .. } => &PrimMazeGenerator,
+                TriMazeAlgorithm {
+                    weighted_prim: true,
+                    ..
+                } => &WeightedPrimMazeGenerator::new(weight.passage_weight(&grid)?),
+                TriMazeAlgorithm {
+                    weighted_kruskal: true,
+                    ..
+                } => &WeightedKruskalMazeGenerator::new(weight.passage_weight(&grid)?),
                 TriMazeAlgorithm {
                     growing_tree: true, ..
-                } => &GrowingTreeMazeGenerator,
-                other_algorithm => unreachable!(
-                    "Invalid algorithm({:?}), should be refused by clap.",
-                    other_algorithm
-                ),
+                } => &GrowingTreeMazeGenerator::new(growing_tree_strategy.clone()),
+                TriMazeAlgorithm { eller: true, .. } => {
+                    &TriLayerMazeGenerator::new(EllerMazeGenerator)
+                }
+                TriMazeAlgorithm { btree: true, .. } => {
+                    &TriLayerMazeGenerator::new(BTreeMazeGenerator::new(con_dir.unwrap()))
+                }
+                TriMazeAlgorithm {
+                    sidewinder: true, ..
+                } => &TriLayerMazeGenerator::new(SidewinderMazeGenerator::new(con_dir.unwrap())),
+                TriMazeAlgorithm {
+                    recursive_division: true,
+                    ..
+                } => &RegionDivisionMazeGenerator::new(*room_max_cells_n),
+                TriMazeAlgorithm {
+                    origin_shift: true, ..
+                } => &OriginShiftMazeGenerator,
+                other_algorithm => {
+                    return Err(cli::Error::InvalidArgs(format!("{:?}", other_algorithm)).into());
+                }
             };
-            generator.generate(grid)
+            let header =
+                MazeHeader::new(MazeKind::Tri).with_generator(generator_info(create_args), seed);
+            (header, generator.generate(grid)?)
+        }
+        DemoAction::Load(GeneralMazeLoadArgs { load_path, .. }) => {
+            let SavedMaze { header, maze } = cli::load_maze_file(load_path)?;
+            (header, maze)
+        }
+        DemoAction::ImportCode(GeneralMazeCodeArgs { code, .. }) => {
+            let SavedMaze { header, maze } = SavedMaze::from_code(code)?;
+            (header, maze)
         }
-        DemoAction::Load(GeneralMazeLoadArgs { load_path, .. }) => cli::load_from_json(load_path)?,
     };
 
     let painter = TriMazePainter::new(&maze, maze_input.cell_height, maze_input.wall_thickness);
@@ -71,7 +124,8 @@ fn main() -> Result<(), AnyError> {
             shape: GeneralRectMazeShape::Mask { action, .. },
             ..
         })
-        | DemoAction::Load(GeneralMazeLoadArgs { action, .. }) => match action {
+        | DemoAction::Load(GeneralMazeLoadArgs { action, .. })
+        | DemoAction::ImportCode(GeneralMazeCodeArgs { action, .. }) => match action {
             GeneralMazeAction::Show {
                 wnd_width,
                 wnd_height,
@@ -84,11 +138,36 @@ fn main() -> Result<(), AnyError> {
             } => picture.save(path, *pic_format)?,
             GeneralMazeAction::Save {
                 json: true, path, ..
-            } => cli::save_to_json(path, &maze)?,
-            other_action => unreachable!(
-                "Invalid maze action({:?}), should be refused by clap.",
-                other_action
-            ),
+            } => cli::save_to_json(path, &header, &maze)?,
+            GeneralMazeAction::Save {
+                binary: true,
+                compress,
+                path,
+                ..
+            } => cli::save_to_binary(path, &header, &maze, *compress)?,
+            GeneralMazeAction::ExportCode => cli::print_code(&header, &maze)?,
+            GeneralMazeAction::Mesh {
+                path,
+                format,
+                settings,
+            } => TriMazeMesher::new(&maze, *settings)
+                .mesh()?
+                .save(path, *format)?,
+            GeneralMazeAction::Graph {
+                path,
+                format,
+                settings,
+            } => MazeGraph::new(&maze, settings, header.start).save(path, *format)?,
+            GeneralMazeAction::Edit { .. } => {
+                return Err(cli::Error::NotSupportEdit("triangular".to_string()).into());
+            }
+            GeneralMazeAction::Stats { json } => cli::print_stats(&MazeStats::new(&maze), *json)?,
+            GeneralMazeAction::Validate { json } => {
+                cli::print_validation(&ValidationReport::new(maze.grid()), *json)?
+            }
+            other_action => {
+                return Err(cli::Error::InvalidArgs(format!("{:?}", other_action)).into());
+            }
         },
     }
 
@@ -113,6 +192,7 @@ struct TriMazeInputArgs {
 enum DemoAction {
     Create(TriMazeCreateArgs),
     Load(GeneralMazeLoadArgs),
+    ImportCode(GeneralMazeCodeArgs),
 }
 
 #[derive(Debug, Clone, Args)]
@@ -120,6 +200,21 @@ struct TriMazeCreateArgs {
     /// Maze generation algorithm
     #[command(flatten)]
     algorithm: TriMazeAlgorithm,
+    /// Passage weights, used by weighted algorithms
+    #[command(flatten)]
+    weight: GeneralImagePassageWeightArgs,
+    /// Strategy to select active cell, used by growing tree algorithm, e.g. newest, middle or "newest:3,random:1"
+    #[arg(long, default_value_t)]
+    growing_tree_strategy: GrowingTreeStrategy,
+    /// Candidate directions to connect, used by binary tree and sidewinder algorithm
+    #[arg(short, long, group = "connect direction")]
+    con_dir: Option<DiagonalDirection>,
+    /// Max cells number of room, used by recursive division algorithm
+    #[arg(long, default_value_t = 1)]
+    room_max_cells_n: usize,
+    /// Seed of random generation, the same seed with the same arguments makes the same maze(random if not given)
+    #[arg(long)]
+    seed: Option<u64>,
     /// Specified maze shape
     #[command(subcommand)]
     shape: GeneralRectMazeShape,
@@ -128,6 +223,12 @@ struct TriMazeCreateArgs {
 #[derive(Debug, Clone, Args)]
 #[group(required = true, multiple = false)]
 pub struct TriMazeAlgorithm {
+    /// Using binary tree algorithm
+    #[arg(long, requires = "connect direction")]
+    pub btree: bool,
+    /// Using sidewinder algorithm
+    #[arg(long, requires = "connect direction")]
+    pub sidewinder: bool,
     /// Using Aldous-Broder algorithm
     #[arg(long)]
     pub aldous_broder: bool,
@@ -146,7 +247,73 @@ pub struct TriMazeAlgorithm {
     /// Using Prim's algorithm
     #[arg(long)]
     pub prim: bool,
+    /// Using weighted Prim's algorithm
+    #[arg(long)]
+    pub weighted_prim: bool,
+    /// Using weighted Kruskal's algorithm
+    #[arg(long)]
+    pub weighted_kruskal: bool,
     /// Using growing tree algorithm
     #[arg(long)]
     pub growing_tree: bool,
+    /// Using origin shift algorithm
+    #[arg(long)]
+    pub origin_shift: bool,
+    /// Using Eller's algorithm
+    #[arg(long)]
+    pub eller: bool,
+    /// Using recursive division algorithm
+    #[arg(long)]
+    pub recursive_division: bool,
+}
+
+impl TriMazeAlgorithm {
+    fn name(&self) -> &'static str {
+        [
+            (self.btree, "binary tree"),
+            (self.sidewinder, "sidewinder"),
+            (self.aldous_broder, "Aldous-Broder"),
+            (self.wilson, "Wilson's"),
+            (self.hunt_and_kill, "Hunt-and-Kill"),
+            (self.recursive_backtracker, "recursive backtracker"),
+            (self.kruskal, "Kruskal's"),
+            (self.prim, "Prim's"),
+            (self.weighted_prim, "weighted Prim's"),
+            (self.weighted_kruskal, "weighted Kruskal's"),
+            (self.growing_tree, "growing tree"),
+            (self.origin_shift, "origin shift"),
+            (self.eller, "Eller's"),
+            (self.recursive_division, "recursive division"),
+        ]
+        .into_iter()
+        .find_map(|(is_chosen, name)| is_chosen.then_some(name))
+        .unwrap_or_default()
+    }
+}
+
+fn generator_info(input: &TriMazeCreateArgs) -> GeneratorInfo {
+    let algorithm = &input.algorithm;
+    let info = GeneratorInfo::new(algorithm.name());
+    match algorithm {
+        TriMazeAlgorithm { btree: true, .. }
+        | TriMazeAlgorithm {
+            sidewinder: true, ..
+        } => info.with_param("con_dir", format!("{:?}", input.con_dir.unwrap())),
+        TriMazeAlgorithm {
+            weighted_prim: true,
+            ..
+        }
+        | TriMazeAlgorithm {
+            weighted_kruskal: true,
+            ..
+        } => input.weight.with_params(info),
+        TriMazeAlgorithm {
+            growing_tree: true, ..
+        } => info.with_param("strategy", &input.growing_tree_strategy),
+        TriMazeAlgorithm {
+            recursive_division: true,
+            ..
+        } => info.with_param("room_max_cells_n", input.room_max_cells_n),
+        _ => info,
+    }
 }
@@ -0,0 +1,335 @@
+use std::path::PathBuf;
+
+use anyhow::Error as AnyError;
+use clap::{ArgGroup, Args, Parser, Subcommand};
+use try_mazes::{
+    cli::{
+        self, GeneralImagePassageWeightArgs, GeneralMazeAction, GeneralMazeCodeArgs,
+        GeneralMazeLoadArgs,
+    },
+    file::{GeneratorInfo, MazeHeader, MazeKind, SavedMaze},
+    gene::{
+        AldousBroderMazeGenerator, GrowingTreeMazeGenerator, GrowingTreeStrategy,
+        HuntAndKillMazeGenerator, KruskalMazeGenerator, OriginShiftMazeGenerator,
+        PrimMazeGenerator, RecursiveBacktrackerMazeGenerator, WeightedKruskalMazeGenerator,
+        WeightedPrimMazeGenerator, WilsonMazeGenerator, voronoi::VoronoiMazeGenerator,
+    },
+    graph::MazeGraph,
+    maze::{
+        Position2d,
+        rect::RectMask,
+        voronoi::{VoronoiGrid, VoronoiShape},
+    },
+    mesh::{MazeMesh, voronoi::VoronoiMazeMesher},
+    show::{MazePicture, voronoi::VoronoiMazePainter},
+    stats::MazeStats,
+    validate::ValidationReport,
+};
+
+const DEF_MIN_SITE_DISTANCE: f32 = 1.0;
+const DEF_UNIT_WIDTH: u16 = 40;
+const DEF_WALL_THICKNESS: u16 = 3;
+
+fn main() -> Result<(), AnyError> {
+    let maze_input = VoronoiMazeInputArgs::parse();
+    let (header, maze) = match &maze_input.action {
+        DemoAction::Create(
+            create_args @ VoronoiMazeCreateArgs {
+                algorithm,
+                growing_tree_strategy,
+                weight,
+                min_distance,
+                shape,
+                ..
+            },
+        ) => {
+            let seed = cli::seed_rng(create_args.seed);
+            let shape = match shape {
+                VoronoiMazeShape::Rect { width, height, .. } => VoronoiShape::Rect {
+                    width: *width,
+                    height: *height,
+                },
+                VoronoiMazeShape::Circle { radius, .. } => VoronoiShape::Circle { radius: *radius },
+                VoronoiMazeShape::Mask {
+                    text: true, path, ..
+                } => VoronoiShape::Mask(RectMask::try_from_text_file(path)?),
+                VoronoiMazeShape::Mask {
+                    image: true, path, ..
+                } => VoronoiShape::Mask(RectMask::try_from_image_file(path)?),
+                other_shape => {
+                    return Err(cli::Error::InvalidArgs(format!("{:?}", other_shape)).into());
+                }
+            };
+            let grid = VoronoiGrid::new(&shape, *min_distance);
+            let (width, height) = grid.size();
+            let site_point = |pos: &Position2d| {
+                grid.site(&(*pos).into())
+                    .map(|(x, y)| (f64::from(x / width), f64::from(y / height)))
+            };
+            let generator: &dyn VoronoiMazeGenerator = match algorithm {
+                VoronoiMazeAlgorithm {
+                    aldous_broder: true,
+                    ..
+                } => &AldousBroderMazeGenerator,
+                VoronoiMazeAlgorithm { wilson: true, .. } => &WilsonMazeGenerator,
+                VoronoiMazeAlgorithm {
+                    hunt_and_kill: true,
+                    ..
+                } => &HuntAndKillMazeGenerator,
+                VoronoiMazeAlgorithm {
+                    recursive_backtracker: true,
+                    ..
+                } => &RecursiveBacktrackerMazeGenerator,
+                VoronoiMazeAlgorithm { kruskal: true, .. } => &KruskalMazeGenerator,
+                VoronoiMazeAlgorithm { prim: true, .. } => &PrimMazeGenerator,
+                VoronoiMazeAlgorithm {
+                    weighted_prim: true,
+                    ..
+                } => &WeightedPrimMazeGenerator::new(weight.passage_weight_at(&grid, site_point)?),
+                VoronoiMazeAlgorithm {
+                    weighted_kruskal: true,
+                    ..
+                } => {
+                    &WeightedKruskalMazeGenerator::new(weight.passage_weight_at(&grid, site_point)?)
+                }
+                VoronoiMazeAlgorithm {
+                    growing_tree: true, ..
+                } => &GrowingTreeMazeGenerator::new(growing_tree_strategy.clone()),
+                VoronoiMazeAlgorithm {
+                    origin_shift: true, ..
+                } => &OriginShiftMazeGenerator,
+                other_algorithm => {
+                    return Err(cli::Error::InvalidArgs(format!("{:?}", other_algorithm)).into());
+                }
+            };
+            let header = MazeHeader::new(MazeKind::Voronoi)
+                .with_generator(generator_info(create_args), seed);
+            (header, generator.generate(grid)?)
+        }
+        DemoAction::Load(GeneralMazeLoadArgs { load_path, .. }) => {
+            let SavedMaze { header, maze } = cli::load_maze_file(load_path)?;
+            (header, maze)
+        }
+        DemoAction::ImportCode(GeneralMazeCodeArgs { code, .. }) => {
+            let SavedMaze { header, maze } = SavedMaze::from_code(code)?;
+            (header, maze)
+        }
+    };
+
+    let painter = VoronoiMazePainter::new(&maze, maze_input.unit_width, maze_input.wall_thickness);
+    let picture = MazePicture::new(&painter);
+    match &maze_input.action {
+        DemoAction::Create(VoronoiMazeCreateArgs {
+            shape: VoronoiMazeShape::Rect { action, .. },
+            ..
+        })
+        | DemoAction::Create(VoronoiMazeCreateArgs {
+            shape: VoronoiMazeShape::Circle { action, .. },
+            ..
+        })
+        | DemoAction::Create(VoronoiMazeCreateArgs {
+            shape: VoronoiMazeShape::Mask { action, .. },
+            ..
+        })
+        | DemoAction::Load(GeneralMazeLoadArgs { action, .. })
+        | DemoAction::ImportCode(GeneralMazeCodeArgs { action, .. }) => match action {
+            GeneralMazeAction::Show {
+                wnd_width,
+                wnd_height,
+            } => picture.show(*wnd_width, *wnd_height)?,
+            GeneralMazeAction::Save {
+                picture: true,
+                path,
+                format: Some(pic_format),
+                ..
+            } => picture.save(path, *pic_format)?,
+            GeneralMazeAction::Save {
+                json: true, path, ..
+            } => cli::save_to_json(path, &header, &maze)?,
+            GeneralMazeAction::Save {
+                binary: true,
+                compress,
+                path,
+                ..
+            } => cli::save_to_binary(path, &header, &maze, *compress)?,
+            GeneralMazeAction::ExportCode => cli::print_code(&header, &maze)?,
+            GeneralMazeAction::Mesh {
+                path,
+                format,
+                settings,
+            } => VoronoiMazeMesher::new(&maze, *settings)
+                .mesh()?
+                .save(path, *format)?,
+            GeneralMazeAction::Graph {
+                path,
+                format,
+                settings,
+            } => MazeGraph::new(&maze, settings, header.start).save(path, *format)?,
+            GeneralMazeAction::Edit { .. } => {
+                return Err(cli::Error::NotSupportEdit("voronoi".to_string()).into());
+            }
+            GeneralMazeAction::Stats { json } => {
+                cli::print_stats(&MazeStats::new(maze.grid()), *json)?
+            }
+            GeneralMazeAction::Validate { json } => {
+                cli::print_validation(&ValidationReport::new(maze.grid()), *json)?
+            }
+            other_action => {
+                return Err(cli::Error::InvalidArgs(format!("{:?}", other_action)).into());
+            }
+        },
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Parser)]
+#[command(flatten_help = true)]
+struct VoronoiMazeInputArgs {
+    /// Length of the unit(the minimum distance between sites by default) in pixels
+    #[arg(short, long, default_value_t = DEF_UNIT_WIDTH)]
+    unit_width: u16,
+    /// Thickness of the maze wall(the stroke)
+    #[arg(short, long, default_value_t = DEF_WALL_THICKNESS)]
+    wall_thickness: u16,
+    /// What to do in demo
+    #[command(subcommand)]
+    action: DemoAction,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum DemoAction {
+    Create(VoronoiMazeCreateArgs),
+    Load(GeneralMazeLoadArgs),
+    ImportCode(GeneralMazeCodeArgs),
+}
+
+#[derive(Debug, Clone, Args)]
+struct VoronoiMazeCreateArgs {
+    /// Maze generation algorithm
+    #[command(flatten)]
+    algorithm: VoronoiMazeAlgorithm,
+    /// Passage weights, used by weighted algorithms
+    #[command(flatten)]
+    weight: GeneralImagePassageWeightArgs,
+    /// Strategy to select active cell, used by growing tree algorithm, e.g. newest, middle or "newest:3,random:1"
+    #[arg(long, default_value_t)]
+    growing_tree_strategy: GrowingTreeStrategy,
+    /// Minimum distance between two sites(cell centers) in units
+    #[arg(short, long, default_value_t = DEF_MIN_SITE_DISTANCE)]
+    min_distance: f32,
+    /// Seed of random generation, the same seed with the same arguments makes the same maze(random if not given)
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Area to scatter sites
+    #[command(subcommand)]
+    shape: VoronoiMazeShape,
+}
+
+#[derive(Debug, Clone, Subcommand)]
+enum VoronoiMazeShape {
+    Rect {
+        /// Width of the rectangle in units
+        width: f32,
+        /// Height of the rectangle in units
+        height: f32,
+        /// Action to do with maze
+        #[command(subcommand)]
+        action: GeneralMazeAction,
+    },
+    Circle {
+        /// Radius of the circle in units
+        radius: f32,
+        /// Action to do with maze
+        #[command(subcommand)]
+        action: GeneralMazeAction,
+    },
+    #[command(group(ArgGroup::new("mask type").required(true).multiple(false)))]
+    Mask {
+        /// Using text mask(x or X is for not cell, other characters are for cell)
+        #[arg(long, group = "mask type")]
+        text: bool,
+        /// Using image mask(black pixel is for not cell, other colors are for cell)
+        #[arg(long, group = "mask type")]
+        image: bool,
+        /// Path of mask file, every character or pixel is a square in unit length
+        path: PathBuf,
+        /// Action to do with maze
+        #[command(subcommand)]
+        action: GeneralMazeAction,
+    },
+}
+
+#[derive(Debug, Clone, Args)]
+#[group(required = true, multiple = false)]
+pub struct VoronoiMazeAlgorithm {
+    /// Using Aldous-Broder algorithm
+    #[arg(long)]
+    pub aldous_broder: bool,
+    /// Using Wilson's algorithm
+    #[arg(long)]
+    pub wilson: bool,
+    /// Using Hunt-and-Kill algorithm
+    #[arg(long)]
+    pub hunt_and_kill: bool,
+    /// Using recursive backtracker algorithm
+    #[arg(long)]
+    pub recursive_backtracker: bool,
+    /// Using Kruskal's algorithm
+    #[arg(long)]
+    pub kruskal: bool,
+    /// Using Prim's algorithm
+    #[arg(long)]
+    pub prim: bool,
+    /// Using weighted Prim's algorithm
+    #[arg(long)]
+    pub weighted_prim: bool,
+    /// Using weighted Kruskal's algorithm
+    #[arg(long)]
+    pub weighted_kruskal: bool,
+    /// Using growing tree algorithm
+    #[arg(long)]
+    pub growing_tree: bool,
+    /// Using origin shift algorithm
+    #[arg(long)]
+    pub origin_shift: bool,
+}
+
+impl VoronoiMazeAlgorithm {
+    fn name(&self) -> &'static str {
+        [
+            (self.aldous_broder, "Aldous-Broder"),
+            (self.wilson, "Wilson's"),
+            (self.hunt_and_kill, "Hunt-and-Kill"),
+            (self.recursive_backtracker, "recursive backtracker"),
+            (self.kruskal, "Kruskal's"),
+            (self.prim, "Prim's"),
+            (self.weighted_prim, "weighted Prim's"),
+            (self.weighted_kruskal, "weighted Kruskal's"),
+            (self.growing_tree, "growing tree"),
+            (self.origin_shift, "origin shift"),
+        ]
+        .into_iter()
+        .find_map(|(is_chosen, name)| is_chosen.then_some(name))
+        .unwrap_or_default()
+    }
+}
+
+fn generator_info(input: &VoronoiMazeCreateArgs) -> GeneratorInfo {
+    let algorithm = &input.algorithm;
+    let info = GeneratorInfo::new(algorithm.name());
+    match algorithm {
+        VoronoiMazeAlgorithm {
+            weighted_prim: true,
+            ..
+        }
+        | VoronoiMazeAlgorithm {
+            weighted_kruskal: true,
+            ..
+        } => input.weight.with_params(info),
+        VoronoiMazeAlgorithm {
+            growing_tree: true, ..
+        } => info.with_param("strategy", &input.growing_tree_strategy),
+        _ => info,
+    }
+}
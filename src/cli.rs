@@ -1,31 +1,61 @@
 use std::{
-    fs::File,
-    io::{BufReader, BufWriter, Write},
+    fs,
     path::{Path, PathBuf},
 };
 
 use anyhow::Error as AnyError;
-use clap::{Args, Subcommand};
-use serde::{Serialize, de::DeserializeOwned};
+use clap::{ArgGroup, Args, Subcommand};
+use serde::Serialize;
 use thiserror::Error;
 
-use crate::show::SavePictureFormat;
+use crate::{
+    file::{
+        GeneratorInfo, MazeHeader, SavedMaze,
+        binary::{self, BinaryMaze},
+        code,
+    },
+    gene::{CellWeights, PassageWeight, RandomWeight},
+    graph::{GraphFormat, GraphSettings},
+    maze::{Grid2d, LayerGrid, Position2d},
+    mesh::{MeshFormat, MeshSettings},
+    rng,
+    show::SavePictureFormat,
+    stats::MazeStats,
+    validate::ValidationReport,
+};
 
 #[derive(Debug, Clone, Error)]
 pub enum Error {
-    #[error("Algorithm {0} doesn't support mask.")]
-    NotSupportMask(String),
+    #[error("Editing {0} maze isn't supported.")]
+    NotSupportEdit(String),
+    #[error("Maze is invalid, found {0} violation(s).")]
+    InvalidMaze(usize),
+    #[error("Displaying {0} maze in text isn't supported.")]
+    NotSupportText(String),
+    #[error("Goal can't be reached from start.")]
+    NoSolution,
+    #[error("Invalid combination of arguments({0}).")]
+    InvalidArgs(String),
 }
 
 #[derive(Debug, Clone, Args)]
 pub struct GeneralMazeLoadArgs {
-    /// Path to load maze(saved as json format before)
+    /// Path to load maze(saved as json or binary format before)
     pub load_path: PathBuf,
     /// What to do with loaded maze
     #[command(subcommand)]
     pub action: GeneralMazeAction,
 }
 
+#[derive(Debug, Clone, Args)]
+pub struct GeneralMazeCodeArgs {
+    /// Code of maze(printed by export-code before)
+    pub code: String,
+    /// What to do with imported maze
+    #[command(subcommand)]
+    pub action: GeneralMazeAction,
+}
+
 #[derive(Debug, Clone, Subcommand)]
 pub enum GeneralRectMazeShape {
     Size {
@@ -37,12 +67,13 @@ pub enum GeneralRectMazeShape {
         #[command(subcommand)]
         action: GeneralMazeAction,
     },
+    #[command(group(ArgGroup::new("mask type").required(true).multiple(false)))]
     Mask {
         /// Using text mask(x or X is for not cell, other characters are for cell)
-        #[arg(long, group = "mask type", required = true)]
+        #[arg(long, group = "mask type")]
         text: bool,
         /// Using image mask(black pixel is for not cell, other colors are for cell)
-        #[arg(long, group = "mask type", required = true)]
+        #[arg(long, group = "mask type")]
         image: bool,
         /// Path of mask file
         path: PathBuf,
@@ -52,6 +83,66 @@ pub enum GeneralRectMazeShape {
     },
 }
 
+const DEF_MAX_PASSAGE_WEIGHT: u32 = 100;
+
+#[derive(Debug, Clone, Copy, Args)]
+pub struct GeneralPassageWeightArgs {
+    /// Max random weight of passages in a layer(row, ring or cube face), used by weighted algorithms
+    #[arg(long, default_value_t = DEF_MAX_PASSAGE_WEIGHT)]
+    pub in_layer_max_weight: u32,
+    /// Max random weight of passages across layers, used by weighted algorithms
+    #[arg(long, default_value_t = DEF_MAX_PASSAGE_WEIGHT)]
+    pub cross_layer_max_weight: u32,
+}
+
+impl GeneralPassageWeightArgs {
+    pub fn random_weight(&self) -> RandomWeight {
+        RandomWeight::new(self.in_layer_max_weight, self.cross_layer_max_weight)
+    }
+
+    pub fn with_params(&self, info: GeneratorInfo) -> GeneratorInfo {
+        info.with_param("in_layer_max_weight", self.in_layer_max_weight)
+            .with_param("cross_layer_max_weight", self.cross_layer_max_weight)
+    }
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct GeneralImagePassageWeightArgs {
+    #[command(flatten)]
+    pub random: GeneralPassageWeightArgs,
+    /// Image to weigh cells by brightness instead of random weights(darker is preferred), used by weighted algorithms
+    #[arg(long)]
+    pub weight_image: Option<PathBuf>,
+}
+
+impl GeneralImagePassageWeightArgs {
+    pub fn passage_weight(&self, grid: &dyn LayerGrid) -> Result<Box<dyn PassageWeight>, AnyError> {
+        Ok(match &self.weight_image {
+            Some(path) => Box::new(CellWeights::from_layer_image(path, grid)?),
+            None => Box::new(self.random.random_weight()),
+        })
+    }
+
+    /// Weights of passages in the grid without layers, the image weighs cells at the points given by `cell_point`.
+    pub fn passage_weight_at<F: Fn(&Position2d) -> Option<(f64, f64)>>(
+        &self,
+        grid: &dyn Grid2d,
+        cell_point: F,
+    ) -> Result<Box<dyn PassageWeight>, AnyError> {
+        Ok(match &self.weight_image {
+            Some(path) => Box::new(CellWeights::from_image(path, grid, cell_point)?),
+            None => Box::new(self.random.random_weight()),
+        })
+    }
+
+    pub fn with_params(&self, info: GeneratorInfo) -> GeneratorInfo {
+        match &self.weight_image {
+            Some(path) => info.with_param("weight_image", path.display()),
+            None => self.random.with_params(info),
+        }
+    }
+}
+
 const DEF_SHOW_WND_WIDTH: usize = 800;
 const DEF_SHOW_WND_HEIGHT: usize = 600;
 
@@ -67,6 +158,7 @@ pub enum GeneralMazeAction {
         wnd_height: usize,
     },
     /// Show maze picture in file
+    #[command(group(ArgGroup::new("save category").required(true).multiple(false)))]
     Save {
         /// Save to a picture
         #[arg(long, group = "save category", requires = "picture format")]
@@ -74,24 +166,120 @@ pub enum GeneralMazeAction {
         /// Save to json
         #[arg(long, group = "save category")]
         json: bool,
+        /// Save to compact binary format, the walls are packed in bits
+        #[arg(long, group = "save category", group = "binary format")]
+        binary: bool,
+        /// Compress the binary format by deflate
+        #[arg(long, requires = "binary format")]
+        compress: bool,
         /// Path to save maze picture
         path: PathBuf,
         /// Format to save maze picture
         #[arg(short, long, group = "picture format")]
         format: Option<SavePictureFormat>,
     },
+    /// Print the short url-safe code of maze(with its start and goal) to share, which can be imported by import-code
+    ExportCode,
+    /// Edit maze in GUI, click walls to toggle them, click cells to set start and goal(only for rectangular and hexagonal maze)
+    Edit {
+        /// Path to save edited maze as json(by Ctrl+S)
+        path: PathBuf,
+    },
+    /// Print metrics of maze, like dead ends, junctions, corridors and the solution
+    Stats {
+        /// Print in json format
+        #[arg(long)]
+        json: bool,
+    },
+    /// Check maze is perfect(every cell is reachable, no loop) and its grid is consistent, useful for loaded mazes
+    Validate {
+        /// Print report in json format
+        #[arg(long)]
+        json: bool,
+    },
+    /// Export the 3D model of maze, walls are extruded on a base plate
+    Mesh {
+        /// Path to save model
+        path: PathBuf,
+        /// Format to save model
+        #[arg(short, long)]
+        format: MeshFormat,
+        /// Settings of model
+        #[command(flatten)]
+        settings: MeshSettings,
+    },
+    /// Export the graph of maze(cells as nodes, passages as edges) for analysis in external tools
+    Graph {
+        /// Path to save graph
+        path: PathBuf,
+        /// Format to save graph
+        #[arg(short, long)]
+        format: GraphFormat,
+        /// Settings of graph
+        #[command(flatten)]
+        settings: GraphSettings,
+    },
 }
 
-pub fn load_from_json<P: AsRef<Path>, M: DeserializeOwned>(path: P) -> Result<M, AnyError> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-    let maze = serde_json::from_reader(reader)?;
-    Ok(maze)
+/// Load maze from json file of any format version or binary file, fail if it isn't the expected kind of maze.
+pub fn load_maze_file<P: AsRef<Path>, M: BinaryMaze>(path: P) -> Result<SavedMaze<M>, AnyError> {
+    SavedMaze::load(path)
 }
 
-pub fn save_to_json<P: AsRef<Path>, M: Serialize>(path: P, maze: &M) -> Result<(), AnyError> {
-    let file = File::create(path)?;
-    let writer = BufWriter::new(file);
-    serde_json::to_writer(writer, &maze)?;
+pub fn save_to_json<P: AsRef<Path>, M: Serialize>(
+    path: P,
+    header: &MazeHeader,
+    maze: &M,
+) -> Result<(), AnyError> {
+    SavedMaze {
+        header: header.clone(),
+        maze,
+    }
+    .save(path)
+}
+
+pub fn save_to_binary<P: AsRef<Path>, M: BinaryMaze>(
+    path: P,
+    header: &MazeHeader,
+    maze: &M,
+    compress: bool,
+) -> Result<(), AnyError> {
+    fs::write(path, binary::to_binary(header, maze, compress)?)?;
     Ok(())
 }
+
+pub fn print_code<M: BinaryMaze>(header: &MazeHeader, maze: &M) -> Result<(), AnyError> {
+    println!("{}", code::to_code(header, maze)?);
+    Ok(())
+}
+
+/// Seed the random generation by the given seed(or a random one if not given), and return the seed.
+pub fn seed_rng(seed: Option<u64>) -> u64 {
+    let seed = seed.unwrap_or_else(rand::random);
+    rng::set_seed(seed);
+    seed
+}
+
+pub fn print_stats(stats: &MazeStats, json: bool) -> Result<(), AnyError> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(stats)?);
+    } else {
+        println!("{}", stats);
+    }
+    Ok(())
+}
+
+/// Print the validation report, and fail if any violation is found.
+pub fn print_validation(report: &ValidationReport, json: bool) -> Result<(), AnyError> {
+    if json {
+        println!("{}", serde_json::to_string_pretty(report)?);
+    } else {
+        println!("{}", report);
+    }
+
+    if report.is_valid() {
+        Ok(())
+    } else {
+        Err(Error::InvalidMaze(report.violations.len()).into())
+    }
+}
@@ -0,0 +1,422 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::Display,
+    path::Path,
+};
+
+use anyhow::Error as AnyError;
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window, WindowOptions};
+use serde::Serialize;
+use skia_safe::{Color, Paint, PaintStyle, Surface};
+
+use crate::{
+    cli,
+    file::MazeHeader,
+    maze::{Grid2d, Position2d},
+    show,
+};
+
+pub mod hexa;
+pub mod rect;
+
+const EDIT_WND_TITLE: &str = "Maze Edit - click walls to toggle, left/right click cells to set start/goal, Ctrl+Z/Ctrl+Y to undo/redo, Ctrl+S to save, ESC to exit";
+
+/// What is under the mouse in the maze picture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EditTarget {
+    /// The wall(or the passage) between two neighbor positions.
+    Wall(Position2d, Position2d),
+    Cell(Position2d),
+}
+
+/// Geometry of the maze picture, to find what is clicked and where to mark cells.
+pub trait EditLayout {
+    /// Find the target at the given point of picture, positions in the target may be not cells(e.g. masked out).
+    fn hit_test(&self, x: f32, y: f32) -> Option<EditTarget>;
+    fn cell_center(&self, pos: &Position2d) -> (f32, f32);
+    /// Size of the empty space in a cell.
+    fn cell_size(&self) -> f32;
+}
+
+pub trait EditMaze: Serialize {
+    fn grid(&self) -> &dyn Grid2d;
+    fn grid_mut(&mut self) -> &mut dyn Grid2d;
+}
+
+/// Problems of the maze made by edits, they don't stop editing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EditWarning {
+    Disconnected { components_n: usize },
+    Imperfect { loops_n: usize },
+    GoalUnreachable,
+}
+
+impl Display for EditWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EditWarning::Disconnected { components_n } => {
+                write!(f, "maze is split into {} parts", components_n)
+            }
+            EditWarning::Imperfect { loops_n } => write!(f, "maze has {} loop(s)", loops_n),
+            EditWarning::GoalUnreachable => write!(f, "goal is unreachable from start"),
+        }
+    }
+}
+
+/// Connectivity of maze, a perfect maze has only one component and no loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Connectivity {
+    pub components_n: usize,
+    pub loops_n: usize,
+}
+
+impl Connectivity {
+    pub fn new(grid: &dyn Grid2d) -> Self {
+        let mut unvisited_pos = grid.all_cells_pos_set();
+        let cells_n = unvisited_pos.len();
+        let mut components_n = 0;
+        let mut passages_n = 0;
+        let mut neighbors = Vec::new();
+        while let Some(start_pos) = unvisited_pos.iter().next().copied() {
+            components_n += 1;
+            for pos in reachable_cells(grid, &start_pos) {
+                unvisited_pos.remove(&pos);
+                neighbors.clear();
+                grid.append_neighbors(&pos, &mut neighbors);
+                passages_n += neighbors
+                    .iter()
+                    .filter(|neighbor| grid.is_connected_to(&pos, neighbor))
+                    .count();
+            }
+        }
+
+        // Every passage is counted from both of its cells, and a spanning tree of each component needs (cells - 1) of them.
+        Self {
+            components_n,
+            loops_n: (passages_n / 2 + components_n).saturating_sub(cells_n),
+        }
+    }
+
+    pub fn is_perfect(&self) -> bool {
+        self.components_n <= 1 && self.loops_n == 0
+    }
+}
+
+fn reachable_cells(grid: &dyn Grid2d, start_pos: &Position2d) -> HashSet<Position2d> {
+    let mut visited_pos = HashSet::from([*start_pos]);
+    let mut candidates = VecDeque::from([*start_pos]);
+    let mut neighbors = Vec::new();
+    while let Some(pos) = candidates.pop_front() {
+        neighbors.clear();
+        grid.append_neighbors(&pos, &mut neighbors);
+        for neighbor in neighbors.iter() {
+            if grid.is_connected_to(&pos, neighbor) && visited_pos.insert(*neighbor) {
+                candidates.push_back(*neighbor);
+            }
+        }
+    }
+
+    visited_pos
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Edit {
+    ToggleWall(Position2d, Position2d),
+    SetStart(Option<Position2d>),
+    SetGoal(Option<Position2d>),
+}
+
+/// Editing session of a maze, with the history of edits for undo and redo.
+#[derive(Debug, Clone)]
+pub struct MazeEditor<M: EditMaze> {
+    maze: M,
+    cells_pos: HashSet<Position2d>,
+    start: Option<Position2d>,
+    goal: Option<Position2d>,
+    undo_edits: Vec<Edit>,
+    redo_edits: Vec<Edit>,
+}
+
+impl<M: EditMaze> MazeEditor<M> {
+    pub fn new(maze: M) -> Self {
+        let cells_pos = maze.grid().all_cells_pos_set();
+        Self {
+            maze,
+            cells_pos,
+            start: None,
+            goal: None,
+            undo_edits: Vec::new(),
+            redo_edits: Vec::new(),
+        }
+    }
+
+    /// Start from the given endpoints(e.g. loaded from file), the ones not on cells are dropped.
+    pub fn with_endpoints(mut self, start: Option<Position2d>, goal: Option<Position2d>) -> Self {
+        self.start = start.filter(|pos| self.cells_pos.contains(pos));
+        self.goal = goal.filter(|pos| self.cells_pos.contains(pos));
+        self
+    }
+
+    pub fn maze(&self) -> &M {
+        &self.maze
+    }
+
+    pub fn into_maze(self) -> M {
+        self.maze
+    }
+
+    pub fn start(&self) -> Option<Position2d> {
+        self.start
+    }
+
+    pub fn goal(&self) -> Option<Position2d> {
+        self.goal
+    }
+
+    /// Toggle the clicked wall, or set(unset if it's set already) the start by primary click on cell, the goal by
+    /// secondary click. Return false if nothing is changed.
+    pub fn click(&mut self, target: EditTarget, is_primary: bool) -> bool {
+        let edit = match target {
+            EditTarget::Wall(from, to) => {
+                if !self.cells_pos.contains(&from) || !self.cells_pos.contains(&to) {
+                    return false;
+                }
+                Edit::ToggleWall(from, to)
+            }
+            EditTarget::Cell(pos) if self.cells_pos.contains(&pos) => {
+                let mark = if is_primary { self.start } else { self.goal };
+                let new_mark = Some(pos).filter(|pos| mark != Some(*pos));
+                if is_primary {
+                    Edit::SetStart(new_mark)
+                } else {
+                    Edit::SetGoal(new_mark)
+                }
+            }
+            EditTarget::Cell(_) => return false,
+        };
+
+        let Some(reverse_edit) = self.apply(edit) else {
+            return false;
+        };
+        self.undo_edits.push(reverse_edit);
+        self.redo_edits.clear();
+        true
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let Some(reverse_edit) = self.undo_edits.pop().and_then(|edit| self.apply(edit)) else {
+            return false;
+        };
+        self.redo_edits.push(reverse_edit);
+        true
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let Some(reverse_edit) = self.redo_edits.pop().and_then(|edit| self.apply(edit)) else {
+            return false;
+        };
+        self.undo_edits.push(reverse_edit);
+        true
+    }
+
+    pub fn warnings(&self) -> Vec<EditWarning> {
+        let grid = self.maze.grid();
+        let connectivity = Connectivity::new(grid);
+        let mut warnings = Vec::new();
+        if connectivity.components_n > 1 {
+            warnings.push(EditWarning::Disconnected {
+                components_n: connectivity.components_n,
+            });
+        }
+        if connectivity.loops_n > 0 {
+            warnings.push(EditWarning::Imperfect {
+                loops_n: connectivity.loops_n,
+            });
+        }
+        if let (Some(start), Some(goal)) = (self.start, self.goal)
+            && !reachable_cells(grid, &start).contains(&goal)
+        {
+            warnings.push(EditWarning::GoalUnreachable);
+        }
+
+        warnings
+    }
+
+    /// Edit maze in GUI, the maze picture is painted again after every edit, and saved to given path as json, with
+    /// the given header and the current endpoints.
+    pub fn run<P: AsRef<Path>, F: Fn(&M) -> Result<Surface, AnyError>>(
+        mut self,
+        layout: &dyn EditLayout,
+        paint_maze: F,
+        save_path: P,
+        header: &MazeHeader,
+    ) -> Result<M, AnyError> {
+        let (mut pixels, pic_width, pic_height) = self.paint(layout, &paint_maze)?;
+        // The window has the same size as the picture, so the mouse position is the point in picture.
+        let mut window = Window::new(
+            EDIT_WND_TITLE,
+            pic_width,
+            pic_height,
+            WindowOptions::default(),
+        )?;
+        window.set_target_fps(60);
+        let mut was_mouse_down = [false; 2];
+        let mut status = self.status();
+        window.set_title(&status);
+        while window.is_open() && !window.is_key_down(Key::Escape) {
+            let mut is_changed = false;
+            for (ind, button) in [MouseButton::Left, MouseButton::Right]
+                .into_iter()
+                .enumerate()
+            {
+                let is_mouse_down = window.get_mouse_down(button);
+                if is_mouse_down
+                    && !was_mouse_down[ind]
+                    && let Some(target) = window
+                        .get_mouse_pos(MouseMode::Discard)
+                        .and_then(|(x, y)| layout.hit_test(x, y))
+                {
+                    is_changed |= self.click(target, button == MouseButton::Left);
+                }
+                was_mouse_down[ind] = is_mouse_down;
+            }
+
+            if window.is_key_down(Key::LeftCtrl) || window.is_key_down(Key::RightCtrl) {
+                if window.is_key_pressed(Key::Z, KeyRepeat::Yes) {
+                    is_changed |= self.undo();
+                } else if window.is_key_pressed(Key::Y, KeyRepeat::Yes) {
+                    is_changed |= self.redo();
+                } else if window.is_key_pressed(Key::S, KeyRepeat::No) {
+                    let header = MazeHeader {
+                        start: self.start,
+                        goal: self.goal,
+                        ..header.clone()
+                    };
+                    cli::save_to_json(&save_path, &header, &self.maze)?;
+                    window.set_title(&format!(
+                        "{} - saved to {}",
+                        status,
+                        save_path.as_ref().display()
+                    ));
+                }
+            }
+
+            if is_changed {
+                (pixels, _, _) = self.paint(layout, &paint_maze)?;
+                status = self.status();
+                window.set_title(&status);
+            }
+            window.update_with_buffer(pixels.as_slice(), pic_width, pic_height)?;
+        }
+
+        Ok(self.maze)
+    }
+
+    fn apply(&mut self, edit: Edit) -> Option<Edit> {
+        match edit {
+            Edit::ToggleWall(from, to) => self
+                .maze
+                .grid_mut()
+                .toggle_connection(&from, &to)
+                .then_some(edit),
+            Edit::SetStart(pos) => Some(Edit::SetStart(std::mem::replace(&mut self.start, pos))),
+            Edit::SetGoal(pos) => Some(Edit::SetGoal(std::mem::replace(&mut self.goal, pos))),
+        }
+    }
+
+    fn status(&self) -> String {
+        let warnings = self.warnings();
+        if warnings.is_empty() {
+            EDIT_WND_TITLE.to_string()
+        } else {
+            let warnings: Vec<_> = warnings.iter().map(|warning| warning.to_string()).collect();
+            format!("Maze Edit - warning: {}", warnings.join(", "))
+        }
+    }
+
+    fn paint<F: Fn(&M) -> Result<Surface, AnyError>>(
+        &self,
+        layout: &dyn EditLayout,
+        paint_maze: &F,
+    ) -> Result<(Vec<u32>, usize, usize), AnyError> {
+        let mut surface = paint_maze(&self.maze)?;
+        let mut paint = Paint::default();
+        paint.set_anti_alias(true);
+        paint.set_style(PaintStyle::Fill);
+        for (mark, color) in [(self.start, Color::GREEN), (self.goal, Color::RED)] {
+            if let Some(pos) = mark {
+                paint.set_color(color);
+                surface.canvas().draw_circle(
+                    layout.cell_center(&pos),
+                    layout.cell_size() / 4.0,
+                    &paint,
+                );
+            }
+        }
+
+        show::surface_pixels(&mut surface)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maze::{
+        NoMask, Position2d,
+        rect::{RectGrid, RectMaze},
+    };
+
+    use super::{Connectivity, EditTarget, EditWarning, MazeEditor};
+
+    #[test]
+    fn test_editor_undo_redo_and_warnings() {
+        let mut maze = RectMaze::NoMask(RectGrid::<NoMask>::new(2, 2));
+        // A perfect maze shaped as the letter U.
+        let grid = maze.grid_mut();
+        grid.connect_to(&Position2d(0, 0), &Position2d(1, 0));
+        grid.connect_to(&Position2d(1, 0), &Position2d(1, 1));
+        grid.connect_to(&Position2d(1, 1), &Position2d(0, 1));
+        assert!(Connectivity::new(grid).is_perfect());
+
+        let mut editor = MazeEditor::new(maze);
+        assert!(editor.warnings().is_empty());
+        let top_wall = EditTarget::Wall(Position2d(0, 0), Position2d(0, 1));
+        assert!(editor.click(top_wall, true));
+        assert_eq!(editor.warnings(), [EditWarning::Imperfect { loops_n: 1 }]);
+        assert!(editor.click(EditTarget::Cell(Position2d(0, 0)), true));
+        assert!(editor.click(EditTarget::Cell(Position2d(0, 1)), false));
+        assert!(!editor.click(EditTarget::Cell(Position2d(2, 0)), false));
+        assert!(!editor.click(EditTarget::Wall(Position2d(0, 0), Position2d(1, 1)), true));
+
+        // Break the loop and the whole maze at both sides.
+        let bottom_wall = EditTarget::Wall(Position2d(1, 0), Position2d(1, 1));
+        assert!(editor.click(bottom_wall, true));
+        assert!(editor.click(top_wall, true));
+        assert_eq!(
+            editor.warnings(),
+            [
+                EditWarning::Disconnected { components_n: 2 },
+                EditWarning::GoalUnreachable
+            ]
+        );
+
+        assert!(editor.undo());
+        assert!(editor.undo());
+        assert_eq!(editor.warnings(), [EditWarning::Imperfect { loops_n: 1 }]);
+        assert!(editor.undo());
+        assert_eq!(editor.goal(), None);
+        assert!(editor.redo());
+        assert_eq!(editor.goal(), Some(Position2d(0, 1)));
+        assert!(editor.undo());
+        assert!(editor.undo());
+        assert!(editor.undo());
+        assert!(!editor.undo());
+        assert_eq!(editor.start(), None);
+        assert!(editor.warnings().is_empty());
+
+        // A new edit drops the edits to redo.
+        assert!(editor.click(EditTarget::Cell(Position2d(1, 1)), true));
+        assert!(!editor.redo());
+        assert!(editor.click(EditTarget::Cell(Position2d(1, 1)), true));
+        assert_eq!(editor.start(), None);
+    }
+}
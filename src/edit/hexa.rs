@@ -0,0 +1,164 @@
+use crate::maze::{
+    Grid2d, Position2d,
+    hexa::{HexaDirection, HexaMaze, HexaPosition},
+};
+
+use super::{EditLayout, EditMaze, EditTarget};
+
+impl EditMaze for HexaMaze {
+    fn grid(&self) -> &dyn Grid2d {
+        HexaMaze::grid(self)
+    }
+
+    fn grid_mut(&mut self) -> &mut dyn Grid2d {
+        HexaMaze::grid_mut(self)
+    }
+}
+
+/// Geometry of the picture painted by `HexaMazePainter` with the same settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HexaEditLayout {
+    width: usize,
+    height: usize,
+    hexa_cell_height: u16,
+    wall_thickness: u16,
+}
+
+impl EditLayout for HexaEditLayout {
+    fn hit_test(&self, x: f32, y: f32) -> Option<EditTarget> {
+        // The hexagon containing the point has the nearest center.
+        let (cell_radius, cell_vert_interval) = self.cell_radius_and_vert_interval();
+        let (x0, _) = self.center(0, 0);
+        let approx_col = ((x - x0) / (1.5 * cell_radius)).round() as isize;
+        let (pos, center_x, center_y) = (approx_col - 1..=approx_col + 1)
+            .filter_map(|col| usize::try_from(col).ok().filter(|col| *col < self.width))
+            .flat_map(|col| {
+                let (_, y0) = self.center(0, col);
+                let approx_row = ((y - y0) / cell_vert_interval).round() as isize;
+                (approx_row - 1..=approx_row + 1)
+                    .filter_map(|row| usize::try_from(row).ok().filter(|row| *row < self.height))
+                    .map(move |row| HexaPosition::new(row, col))
+            })
+            .map(|pos| {
+                let (center_x, center_y) = self.center(pos.row, pos.col);
+                (pos, center_x, center_y)
+            })
+            .min_by(|(_, x0, y0), (_, x1, y1)| {
+                let distance0 = (x - x0).powi(2) + (y - y0).powi(2);
+                let distance1 = (x - x1).powi(2) + (y - y1).powi(2);
+                distance0.total_cmp(&distance1)
+            })?;
+
+        // Find the edge the point is closest to, by projecting it on the normals of edges.
+        let (offset_x, offset_y) = (x - center_x, y - center_y);
+        let (dir, projection) = [
+            (HexaDirection::North, -90f32),
+            (HexaDirection::NorthEast, -30f32),
+            (HexaDirection::SouthEast, 30f32),
+            (HexaDirection::South, 90f32),
+            (HexaDirection::SouthWest, 150f32),
+            (HexaDirection::NorthWest, -150f32),
+        ]
+        .into_iter()
+        .map(|(dir, deg)| {
+            let rad = deg.to_radians();
+            (dir, offset_x * rad.cos() + offset_y * rad.sin())
+        })
+        .max_by(|(_, p0), (_, p1)| p0.total_cmp(p1))?;
+        let edge_distance = cell_vert_interval / 2.0 - projection;
+        let wall_hit_range =
+            f32::from(self.wall_thickness) / 2.0 + f32::from(self.hexa_cell_height) / 6.0;
+        if edge_distance < -wall_hit_range {
+            // Out of the picture.
+            None
+        } else if edge_distance <= wall_hit_range {
+            pos.neighbor(dir)
+                .filter(|neighbor| neighbor.row < self.height && neighbor.col < self.width)
+                .map(|neighbor| EditTarget::Wall(pos.into(), neighbor.into()))
+        } else {
+            Some(EditTarget::Cell(pos.into()))
+        }
+    }
+
+    fn cell_center(&self, pos: &Position2d) -> (f32, f32) {
+        self.center(pos.0, pos.1)
+    }
+
+    fn cell_size(&self) -> f32 {
+        f32::from(self.hexa_cell_height)
+    }
+}
+
+impl HexaEditLayout {
+    pub fn new(maze: &HexaMaze, hexa_cell_height: u16, wall_thickness: u16) -> Self {
+        let (width, height) = maze.size();
+        Self {
+            width,
+            height,
+            hexa_cell_height,
+            wall_thickness,
+        }
+    }
+
+    fn cell_radius_and_vert_interval(&self) -> (f32, f32) {
+        let cell_vert_interval = f32::from(self.hexa_cell_height) + f32::from(self.wall_thickness);
+        (cell_vert_interval / 3f32.sqrt(), cell_vert_interval)
+    }
+
+    fn center(&self, row: usize, col: usize) -> (f32, f32) {
+        let (cell_radius, cell_vert_interval) = self.cell_radius_and_vert_interval();
+        let wall_thickness = f32::from(self.wall_thickness);
+        // The picture is translated by the stroke offsets, and cells in odd columns are lower by half interval.
+        let x = wall_thickness / 3f32.sqrt() + cell_radius + col as f32 * 1.5 * cell_radius;
+        let y = wall_thickness / 2.0
+            + (row as f32 + 0.5) * cell_vert_interval
+            + if col % 2 == 1 {
+                cell_vert_interval / 2.0
+            } else {
+                0.0
+            };
+        (x, y)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        edit::{EditLayout, EditTarget},
+        maze::{NoMask, Position2d, hexa::HexaGrid, hexa::HexaMaze},
+    };
+
+    use super::HexaEditLayout;
+
+    #[test]
+    fn test_hit_test() {
+        let maze = HexaMaze::NoMask(HexaGrid::<NoMask>::new(4, 3));
+        let layout = HexaEditLayout::new(&maze, 30, 4);
+        for pos in [Position2d(0, 0), Position2d(1, 1), Position2d(2, 3)] {
+            let (x, y) = layout.cell_center(&pos);
+            assert_eq!(layout.hit_test(x, y), Some(EditTarget::Cell(pos)));
+        }
+
+        // Walls are at the middle between centers of neighbors.
+        let pos = Position2d(1, 1);
+        let (x0, y0) = layout.cell_center(&pos);
+        for neighbor in [
+            Position2d(0, 1),
+            Position2d(1, 2),
+            Position2d(2, 2),
+            Position2d(1, 0),
+        ] {
+            let (x1, y1) = layout.cell_center(&neighbor);
+            assert!(matches!(
+                layout.hit_test((x0 + x1) / 2.0, (y0 + y1) / 2.0),
+                Some(EditTarget::Wall(from, to))
+                    if (from, to) == (pos, neighbor) || (from, to) == (neighbor, pos)
+            ));
+        }
+
+        // Borders can't be edited.
+        let (x, y) = layout.cell_center(&Position2d(0, 0));
+        assert_eq!(layout.hit_test(x, y - 17.0), None);
+        assert_eq!(layout.hit_test(x - 500.0, y), None);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::maze::{Grid2d, Position2d, rect::RectMaze};
+
+use super::{EditLayout, EditMaze, EditTarget};
+
+impl EditMaze for RectMaze {
+    fn grid(&self) -> &dyn Grid2d {
+        RectMaze::grid(self)
+    }
+
+    fn grid_mut(&mut self) -> &mut dyn Grid2d {
+        RectMaze::grid_mut(self)
+    }
+}
+
+/// Geometry of the picture painted by `RectMazePainter` with the same settings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RectEditLayout {
+    width: usize,
+    height: usize,
+    wall_thickness: usize,
+    cell_width: usize,
+}
+
+impl EditLayout for RectEditLayout {
+    fn hit_test(&self, x: f32, y: f32) -> Option<EditTarget> {
+        // Coordinates relative to the center lines of the west and the north border.
+        let half_wall_thickness = self.wall_thickness as f32 / 2.0;
+        let cell_interval = (self.cell_width + self.wall_thickness) as f32;
+        let col_f = (x - half_wall_thickness) / cell_interval;
+        let row_f = (y - half_wall_thickness) / cell_interval;
+        if col_f < 0.0 || row_f < 0.0 {
+            return None;
+        }
+
+        let (col, row) = (col_f as usize, row_f as usize);
+        let vert_wall_distance = (col_f - col_f.round()).abs() * cell_interval;
+        let horz_wall_distance = (row_f - row_f.round()).abs() * cell_interval;
+        let wall_hit_range = half_wall_thickness + self.cell_width as f32 / 6.0;
+        if vert_wall_distance <= horz_wall_distance && vert_wall_distance <= wall_hit_range {
+            let line = col_f.round() as usize;
+            (row < self.height && line > 0 && line < self.width)
+                .then(|| EditTarget::Wall(Position2d(row, line - 1), Position2d(row, line)))
+        } else if horz_wall_distance <= wall_hit_range {
+            let line = row_f.round() as usize;
+            (col < self.width && line > 0 && line < self.height)
+                .then(|| EditTarget::Wall(Position2d(line - 1, col), Position2d(line, col)))
+        } else {
+            (row < self.height && col < self.width)
+                .then_some(EditTarget::Cell(Position2d(row, col)))
+        }
+    }
+
+    fn cell_center(&self, pos: &Position2d) -> (f32, f32) {
+        let half_wall_thickness = self.wall_thickness as f32 / 2.0;
+        let cell_interval = (self.cell_width + self.wall_thickness) as f32;
+        (
+            half_wall_thickness + (pos.1 as f32 + 0.5) * cell_interval,
+            half_wall_thickness + (pos.0 as f32 + 0.5) * cell_interval,
+        )
+    }
+
+    fn cell_size(&self) -> f32 {
+        self.cell_width as f32
+    }
+}
+
+impl RectEditLayout {
+    pub fn new(maze: &RectMaze, wall_thickness: usize, cell_width: usize) -> Self {
+        let (width, height) = maze.size();
+        Self {
+            width,
+            height,
+            wall_thickness,
+            cell_width,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        edit::{EditLayout, EditTarget},
+        maze::{
+            NoMask, Position2d,
+            rect::{RectGrid, RectMaze},
+        },
+    };
+
+    use super::RectEditLayout;
+
+    #[test]
+    fn test_hit_test() {
+        let maze = RectMaze::NoMask(RectGrid::<NoMask>::new(4, 3));
+        let layout = RectEditLayout::new(&maze, 4, 20);
+        let pos = Position2d(1, 2);
+        let (x, y) = layout.cell_center(&pos);
+        assert_eq!(layout.hit_test(x, y), Some(EditTarget::Cell(pos)));
+        // The west wall is at the middle between centers of the cell and its west neighbor.
+        assert_eq!(
+            layout.hit_test(x - 12.0, y + 5.0),
+            Some(EditTarget::Wall(Position2d(1, 1), pos))
+        );
+        assert_eq!(
+            layout.hit_test(x + 3.0, y + 11.0),
+            Some(EditTarget::Wall(pos, Position2d(2, 2)))
+        );
+        // Borders can't be edited.
+        assert_eq!(layout.hit_test(2.0, y), None);
+        assert_eq!(layout.hit_test(x, 3.0 * 24.0 + 2.0), None);
+    }
+}
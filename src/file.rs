@@ -0,0 +1,330 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs::{self, File},
+    io::BufWriter,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Error as AnyError;
+use binary::BinaryMaze;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::{Value, json};
+use thiserror::Error;
+
+use crate::maze::{
+    Position2d, bits, circ::CircMaze, cube::CubeMaze, hexa::HexaMaze, rect::RectMaze, tri::TriMaze,
+    voronoi::VoronoiMaze,
+};
+
+pub mod binary;
+pub mod code;
+
+/// Version of the maze file format, the version 1 file has only the maze itself, without header.
+pub const FORMAT_VERSION: u32 = 2;
+const VERSION_KEY: &str = "format_version";
+
+#[derive(Debug, Clone, Error)]
+pub enum Error {
+    #[error("File has a {found} maze, but a {expected} maze is expected.")]
+    KindMismatch { expected: MazeKind, found: MazeKind },
+    #[error(
+        "File format version {0} isn't supported(the latest version is {FORMAT_VERSION}), try a newer version of program."
+    )]
+    UnsupportedVersion(u32),
+    #[error("File doesn't have a valid {0} maze: {1}.")]
+    InvalidMaze(MazeKind, String),
+    #[error("File doesn't have a maze of any known kind.")]
+    UnknownKind,
+    #[error("Binary maze file is broken: {0}.")]
+    BrokenBinary(String),
+    #[error("Maze code is invalid: {0}.")]
+    InvalidCode(String),
+    #[error("Failed to encode maze: {0}.")]
+    EncodeFailure(String),
+}
+
+impl From<bits::Error> for Error {
+    fn from(value: bits::Error) -> Self {
+        match value {
+            bits::Error::Overflow(_) => Error::EncodeFailure(value.to_string()),
+            bits::Error::UnexpectedEnd => Error::BrokenBinary(value.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MazeKind {
+    Rect,
+    Hexa,
+    Tri,
+    Circ,
+    Cube,
+    Voronoi,
+}
+
+impl MazeKind {
+    pub fn all_kinds() -> &'static [MazeKind; 6] {
+        static ALL_KINDS: [MazeKind; 6] = [
+            MazeKind::Rect,
+            MazeKind::Hexa,
+            MazeKind::Tri,
+            MazeKind::Circ,
+            MazeKind::Cube,
+            MazeKind::Voronoi,
+        ];
+
+        &ALL_KINDS
+    }
+}
+
+impl Display for MazeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            MazeKind::Rect => "rectangular",
+            MazeKind::Hexa => "hexagonal",
+            MazeKind::Tri => "triangular",
+            MazeKind::Circ => "circular",
+            MazeKind::Cube => "cube",
+            MazeKind::Voronoi => "Voronoi",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Maze which can be saved in the maze file, with its kind written in header.
+pub trait MazeFile: Serialize + DeserializeOwned {
+    const KIND: MazeKind;
+}
+
+impl MazeFile for RectMaze {
+    const KIND: MazeKind = MazeKind::Rect;
+}
+
+impl MazeFile for HexaMaze {
+    const KIND: MazeKind = MazeKind::Hexa;
+}
+
+impl MazeFile for TriMaze {
+    const KIND: MazeKind = MazeKind::Tri;
+}
+
+impl MazeFile for CircMaze {
+    const KIND: MazeKind = MazeKind::Circ;
+}
+
+impl MazeFile for CubeMaze {
+    const KIND: MazeKind = MazeKind::Cube;
+}
+
+impl MazeFile for VoronoiMaze {
+    const KIND: MazeKind = MazeKind::Voronoi;
+}
+
+/// Algorithm generated the maze, with the parameters it used(e.g. the connect direction).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GeneratorInfo {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub params: BTreeMap<String, String>,
+}
+
+impl GeneratorInfo {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            params: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_param<V: Display>(mut self, key: &str, value: V) -> Self {
+        self.params.insert(key.to_string(), value.to_string());
+        self
+    }
+}
+
+/// Header of maze file, tells what the maze is and how it's made. The fields except the version and the kind are
+/// optional, they're unknown in files of older format.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MazeHeader {
+    pub format_version: u32,
+    pub kind: MazeKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generator: Option<GeneratorInfo>,
+    /// Seed of the random generation, the same generator with the same seed makes the same maze.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Seconds since the Unix epoch when the maze was made.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start: Option<Position2d>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub goal: Option<Position2d>,
+}
+
+impl MazeHeader {
+    /// Header of the maze made now.
+    pub fn new(kind: MazeKind) -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            kind,
+            generator: None,
+            seed: None,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .ok()
+                .map(|duration| duration.as_secs()),
+            start: None,
+            goal: None,
+        }
+    }
+
+    pub fn with_generator(mut self, generator: GeneratorInfo, seed: u64) -> Self {
+        self.generator = Some(generator);
+        self.seed = Some(seed);
+        self
+    }
+}
+
+/// Content of maze file, the header is flattened with the maze, so the file is a json object like
+/// `{"format_version": 2, "kind": "rect", ..., "maze": {...}}`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedMaze<M> {
+    #[serde(flatten)]
+    pub header: MazeHeader,
+    pub maze: M,
+}
+
+impl<M> SavedMaze<M> {
+    pub fn new(header: MazeHeader, maze: M) -> Self {
+        Self { header, maze }
+    }
+}
+
+impl<M: MazeFile> SavedMaze<M> {
+    /// Read maze from the json of any supported format version, the maze in file should be the expected kind.
+    pub fn from_json(value: Value) -> Result<Self, Error> {
+        let value = migrate(value, M::KIND)?;
+        let invalid_maze = |e: serde_json::Error| Error::InvalidMaze(M::KIND, e.to_string());
+        let found_kind = MazeKind::deserialize(&value["kind"]).map_err(invalid_maze)?;
+        if found_kind != M::KIND {
+            return Err(Error::KindMismatch {
+                expected: M::KIND,
+                found: found_kind,
+            });
+        }
+
+        Self::deserialize(value).map_err(invalid_maze)
+    }
+}
+
+impl<M: BinaryMaze> SavedMaze<M> {
+    /// Load maze from file saved in json or binary format, the format is detected by the file content.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, AnyError> {
+        let bytes = fs::read(path)?;
+        if binary::is_binary(&bytes) {
+            Ok(Self::from_binary(&bytes)?)
+        } else {
+            Ok(Self::from_json(serde_json::from_slice(&bytes)?)?)
+        }
+    }
+}
+
+impl<M: Serialize> SavedMaze<M> {
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), AnyError> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(writer, self)?;
+        Ok(())
+    }
+}
+
+/// Migrate the json of maze file to the current format version, the kind is taken for the older file without it.
+fn migrate(mut value: Value, kind: MazeKind) -> Result<Value, Error> {
+    let version = match value.get(VERSION_KEY) {
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| {
+                Error::InvalidMaze(kind, format!("invalid format version {}", version))
+            })?,
+        None => 1,
+    };
+    if version > FORMAT_VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+
+    if version < 2 {
+        value = json!({VERSION_KEY: 2, "kind": kind, "maze": value});
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::{
+        gene::{Maze2dGenerator, WilsonMazeGenerator},
+        maze::{
+            NoMask, Position2d,
+            hexa::{HexaGrid, HexaMaze},
+            rect::{RectGrid, RectMaze},
+        },
+    };
+
+    use super::{Error, FORMAT_VERSION, GeneratorInfo, MazeHeader, MazeKind, SavedMaze};
+
+    #[test]
+    fn test_save_load_and_migrate() {
+        let mut grid = RectGrid::<NoMask>::new(4, 3);
+        WilsonMazeGenerator.generate_2d(&mut grid).unwrap();
+        let maze = RectMaze::NoMask(grid);
+        let mut header =
+            MazeHeader::new(MazeKind::Rect).with_generator(GeneratorInfo::new("wilson"), 42);
+        header.goal = Some(Position2d(2, 3));
+        let json = serde_json::to_value(SavedMaze::new(header.clone(), maze.clone())).unwrap();
+        assert_eq!(json["format_version"], json!(FORMAT_VERSION));
+        assert_eq!(json["kind"], json!("rect"));
+        let loaded = SavedMaze::<RectMaze>::from_json(json.clone()).unwrap();
+        assert_eq!(loaded.header, header);
+        assert_eq!(
+            serde_json::to_value(&loaded.maze).unwrap(),
+            serde_json::to_value(&maze).unwrap()
+        );
+
+        // The file of version 1 is the maze itself.
+        let old_json = serde_json::to_value(&maze).unwrap();
+        let loaded = SavedMaze::<RectMaze>::from_json(old_json.clone()).unwrap();
+        assert_eq!(loaded.header.kind, MazeKind::Rect);
+        assert_eq!(loaded.header.generator, None);
+        assert_eq!(serde_json::to_value(&loaded.maze).unwrap(), old_json);
+
+        assert!(matches!(
+            SavedMaze::<HexaMaze>::from_json(json.clone()),
+            Err(Error::KindMismatch {
+                expected: MazeKind::Hexa,
+                found: MazeKind::Rect
+            })
+        ));
+        assert!(matches!(
+            SavedMaze::<HexaMaze>::from_json(old_json),
+            Err(Error::InvalidMaze(MazeKind::Hexa, _))
+        ));
+        let mut new_json = json;
+        new_json["format_version"] = json!(FORMAT_VERSION + 1);
+        assert!(matches!(
+            SavedMaze::<RectMaze>::from_json(new_json),
+            Err(Error::UnsupportedVersion(_))
+        ));
+
+        let hexa_maze = HexaMaze::NoMask(HexaGrid::<NoMask>::new(2, 2));
+        let hexa_json =
+            serde_json::to_value(SavedMaze::new(MazeHeader::new(MazeKind::Hexa), hexa_maze))
+                .unwrap();
+        assert!(SavedMaze::<HexaMaze>::from_json(hexa_json).is_ok());
+    }
+}
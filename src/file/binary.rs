@@ -0,0 +1,254 @@
+use std::{
+    fs,
+    io::{Read, Write},
+    path::Path,
+};
+
+use anyhow::Error as AnyError;
+use flate2::{Compression, read::DeflateDecoder, write::DeflateEncoder};
+
+use super::{Error, MazeFile, MazeHeader, SavedMaze};
+use crate::maze::bits::{BitReader, BitWriter};
+
+/// Magic bytes at the beginning of binary maze file.
+pub const BINARY_MAGIC: &[u8; 4] = b"TMZB";
+const BINARY_VERSION: u8 = 1;
+const COMPRESSED_FLAG: u8 = 1;
+/// Limit of maze bits in bytes, so a small broken file can't expand into a huge allocation by decompression, and the
+/// writer never makes a file which the reader refuses.
+pub(crate) const MAX_DECOMPRESSED_LEN: usize = 1 << 24;
+
+/// Maze which can be packed in bits, the walls of each cell take only one bit per direction.
+pub trait BinaryMaze: MazeFile + Sized {
+    fn write_bits(&self, writer: &mut BitWriter) -> Result<(), Error>;
+    fn read_bits(reader: &mut BitReader) -> Result<Self, Error>;
+}
+
+/// Encode maze in binary, the file starts with the magic bytes, the version, the flags and the header in json, then
+/// the maze packed in bits, compressed by deflate if asked.
+pub fn to_binary<M: BinaryMaze>(
+    header: &MazeHeader,
+    maze: &M,
+    compress: bool,
+) -> Result<Vec<u8>, Error> {
+    let encode_failure = |e: &dyn ToString| Error::EncodeFailure(e.to_string());
+    let header_json = serde_json::to_vec(header).map_err(|e| encode_failure(&e))?;
+    let mut bytes = BINARY_MAGIC.to_vec();
+    bytes.push(BINARY_VERSION);
+    bytes.push(if compress { COMPRESSED_FLAG } else { 0 });
+    let header_len = u32::try_from(header_json.len()).map_err(|e| encode_failure(&e))?;
+    bytes.extend_from_slice(&header_len.to_le_bytes());
+    bytes.extend_from_slice(&header_json);
+
+    let mut writer = BitWriter::new();
+    maze.write_bits(&mut writer)?;
+    let maze_bytes = writer.into_bytes();
+    if maze_bytes.len() > MAX_DECOMPRESSED_LEN {
+        return Err(Error::EncodeFailure(format!(
+            "maze is larger than {} bytes",
+            MAX_DECOMPRESSED_LEN
+        )));
+    }
+    if compress {
+        let mut encoder = DeflateEncoder::new(bytes, Compression::best());
+        encoder
+            .write_all(&maze_bytes)
+            .map_err(|e| encode_failure(&e))?;
+        encoder.finish().map_err(|e| encode_failure(&e))
+    } else {
+        bytes.extend_from_slice(&maze_bytes);
+        Ok(bytes)
+    }
+}
+
+pub fn is_binary(bytes: &[u8]) -> bool {
+    bytes.starts_with(BINARY_MAGIC)
+}
+
+/// Read the header and the bits of maze from binary, the maze bits are decompressed if needed.
+pub fn split_binary(bytes: &[u8]) -> Result<(MazeHeader, Vec<u8>), Error> {
+    let broken = |reason: &str| Error::BrokenBinary(reason.to_string());
+    if !is_binary(bytes) {
+        return Err(broken("missing magic bytes"));
+    }
+    let (version, flags) = match bytes.get(4..6) {
+        Some([version, flags]) => (*version, *flags),
+        _ => return Err(broken("missing version")),
+    };
+    if version != BINARY_VERSION {
+        return Err(Error::UnsupportedVersion(u32::from(version)));
+    }
+    let header_len = bytes
+        .get(6..10)
+        .map(|len_bytes| u32::from_le_bytes(len_bytes.try_into().unwrap()) as usize)
+        .ok_or_else(|| broken("missing header"))?;
+    let header_bytes = bytes
+        .get(10..(10 + header_len))
+        .ok_or_else(|| broken("header is cut"))?;
+    let header: MazeHeader =
+        serde_json::from_slice(header_bytes).map_err(|e| Error::BrokenBinary(e.to_string()))?;
+    let maze_bytes = &bytes[(10 + header_len)..];
+    let maze_bytes = if flags & COMPRESSED_FLAG != 0 {
+        decompress(maze_bytes).map_err(Error::BrokenBinary)?
+    } else {
+        maze_bytes.to_vec()
+    };
+
+    Ok((header, maze_bytes))
+}
+
+/// Decompress bytes by deflate, fail if the decompressed bytes are longer than the limit.
+pub(crate) fn decompress(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut decompressed = Vec::new();
+    DeflateDecoder::new(bytes)
+        .take(MAX_DECOMPRESSED_LEN as u64 + 1)
+        .read_to_end(&mut decompressed)
+        .map_err(|e| e.to_string())?;
+    if decompressed.len() > MAX_DECOMPRESSED_LEN {
+        return Err(format!(
+            "decompressed maze is larger than {} bytes",
+            MAX_DECOMPRESSED_LEN
+        ));
+    }
+
+    Ok(decompressed)
+}
+
+impl<M: BinaryMaze> SavedMaze<M> {
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, Error> {
+        let (header, maze_bytes) = split_binary(bytes)?;
+        if header.kind != M::KIND {
+            return Err(Error::KindMismatch {
+                expected: M::KIND,
+                found: header.kind,
+            });
+        }
+
+        let maze = M::read_bits(&mut BitReader::new(&maze_bytes))?;
+        Ok(Self { header, maze })
+    }
+
+    pub fn to_binary(&self, compress: bool) -> Result<Vec<u8>, Error> {
+        to_binary(&self.header, &self.maze, compress)
+    }
+
+    pub fn save_binary<P: AsRef<Path>>(&self, path: P, compress: bool) -> Result<(), AnyError> {
+        fs::write(path, self.to_binary(compress)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use flate2::{Compression, write::DeflateEncoder};
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        file::{Error, MazeFile, MazeHeader, MazeKind, SavedMaze},
+        gene::{Maze2dGenerator, RecursiveBacktrackerMazeGenerator},
+        maze::{
+            any::AnyMaze,
+            circ::{CircGrid, CircMaze},
+            hexa::HexaMaze,
+        },
+    };
+
+    use super::{BinaryMaze, BitReader, BitWriter, MAX_DECOMPRESSED_LEN};
+
+    #[test]
+    fn test_binary_round_trip() {
+        let mut grid = CircGrid::new(6);
+        RecursiveBacktrackerMazeGenerator
+            .generate_2d(&mut grid)
+            .unwrap();
+        let mut header = MazeHeader::new(CircMaze::KIND);
+        header.seed = Some(7);
+        let saved = SavedMaze::new(header.clone(), CircMaze::new(grid));
+        let maze_json = serde_json::to_value(&saved.maze).unwrap();
+        for compress in [false, true] {
+            let bytes = saved.to_binary(compress).unwrap();
+            let loaded = SavedMaze::<CircMaze>::from_binary(&bytes).unwrap();
+            assert_eq!(loaded.header, header);
+            assert_eq!(serde_json::to_value(&loaded.maze).unwrap(), maze_json);
+
+            let loaded = AnyMaze::from_binary(&bytes).unwrap();
+            assert_eq!(loaded.maze.kind(), MazeKind::Circ);
+            assert_eq!(serde_json::to_value(&loaded.maze).unwrap(), maze_json);
+
+            assert!(matches!(
+                SavedMaze::<HexaMaze>::from_binary(&bytes),
+                Err(Error::KindMismatch { .. })
+            ));
+        }
+
+        // The maze bits take 2 bits per cell, much less than json.
+        let bytes = saved.to_binary(false).unwrap();
+        assert!(bytes.len() < serde_json::to_vec(&saved).unwrap().len() / 4);
+        for len in 0..bytes.len() {
+            assert!(SavedMaze::<CircMaze>::from_binary(&bytes[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_decompression_limit() {
+        let mut grid = CircGrid::new(3);
+        RecursiveBacktrackerMazeGenerator
+            .generate_2d(&mut grid)
+            .unwrap();
+        let saved = SavedMaze::new(MazeHeader::new(CircMaze::KIND), CircMaze::new(grid));
+        let mut bytes = saved.to_binary(true).unwrap();
+        let header_len = u32::from_le_bytes(bytes[6..10].try_into().unwrap()) as usize;
+        bytes.truncate(10 + header_len);
+        // Zeros are compressed into a few bytes, but decompressed beyond the limit.
+        let mut encoder = DeflateEncoder::new(bytes, Compression::best());
+        let zeros = vec![0; 1 << 20];
+        for _ in 0..=(MAX_DECOMPRESSED_LEN >> 20) {
+            encoder.write_all(&zeros).unwrap();
+        }
+        let bytes = encoder.finish().unwrap();
+        assert!(bytes.len() < MAX_DECOMPRESSED_LEN / 500);
+        assert!(matches!(
+            SavedMaze::<CircMaze>::from_binary(&bytes),
+            Err(Error::BrokenBinary(_))
+        ));
+    }
+
+    /// Maze of the given count of zero bytes, only to be encoded.
+    #[derive(Debug, Serialize, Deserialize)]
+    struct ZerosMaze(usize);
+
+    impl MazeFile for ZerosMaze {
+        const KIND: MazeKind = MazeKind::Rect;
+    }
+
+    impl BinaryMaze for ZerosMaze {
+        fn write_bits(&self, writer: &mut BitWriter) -> Result<(), Error> {
+            for _ in 0..self.0 {
+                writer.write_bits(0, 8);
+            }
+            Ok(())
+        }
+
+        fn read_bits(_reader: &mut BitReader) -> Result<Self, Error> {
+            unimplemented!()
+        }
+    }
+
+    #[test]
+    fn test_encode_limit() {
+        // The maze too large is refused whether compressed or not, so its file can always be read back.
+        let saved = SavedMaze::new(
+            MazeHeader::new(MazeKind::Rect),
+            ZerosMaze(MAX_DECOMPRESSED_LEN + 1),
+        );
+        for compress in [false, true] {
+            assert!(matches!(
+                saved.to_binary(compress),
+                Err(Error::EncodeFailure(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,255 @@
+use std::io::Write;
+
+use flate2::{Compression, write::DeflateEncoder};
+
+use crate::maze::{
+    Position2d,
+    bits::{BitReader, BitWriter},
+};
+
+use super::{
+    Error, MazeHeader, MazeKind, SavedMaze,
+    binary::{self, BinaryMaze},
+};
+
+const CODE_VERSION: u8 = 1;
+const COMPRESSED_FLAG: u8 = 1;
+// The version, the checksum and the flags.
+const CODE_HEADER_LEN: usize = 6;
+const BASE64_URL_CHARS: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Encode maze in a short url-safe(base64url) string to share, with only its kind, walls, mask and endpoints.
+pub fn to_code<M: BinaryMaze>(header: &MazeHeader, maze: &M) -> Result<String, Error> {
+    let mut writer = BitWriter::new();
+    let kind_ind = MazeKind::all_kinds()
+        .iter()
+        .position(|kind| *kind == M::KIND)
+        .unwrap();
+    writer.write_bits(kind_ind as u64, 3);
+    for endpoint in [header.start, header.goal] {
+        writer.write_bool(endpoint.is_some());
+        if let Some(Position2d(row, col)) = endpoint {
+            writer.write_usize(row)?;
+            writer.write_usize(col)?;
+        }
+    }
+    maze.write_bits(&mut writer)?;
+    let data = writer.into_bytes();
+
+    // Small mazes may get longer after compressed.
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    let encode_failure = |e: std::io::Error| Error::EncodeFailure(e.to_string());
+    encoder.write_all(&data).map_err(encode_failure)?;
+    let compressed = encoder.finish().map_err(encode_failure)?;
+    let (flags, data) = if compressed.len() < data.len() {
+        (COMPRESSED_FLAG, compressed)
+    } else {
+        (0, data)
+    };
+
+    let mut payload = vec![flags];
+    payload.extend(data);
+    let mut bytes = vec![CODE_VERSION];
+    bytes.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+    bytes.extend(payload);
+    Ok(encode_base64_url(&bytes))
+}
+
+impl<M: BinaryMaze> SavedMaze<M> {
+    pub fn to_code(&self) -> Result<String, Error> {
+        to_code(&self.header, &self.maze)
+    }
+
+    /// Decode maze from the string made by `to_code`, fail if the string is corrupt or has other kind of maze.
+    pub fn from_code(code: &str) -> Result<Self, Error> {
+        let invalid = |reason: &str| Error::InvalidCode(reason.to_string());
+        let bytes =
+            decode_base64_url(code.trim()).ok_or_else(|| invalid("not a base64url string"))?;
+        if bytes.len() < CODE_HEADER_LEN {
+            return Err(invalid("too short"));
+        }
+        if bytes[0] != CODE_VERSION {
+            return Err(Error::UnsupportedVersion(u32::from(bytes[0])));
+        }
+        let payload = &bytes[5..];
+        if crc32fast::hash(payload).to_le_bytes() != bytes[1..5] {
+            return Err(invalid(
+                "checksum mismatch, the code may be mistyped or cut",
+            ));
+        }
+        let data = if payload[0] & COMPRESSED_FLAG != 0 {
+            binary::decompress(&payload[1..]).map_err(Error::InvalidCode)?
+        } else {
+            payload[1..].to_vec()
+        };
+
+        let in_code = |e: Error| match e {
+            Error::BrokenBinary(reason) => Error::InvalidCode(reason),
+            other => other,
+        };
+        let mut reader = BitReader::new(&data);
+        let kind = MazeKind::all_kinds()
+            .get(reader.read_bits(3).map_err(|e| in_code(e.into()))? as usize)
+            .copied()
+            .ok_or_else(|| invalid("unknown maze kind"))?;
+        if kind != M::KIND {
+            return Err(Error::KindMismatch {
+                expected: M::KIND,
+                found: kind,
+            });
+        }
+        let mut endpoints = [None, None];
+        for endpoint in endpoints.iter_mut() {
+            if reader.read_bool().map_err(|e| in_code(e.into()))? {
+                *endpoint = Some(Position2d(
+                    reader.read_usize().map_err(|e| in_code(e.into()))?,
+                    reader.read_usize().map_err(|e| in_code(e.into()))?,
+                ));
+            }
+        }
+        let maze = M::read_bits(&mut reader).map_err(in_code)?;
+        // Only the padding of the last byte can be left.
+        if reader.remaining_bits_n() >= 8 {
+            return Err(invalid("unexpected data after maze"));
+        }
+
+        let [start, goal] = endpoints;
+        let header = MazeHeader {
+            timestamp: None,
+            start,
+            goal,
+            ..MazeHeader::new(kind)
+        };
+        Ok(Self { header, maze })
+    }
+}
+
+fn encode_base64_url(bytes: &[u8]) -> String {
+    let mut code = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (ind, byte)| {
+            group | u32::from(*byte) << (16 - ind * 8)
+        });
+        // Every 3 bytes make 4 characters, the last 1 or 2 bytes make 2 or 3 characters without padding.
+        for char_ind in 0..=chunk.len() {
+            let value = (group >> (18 - char_ind * 6)) & 0x3f;
+            code.push(char::from(BASE64_URL_CHARS[value as usize]));
+        }
+    }
+
+    code
+}
+
+fn decode_base64_url(code: &str) -> Option<Vec<u8>> {
+    let values = code
+        .bytes()
+        .map(|c| BASE64_URL_CHARS.iter().position(|code_c| *code_c == c))
+        .collect::<Option<Vec<_>>>()?;
+    let mut bytes = Vec::with_capacity(values.len() * 3 / 4);
+    for chunk in values.chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let group = chunk.iter().enumerate().fold(0u32, |group, (ind, value)| {
+            group | (*value as u32) << (18 - ind * 6)
+        });
+        for byte_ind in 0..(chunk.len() - 1) {
+            bytes.push((group >> (16 - byte_ind * 8)) as u8);
+        }
+    }
+
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use flate2::{Compression, write::DeflateEncoder};
+
+    use crate::{
+        file::{Error, MazeHeader, MazeKind, SavedMaze, binary::MAX_DECOMPRESSED_LEN},
+        gene::{Maze2dGenerator, WilsonMazeGenerator},
+        maze::{
+            Position2d, WithMask,
+            hexa::HexaMaze,
+            rect::{RectGrid, RectMask, RectMaze, RectPosition},
+        },
+    };
+
+    use super::{CODE_VERSION, COMPRESSED_FLAG, decode_base64_url, encode_base64_url};
+
+    #[test]
+    fn test_base64_url() {
+        for bytes in [&b""[..], b"f", b"fo", b"foo", b"foob", b"\xff\xfe\x00"] {
+            let code = encode_base64_url(bytes);
+            assert!(!code.contains('='));
+            assert_eq!(decode_base64_url(&code).as_deref(), Some(bytes));
+        }
+        assert_eq!(encode_base64_url(b"\xfb\xff"), "-_8");
+        assert_eq!(decode_base64_url("ab+c"), None);
+        assert_eq!(decode_base64_url("abcde"), None);
+    }
+
+    #[test]
+    fn test_code_round_trip() {
+        let mut mask = RectMask::new(12, 8);
+        for r in 0..8 {
+            for c in 0..12 {
+                mask.set_flag(&RectPosition::new(r, c), (r, c) != (3, 0));
+            }
+        }
+        let mut grid = RectGrid::<WithMask>::new(&mask);
+        WilsonMazeGenerator.generate_2d(&mut grid).unwrap();
+        let mut header = MazeHeader::new(MazeKind::Rect);
+        header.start = Some(Position2d(0, 0));
+        header.goal = Some(Position2d(7, 11));
+        let saved = SavedMaze::new(header, RectMaze::WithMask(grid));
+        let code = saved.to_code().unwrap();
+        assert!(
+            code.bytes()
+                .all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_')
+        );
+
+        let loaded = SavedMaze::<RectMaze>::from_code(&code).unwrap();
+        assert_eq!(loaded.header.start, saved.header.start);
+        assert_eq!(loaded.header.goal, saved.header.goal);
+        assert_eq!(
+            serde_json::to_value(&loaded.maze).unwrap(),
+            serde_json::to_value(&saved.maze).unwrap()
+        );
+
+        assert!(matches!(
+            SavedMaze::<HexaMaze>::from_code(&code),
+            Err(Error::KindMismatch { .. })
+        ));
+        let mut mistyped_code = code.clone();
+        let mistyped_char = if &code[10..11] == "A" { "B" } else { "A" };
+        mistyped_code.replace_range(10..11, mistyped_char);
+        for corrupt_code in [&code[..(code.len() - 2)], &mistyped_code, "not a code!", ""] {
+            assert!(matches!(
+                SavedMaze::<RectMaze>::from_code(corrupt_code),
+                Err(Error::InvalidCode(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_code_decompression_limit() {
+        // Zeros are compressed into a short code, but decompressed beyond the limit.
+        let mut encoder = DeflateEncoder::new(vec![COMPRESSED_FLAG], Compression::best());
+        let zeros = vec![0; 1 << 20];
+        for _ in 0..=(MAX_DECOMPRESSED_LEN >> 20) {
+            encoder.write_all(&zeros).unwrap();
+        }
+        let payload = encoder.finish().unwrap();
+        let mut bytes = vec![CODE_VERSION];
+        bytes.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+        bytes.extend(payload);
+        assert!(matches!(
+            SavedMaze::<RectMaze>::from_code(&encode_base64_url(&bytes)),
+            Err(Error::InvalidCode(_))
+        ));
+    }
+}
@@ -1,35 +1,51 @@
 use std::{
     cell::RefCell,
-    collections::{HashMap, HashSet, LinkedList},
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet, VecDeque},
+    fmt::{self, Display},
     hash::Hash,
     iter,
+    path::Path,
+    str::FromStr,
 };
 
-use rand::{Rng, seq::IteratorRandom};
+use anyhow::Error as AnyError;
 
-use crate::maze::{Grid2d, LayerGrid, Position2d};
+use clap::ValueEnum;
+use image::ImageReader;
+use rand::{
+    Rng,
+    seq::{IteratorRandom, SliceRandom},
+};
+use thiserror::Error;
+
+use crate::{
+    maze::{Grid2d, LayerGrid, Position2d},
+    rng,
+};
 
 pub mod circ;
+pub mod cube;
 pub mod hexa;
 pub mod rect;
 pub mod tri;
+pub mod voronoi;
 
 /// The most general generator for maze in 2D.
 pub trait Maze2dGenerator {
-    fn generate_2d(&self, grid: &mut dyn Grid2d);
+    /// Carve passages in the grid to make a perfect maze, fail if the grid is empty or has isolated areas.
+    fn generate_2d(&self, grid: &mut dyn Grid2d) -> Result<(), Error>;
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct AldousBroderMazeGenerator;
 
 impl Maze2dGenerator for AldousBroderMazeGenerator {
-    fn generate_2d(&self, grid: &mut dyn Grid2d) {
+    fn generate_2d(&self, grid: &mut dyn Grid2d) -> Result<(), Error> {
+        check_grid(grid)?;
         let mut visited_pos = HashSet::new();
-        let mut rng = rand::rng();
-        let Some(mut cur_pos) = grid.random_cell_pos() else {
-            // Empty grid
-            return;
-        };
+        let mut rng = rng::rng();
+        let mut cur_pos = grid.random_cell_pos().ok_or(Error::EmptyGrid)?;
         visited_pos.insert(cur_pos);
         let mut unvisited_cells_n = grid.cells_n() - 1;
         let mut neighbors = Vec::new();
@@ -39,7 +55,7 @@ impl Maze2dGenerator for AldousBroderMazeGenerator {
             let candidate = neighbors
                 .iter()
                 .choose(&mut rng)
-                .expect("There should be at least one neighbor in given non-empty grid.");
+                .ok_or(Error::DisconnectedGrid)?;
             if !visited_pos.contains(candidate) {
                 grid.connect_to(&cur_pos, candidate);
                 visited_pos.insert(*candidate);
@@ -48,6 +64,8 @@ impl Maze2dGenerator for AldousBroderMazeGenerator {
 
             cur_pos = *candidate;
         }
+
+        Ok(())
     }
 }
 
@@ -55,12 +73,15 @@ impl Maze2dGenerator for AldousBroderMazeGenerator {
 pub struct WilsonMazeGenerator;
 
 impl Maze2dGenerator for WilsonMazeGenerator {
-    fn generate_2d(&self, grid: &mut dyn Grid2d) {
-        let mut unvisited_pos = grid.all_cells_pos_set();
-        let mut rng = rand::rng();
-        let Some(first_visited_pos) = unvisited_pos.iter().choose(&mut rng).copied() else {
-            return;
-        };
+    fn generate_2d(&self, grid: &mut dyn Grid2d) -> Result<(), Error> {
+        check_grid(grid)?;
+        let mut unvisited_pos: BTreeSet<_> = grid.all_cells_pos_set().into_iter().collect();
+        let mut rng = rng::rng();
+        let first_visited_pos = unvisited_pos
+            .iter()
+            .choose(&mut rng)
+            .copied()
+            .ok_or(Error::EmptyGrid)?;
         unvisited_pos.remove(&first_visited_pos);
         let mut cur_path = Vec::new();
         let mut walk_visited_pos = HashMap::new();
@@ -78,7 +99,7 @@ impl Maze2dGenerator for WilsonMazeGenerator {
                 let candidate = neighbors
                     .iter()
                     .choose(&mut rng)
-                    .expect("There should be at least one neighbor in given non-empty grid.");
+                    .ok_or(Error::DisconnectedGrid)?;
                 walk_visited_pos.insert(cur_pos, cur_path.len());
                 cur_path.push(cur_pos);
                 if !unvisited_pos.contains(candidate) {
@@ -108,6 +129,8 @@ impl Maze2dGenerator for WilsonMazeGenerator {
                 unvisited_pos.remove(from);
             }
         }
+
+        Ok(())
     }
 }
 
@@ -115,12 +138,11 @@ impl Maze2dGenerator for WilsonMazeGenerator {
 pub struct HuntAndKillMazeGenerator;
 
 impl Maze2dGenerator for HuntAndKillMazeGenerator {
-    fn generate_2d(&self, grid: &mut dyn Grid2d) {
-        let mut unvisited_pos = grid.all_cells_pos_set();
-        let mut rng = rand::rng();
-        let Some(mut cur_pos) = grid.random_cell_pos() else {
-            return;
-        };
+    fn generate_2d(&self, grid: &mut dyn Grid2d) -> Result<(), Error> {
+        check_grid(grid)?;
+        let mut unvisited_pos: BTreeSet<_> = grid.all_cells_pos_set().into_iter().collect();
+        let mut rng = rng::rng();
+        let mut cur_pos = grid.random_cell_pos().ok_or(Error::EmptyGrid)?;
         let mut neighbors = Vec::new();
         while !unvisited_pos.is_empty() {
             // Kill phase.
@@ -159,6 +181,8 @@ impl Maze2dGenerator for HuntAndKillMazeGenerator {
             grid.connect_to(&prev_visited_pos, &next_start_pos);
             cur_pos = next_start_pos;
         }
+
+        Ok(())
     }
 }
 
@@ -166,11 +190,10 @@ impl Maze2dGenerator for HuntAndKillMazeGenerator {
 pub struct RecursiveBacktrackerMazeGenerator;
 
 impl Maze2dGenerator for RecursiveBacktrackerMazeGenerator {
-    fn generate_2d(&self, grid: &mut dyn Grid2d) {
-        let mut rng = rand::rng();
-        let Some(start_pos) = grid.random_cell_pos() else {
-            return;
-        };
+    fn generate_2d(&self, grid: &mut dyn Grid2d) -> Result<(), Error> {
+        check_grid(grid)?;
+        let mut rng = rng::rng();
+        let start_pos = grid.random_cell_pos().ok_or(Error::EmptyGrid)?;
         let mut visited_pos = HashSet::new();
         let mut unvisited_cells_n = grid.cells_n();
         let mut visited_stack = Vec::from_iter(iter::once(start_pos));
@@ -198,6 +221,8 @@ impl Maze2dGenerator for RecursiveBacktrackerMazeGenerator {
             grid.connect_to(&cur_pos, candidate);
             visited_stack.push(*candidate);
         }
+
+        Ok(())
     }
 }
 
@@ -219,7 +244,7 @@ impl MazeEdge {
 }
 
 /// A helper to find which set an element belongs after some arbitrary union operations. Every element starts from a set only contains itself.
-struct Union<T: Hash + Eq> {
+pub(crate) struct Union<T: Hash + Eq> {
     ele_inds: HashMap<T, usize>,
     set_ids: RefCell<Vec<usize>>,
     sets_n: usize,
@@ -278,29 +303,7 @@ impl<T: Hash + Eq> Union<T> {
         true
     }
 
-    pub fn into_sets(self) -> Vec<HashSet<T>> {
-        let set_id_to_ind: HashMap<_, _> = self
-            .set_ids
-            .borrow()
-            .iter()
-            .enumerate()
-            .filter(|(ele_ind, id)| ele_ind == *id)
-            .map(|(_, id)| *id)
-            .enumerate()
-            .map(|(set_ind, id)| (id, set_ind))
-            .collect();
-        let mut sets: Vec<_> = iter::repeat_with(|| HashSet::new())
-            .take(set_id_to_ind.len())
-            .collect();
-        for (pos, ind) in self.ele_inds {
-            let set_ind = set_id_to_ind[&Self::set_id(&self.set_ids, ind)];
-            sets[set_ind].insert(pos);
-        }
-
-        sets
-    }
-
-    fn is_union(&self, ele0: &T, ele1: &T) -> Option<bool> {
+    pub(crate) fn is_union(&self, ele0: &T, ele1: &T) -> Option<bool> {
         self.ele_set_id(ele0)
             .and_then(|set_ind0| self.ele_set_id(ele1).map(|set_ind1| set_ind0 == set_ind1))
     }
@@ -328,10 +331,11 @@ impl<T: Hash + Eq> Union<T> {
 pub struct KruskalMazeGenerator;
 
 impl Maze2dGenerator for KruskalMazeGenerator {
-    fn generate_2d(&self, grid: &mut dyn Grid2d) {
+    fn generate_2d(&self, grid: &mut dyn Grid2d) -> Result<(), Error> {
+        check_grid(grid)?;
         let all_pos = grid.all_cells_pos_set();
         let mut neighbors = Vec::new();
-        let mut edges = HashSet::new();
+        let mut edges = BTreeSet::new();
         // Find and save all neighbors(edges) in the maze.
         for pos in all_pos.iter() {
             neighbors.clear();
@@ -342,7 +346,7 @@ impl Maze2dGenerator for KruskalMazeGenerator {
                     .map(|neighbor| MazeEdge::new(pos, neighbor)),
             );
         }
-        let mut rng = rand::rng();
+        let mut rng = rng::rng();
         let mut cell_pos_union = Union::from_iter(all_pos);
         while cell_pos_union.sets_n() > 1 {
             // Select an edge randomly.
@@ -356,6 +360,8 @@ impl Maze2dGenerator for KruskalMazeGenerator {
 
             edges.remove(&edge);
         }
+
+        Ok(())
     }
 }
 
@@ -363,19 +369,18 @@ impl Maze2dGenerator for KruskalMazeGenerator {
 pub struct PrimMazeGenerator;
 
 impl Maze2dGenerator for PrimMazeGenerator {
-    fn generate_2d(&self, grid: &mut dyn Grid2d) {
+    fn generate_2d(&self, grid: &mut dyn Grid2d) -> Result<(), Error> {
+        check_grid(grid)?;
         let cells_n = grid.cells_n();
-        let Some(start_pos) = grid.random_cell_pos() else {
-            return;
-        };
+        let start_pos = grid.random_cell_pos().ok_or(Error::EmptyGrid)?;
         let mut neighbors = Vec::new();
         grid.append_neighbors(&start_pos, &mut neighbors);
-        let mut edges: HashSet<_> = neighbors
+        let mut edges: BTreeSet<_> = neighbors
             .iter()
             .map(|neighbor| MazeEdge::new(&start_pos, neighbor))
             .collect();
         let mut visited_pos: HashSet<_> = iter::once(start_pos).collect();
-        let mut rng = rand::rng();
+        let mut rng = rng::rng();
         while visited_pos.len() < cells_n {
             // Select an edge randomly.
             let Some(edge) = edges.iter().choose(&mut rng).cloned() else {
@@ -405,29 +410,202 @@ impl Maze2dGenerator for PrimMazeGenerator {
                     .map(|neighbor| MazeEdge::new(&to, neighbor)),
             );
         }
+
+        Ok(())
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
-pub struct GrowingTreeMazeGenerator;
+#[derive(Debug, Clone, Error)]
+pub enum Error {
+    #[error(
+        "Invalid growing tree strategy({0}), expect selections(newest, oldest, random or middle) with optional weights, e.g. \"newest:3,random:1\"."
+    )]
+    InvalidGrowingTreeStrategy(String),
+    #[error("Can't generate maze in an empty grid.")]
+    EmptyGrid,
+    #[error("Found isolated area in given grid, every cell should be reachable from the others.")]
+    DisconnectedGrid,
+    #[error("Algorithm {0} doesn't support mask.")]
+    NotSupportMask(String),
+}
+
+/// Check the grid has cells and all of them are connected as neighbors, so every generator can make a perfect maze.
+fn check_grid<G: Grid2d + ?Sized>(grid: &G) -> Result<(), Error> {
+    let start_pos = grid.random_cell_pos().ok_or(Error::EmptyGrid)?;
+    let mut visited_pos = HashSet::from([start_pos]);
+    let mut visit_stack = vec![start_pos];
+    let mut neighbors = Vec::new();
+    while let Some(pos) = visit_stack.pop() {
+        neighbors.clear();
+        grid.append_neighbors(&pos, &mut neighbors);
+        for neighbor in neighbors.iter() {
+            if visited_pos.insert(*neighbor) {
+                visit_stack.push(*neighbor);
+            }
+        }
+    }
+
+    if visited_pos.len() == grid.cells_n() {
+        Ok(())
+    } else {
+        Err(Error::DisconnectedGrid)
+    }
+}
+
+/// How to select the next active cell in growing tree algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
+pub enum GrowingTreeSelection {
+    /// The most recently added cell, which behaves like recursive backtracker
+    Newest,
+    /// The earliest added cell
+    Oldest,
+    /// A random cell, which behaves like Prim's algorithm
+    Random,
+    /// The cell in the middle of active cells
+    Middle,
+}
+
+impl Display for GrowingTreeSelection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            GrowingTreeSelection::Newest => "newest",
+            GrowingTreeSelection::Oldest => "oldest",
+            GrowingTreeSelection::Random => "random",
+            GrowingTreeSelection::Middle => "middle",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl GrowingTreeSelection {
+    fn select_ind(&self, active_n: usize, rng: &mut impl Rng) -> usize {
+        match self {
+            GrowingTreeSelection::Newest => active_n - 1,
+            GrowingTreeSelection::Oldest => 0,
+            GrowingTreeSelection::Random => rng.random_range(0..active_n),
+            GrowingTreeSelection::Middle => active_n / 2,
+        }
+    }
+}
+
+/// Weighted mix of selections in growing tree algorithm, written as "newest" or "newest:3,random:1".
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct GrowingTreeStrategy {
+    weighted_selections: Vec<(GrowingTreeSelection, u32)>,
+    total_weight: u32,
+}
+
+impl Default for GrowingTreeStrategy {
+    fn default() -> Self {
+        Self::new(GrowingTreeSelection::Random)
+    }
+}
+
+impl Display for GrowingTreeStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let [(selection, _)] = self.weighted_selections.as_slice() {
+            return write!(f, "{}", selection);
+        }
+
+        let mixes: Vec<_> = self
+            .weighted_selections
+            .iter()
+            .map(|(selection, weight)| format!("{}:{}", selection, weight))
+            .collect();
+        write!(f, "{}", mixes.join(","))
+    }
+}
+
+impl FromStr for GrowingTreeStrategy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid_strategy = || Error::InvalidGrowingTreeStrategy(s.to_string());
+        let weighted_selections = s
+            .split(',')
+            .map(|mix| {
+                let (name, weight) = match mix.split_once(':') {
+                    Some((name, weight)) => {
+                        (name, weight.trim().parse().map_err(|_| invalid_strategy())?)
+                    }
+                    None => (mix, 1),
+                };
+                let selection = GrowingTreeSelection::from_str(name.trim(), true)
+                    .map_err(|_| invalid_strategy())?;
+                Ok((selection, weight))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::with_weights(weighted_selections).ok_or_else(invalid_strategy)
+    }
+}
+
+impl GrowingTreeStrategy {
+    pub fn new(selection: GrowingTreeSelection) -> Self {
+        Self {
+            weighted_selections: vec![(selection, 1)],
+            total_weight: 1,
+        }
+    }
+
+    /// Mix selections by given weights, return None if there is no selection with positive weight, or the total
+    /// weight overflows.
+    pub fn with_weights(
+        weighted_selections: impl IntoIterator<Item = (GrowingTreeSelection, u32)>,
+    ) -> Option<Self> {
+        let weighted_selections: Vec<_> = weighted_selections
+            .into_iter()
+            .filter(|(_, weight)| *weight > 0)
+            .collect();
+        if weighted_selections.is_empty() {
+            return None;
+        }
+
+        let total_weight = weighted_selections
+            .iter()
+            .try_fold(0u32, |total, (_, weight)| total.checked_add(*weight))?;
+        Some(Self {
+            weighted_selections,
+            total_weight,
+        })
+    }
+
+    fn select_ind(&self, active_n: usize, rng: &mut impl Rng) -> usize {
+        let mut rest_weight = rng.random_range(0..self.total_weight);
+        for (selection, weight) in self.weighted_selections.iter() {
+            if rest_weight < *weight {
+                return selection.select_ind(active_n, rng);
+            }
+
+            rest_weight -= weight;
+        }
+
+        unreachable!("Random weight should be less than the total weight of selections.")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct GrowingTreeMazeGenerator {
+    strategy: GrowingTreeStrategy,
+}
 
 impl Maze2dGenerator for GrowingTreeMazeGenerator {
-    fn generate_2d(&self, grid: &mut dyn Grid2d) {
+    fn generate_2d(&self, grid: &mut dyn Grid2d) -> Result<(), Error> {
+        check_grid(grid)?;
         let cells_n = grid.cells_n();
-        let Some(start_pos) = grid.random_cell_pos() else {
-            return;
-        };
-        let mut active_pos: LinkedList<_> = iter::once(start_pos).collect();
+        let start_pos = grid.random_cell_pos().ok_or(Error::EmptyGrid)?;
+        // Active positions are ordered by the time added, both ends can be removed in constant time.
+        let mut active_pos: VecDeque<_> = iter::once(start_pos).collect();
         let mut visited_pos: HashSet<_> = iter::once(start_pos).collect();
-        let mut rng = rand::rng();
+        let mut rng = rng::rng();
         let mut neighbors = Vec::new();
         while visited_pos.len() < cells_n {
             if active_pos.is_empty() {
                 break;
             }
-            // Select an active position randomly.
-            let active_ind = rng.random_range(0..active_pos.len());
-            let pos = *active_pos.iter().nth(active_ind).unwrap();
+            // Select an active position by strategy.
+            let active_ind = self.strategy.select_ind(active_pos.len(), &mut rng);
+            let pos = active_pos[active_ind];
             // Select an unvisited neighbor randomly.
             neighbors.clear();
             grid.append_neighbors(&pos, &mut neighbors);
@@ -437,9 +615,7 @@ impl Maze2dGenerator for GrowingTreeMazeGenerator {
                 .choose(&mut rng)
             else {
                 // No unvisited neighbor is available, so remove the selected active position
-                let mut tail = active_pos.split_off(active_ind + 1);
-                active_pos.pop_back();
-                active_pos.append(&mut tail);
+                active_pos.remove(active_ind);
                 continue;
             };
 
@@ -448,55 +624,547 @@ impl Maze2dGenerator for GrowingTreeMazeGenerator {
             visited_pos.insert(*neighbor);
             grid.connect_to(&pos, neighbor);
         }
+
+        Ok(())
+    }
+}
+
+impl GrowingTreeMazeGenerator {
+    pub fn new(strategy: GrowingTreeStrategy) -> Self {
+        Self { strategy }
+    }
+}
+
+/// Weight(cost) of passage between two neighbor cells, weighted generators prefer passages with lower weights.
+pub trait PassageWeight {
+    fn weight(&self, from: &Position2d, to: &Position2d) -> u32;
+}
+
+impl<F: Fn(&Position2d, &Position2d) -> u32> PassageWeight for F {
+    fn weight(&self, from: &Position2d, to: &Position2d) -> u32 {
+        self(from, to)
+    }
+}
+
+impl PassageWeight for Box<dyn PassageWeight> {
+    fn weight(&self, from: &Position2d, to: &Position2d) -> u32 {
+        self.as_ref().weight(from, to)
+    }
+}
+
+/// Random weights, biased by whether the passage is in a layer(the first coordinate of position, e.g. row in
+/// rectangular grid, ring in circular grid, face in cube grid) or across layers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RandomWeight {
+    in_layer_max_weight: u32,
+    cross_layer_max_weight: u32,
+}
+
+impl PassageWeight for RandomWeight {
+    fn weight(&self, from: &Position2d, to: &Position2d) -> u32 {
+        let max_weight = if from.0 == to.0 {
+            self.in_layer_max_weight
+        } else {
+            self.cross_layer_max_weight
+        };
+        rng::rng().random_range(0..=max_weight)
+    }
+}
+
+impl RandomWeight {
+    pub fn new(in_layer_max_weight: u32, cross_layer_max_weight: u32) -> Self {
+        Self {
+            in_layer_max_weight,
+            cross_layer_max_weight,
+        }
+    }
+
+    pub fn uniform(max_weight: u32) -> Self {
+        Self::new(max_weight, max_weight)
+    }
+}
+
+/// Weights of cells, the weight of passage is the sum of weights of its two cells(saturated at `u32::MAX`).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CellWeights {
+    weights: HashMap<Position2d, u32>,
+}
+
+impl PassageWeight for CellWeights {
+    fn weight(&self, from: &Position2d, to: &Position2d) -> u32 {
+        self.cell_weight(from).saturating_add(self.cell_weight(to))
+    }
+}
+
+impl FromIterator<(Position2d, u32)> for CellWeights {
+    fn from_iter<I: IntoIterator<Item = (Position2d, u32)>>(iter: I) -> Self {
+        Self {
+            weights: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl CellWeights {
+    /// Weigh cells by the brightness of image, so passages through darker pixels are preferred. Every cell takes the
+    /// pixel at the point given by `cell_point`, which is relative to the image size(in [0, 1]), cells without point
+    /// weigh nothing.
+    pub fn from_image<P: AsRef<Path>, F: Fn(&Position2d) -> Option<(f64, f64)>>(
+        path: P,
+        grid: &dyn Grid2d,
+        cell_point: F,
+    ) -> Result<Self, AnyError> {
+        let image = ImageReader::open(path)?.decode()?.to_luma8();
+        let (width, height) = image.dimensions();
+        let pixel_ind =
+            |ratio: f64, len: u32| ((ratio * f64::from(len)) as u32).min(len.saturating_sub(1));
+        Ok(grid
+            .all_cells_pos_set()
+            .into_iter()
+            .filter_map(|pos| {
+                let (x, y) = cell_point(&pos)?;
+                let pixel = image.get_pixel_checked(pixel_ind(x, width), pixel_ind(y, height))?;
+                Some((pos, u32::from(pixel.0[0])))
+            })
+            .collect())
+    }
+
+    /// Weigh cells by the brightness of image, the grid is stretched onto the image layer by layer.
+    pub fn from_layer_image<P: AsRef<Path>>(
+        path: P,
+        grid: &dyn LayerGrid,
+    ) -> Result<Self, AnyError> {
+        let layers_n = grid.layers_n();
+        Self::from_image(path, grid, |pos| {
+            Some((
+                (pos.1 as f64 + 0.5) / grid.cells_n_at(pos.0) as f64,
+                (pos.0 as f64 + 0.5) / layers_n as f64,
+            ))
+        })
+    }
+
+    pub fn cell_weight(&self, pos: &Position2d) -> u32 {
+        self.weights.get(pos).copied().unwrap_or(0)
+    }
+}
+
+/// Prim's algorithm always expanding the maze by the passage with the lowest weight, ties are broken randomly.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WeightedPrimMazeGenerator<W: PassageWeight> {
+    weight: W,
+}
+
+impl<W: PassageWeight> Maze2dGenerator for WeightedPrimMazeGenerator<W> {
+    fn generate_2d(&self, grid: &mut dyn Grid2d) -> Result<(), Error> {
+        check_grid(grid)?;
+        let cells_n = grid.cells_n();
+        let start_pos = grid.random_cell_pos().ok_or(Error::EmptyGrid)?;
+        let mut rng = rng::rng();
+        let mut edges = BinaryHeap::new();
+        let mut visited_pos = HashSet::new();
+        let mut neighbors = Vec::new();
+        let mut to = start_pos;
+        loop {
+            // Add edges from the newly visited position to unvisited neighbors as candidates.
+            visited_pos.insert(to);
+            neighbors.clear();
+            grid.append_neighbors(&to, &mut neighbors);
+            edges.extend(
+                neighbors
+                    .iter()
+                    .filter(|neighbor| !visited_pos.contains(neighbor))
+                    .map(|neighbor| {
+                        Reverse((
+                            self.weight.weight(&to, neighbor),
+                            rng.random::<u32>(),
+                            to,
+                            *neighbor,
+                        ))
+                    }),
+            );
+            if visited_pos.len() >= cells_n {
+                break;
+            }
+
+            // Select the edge with the lowest weight, which connects to an unvisited position.
+            let Some((from, next_to)) = iter::from_fn(|| edges.pop())
+                .map(|Reverse((_, _, from, to))| (from, to))
+                .find(|(_, to)| !visited_pos.contains(to))
+            else {
+                break;
+            };
+            grid.connect_to(&from, &next_to);
+            to = next_to;
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: PassageWeight> WeightedPrimMazeGenerator<W> {
+    pub fn new(weight: W) -> Self {
+        Self { weight }
+    }
+}
+
+/// Kruskal's algorithm connecting passages in the order of their weights, ties are broken randomly.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WeightedKruskalMazeGenerator<W: PassageWeight> {
+    weight: W,
+}
+
+impl<W: PassageWeight> Maze2dGenerator for WeightedKruskalMazeGenerator<W> {
+    fn generate_2d(&self, grid: &mut dyn Grid2d) -> Result<(), Error> {
+        check_grid(grid)?;
+        let all_pos = grid.all_cells_pos_set();
+        let mut neighbors = Vec::new();
+        let mut edges = BTreeSet::new();
+        // Find and save all neighbors(edges) in the maze.
+        for pos in all_pos.iter() {
+            neighbors.clear();
+            grid.append_neighbors(pos, &mut neighbors);
+            edges.extend(
+                neighbors
+                    .iter()
+                    .map(|neighbor| MazeEdge::new(pos, neighbor)),
+            );
+        }
+        let mut rng = rng::rng();
+        let mut weighted_edges: Vec<_> = edges
+            .into_iter()
+            .map(|edge| {
+                let weight = self.weight.weight(&edge.low, &edge.high);
+                (weight, rng.random::<u32>(), edge)
+            })
+            .collect();
+        weighted_edges.sort_unstable();
+
+        let mut cell_pos_union = Union::from_iter(all_pos);
+        for (_, _, edge) in weighted_edges {
+            if cell_pos_union.sets_n() <= 1 {
+                break;
+            }
+
+            if cell_pos_union.merge(&edge.low, &edge.high) {
+                // The edge can connect two different areas in the current maze, connect it to merge these two areas.
+                grid.connect_to(&edge.low, &edge.high);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: PassageWeight> WeightedKruskalMazeGenerator<W> {
+    pub fn new(weight: W) -> Self {
+        Self { weight }
+    }
+}
+
+const ORIGIN_SHIFTS_N_PER_CELL: usize = 20;
+
+/// Origin shift algorithm, starts from a simple perfect maze and shifts its origin many times to randomize it.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct OriginShiftMazeGenerator;
+
+impl Maze2dGenerator for OriginShiftMazeGenerator {
+    fn generate_2d(&self, grid: &mut dyn Grid2d) -> Result<(), Error> {
+        check_grid(grid)?;
+        let shifts_n = grid.cells_n() * ORIGIN_SHIFTS_N_PER_CELL;
+        if let Some(mut origin_shift) = OriginShift::new(grid) {
+            origin_shift.shift(grid, shifts_n);
+        }
+
+        Ok(())
+    }
+}
+
+/// Perfect maze kept as a directed tree, every cell points to its parent except the origin(root). Moving the
+/// origin to a random neighbor keeps the maze perfect, so the maze can be changed continuously.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OriginShift {
+    origin: Position2d,
+    parents: HashMap<Position2d, Position2d>,
+}
+
+impl OriginShift {
+    /// Build the tree from passages in grid with a random origin, walls are broken to reach cells not connected,
+    /// and passages making loops are walled, so the maze becomes perfect. Only the cells reachable from the
+    /// origin are in the tree.
+    pub fn new(grid: &mut dyn Grid2d) -> Option<Self> {
+        let origin = grid.random_cell_pos()?;
+        let mut parents = HashMap::new();
+        let mut visited_pos = HashSet::new();
+        // Expand through passages first, and only break walls when no passage leads to unvisited cells.
+        let mut candidates = VecDeque::from([(origin, None)]);
+        let mut neighbors = Vec::new();
+        while let Some((pos, parent)) = candidates.pop_front() {
+            if !visited_pos.insert(pos) {
+                continue;
+            }
+
+            if let Some(parent) = parent {
+                grid.connect_to(&pos, &parent);
+                parents.insert(pos, parent);
+            }
+            neighbors.clear();
+            grid.append_neighbors(&pos, &mut neighbors);
+            for neighbor in neighbors.iter().filter(|pos| !visited_pos.contains(pos)) {
+                if grid.is_connected_to(&pos, neighbor) {
+                    candidates.push_front((*neighbor, Some(pos)));
+                } else {
+                    candidates.push_back((*neighbor, Some(pos)));
+                }
+            }
+        }
+
+        // Wall the passages not in tree.
+        for pos in visited_pos.iter() {
+            neighbors.clear();
+            grid.append_neighbors(pos, &mut neighbors);
+            for neighbor in neighbors.iter() {
+                if parents.get(pos) != Some(neighbor) && parents.get(neighbor) != Some(pos) {
+                    grid.disconnect_from(pos, neighbor);
+                }
+            }
+        }
+
+        Some(Self { origin, parents })
+    }
+
+    pub fn origin(&self) -> Position2d {
+        self.origin
+    }
+
+    /// Move the origin to a random neighbor for given times.
+    pub fn shift(&mut self, grid: &mut dyn Grid2d, shifts_n: usize) {
+        let mut rng = rng::rng();
+        let mut neighbors = Vec::new();
+        for _ in 0..shifts_n {
+            neighbors.clear();
+            grid.append_neighbors(&self.origin, &mut neighbors);
+            let Some(new_origin) = neighbors.iter().choose(&mut rng).copied() else {
+                return;
+            };
+
+            // The old origin points to the new one, which drops the passage to its parent.
+            grid.connect_to(&self.origin, &new_origin);
+            self.parents.insert(self.origin, new_origin);
+            if let Some(parent) = self.parents.remove(&new_origin)
+                && parent != self.origin
+            {
+                grid.disconnect_from(&new_origin, &parent);
+            }
+            self.origin = new_origin;
+        }
+    }
+}
+
+/// Recursive division on arbitrary grid, each region is divided into two connected sub-regions grown from two
+/// random seeds, and only one passage is left between them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RegionDivisionMazeGenerator {
+    room_max_cells_n: usize,
+}
+
+impl Maze2dGenerator for RegionDivisionMazeGenerator {
+    fn generate_2d(&self, grid: &mut dyn Grid2d) -> Result<(), Error> {
+        check_grid(grid)?;
+        let mut rng = rng::rng();
+        let mut neighbors = Vec::new();
+        // Start from the connected areas in grid, so every region to divide is connected.
+        let mut regions = Self::connected_regions(grid);
+        while let Some(region) = regions.pop() {
+            if self.is_room(&region) {
+                // Break all the walls in the current region to make a room.
+                for pos in region.iter() {
+                    neighbors.clear();
+                    grid.append_neighbors(pos, &mut neighbors);
+                    for neighbor in neighbors.iter().filter(|pos| region.contains(pos)) {
+                        grid.connect_to(pos, neighbor);
+                    }
+                }
+                continue;
+            }
+
+            // Grow two sub-regions from two random seeds, by picking the cell to expand randomly.
+            let mut in_first_region = HashMap::new();
+            let mut frontier = Vec::new();
+            for (seed, is_first) in region
+                .iter()
+                .choose_multiple(&mut rng, 2)
+                .into_iter()
+                .zip([true, false])
+            {
+                in_first_region.insert(*seed, is_first);
+                frontier.push(*seed);
+            }
+            while !frontier.is_empty() {
+                let pos = frontier.swap_remove(rng.random_range(0..frontier.len()));
+                let is_first = in_first_region[&pos];
+                neighbors.clear();
+                grid.append_neighbors(&pos, &mut neighbors);
+                for neighbor in neighbors.iter() {
+                    if region.contains(neighbor) && !in_first_region.contains_key(neighbor) {
+                        in_first_region.insert(*neighbor, is_first);
+                        frontier.push(*neighbor);
+                    }
+                }
+            }
+
+            // Leave one passage in the wall between two sub-regions.
+            let (first_region, second_region): (BTreeSet<_>, BTreeSet<_>) =
+                region.into_iter().partition(|pos| in_first_region[pos]);
+            let mut passages = Vec::new();
+            for pos in first_region.iter() {
+                neighbors.clear();
+                grid.append_neighbors(pos, &mut neighbors);
+                passages.extend(
+                    neighbors
+                        .iter()
+                        .filter(|neighbor| second_region.contains(neighbor))
+                        .map(|neighbor| (*pos, *neighbor)),
+                );
+            }
+            if let Some((from, to)) = passages.iter().choose(&mut rng) {
+                grid.connect_to(from, to);
+            }
+
+            regions.push(first_region);
+            regions.push(second_region);
+        }
+
+        Ok(())
+    }
+}
+
+impl RegionDivisionMazeGenerator {
+    pub fn new(room_max_cells_n: usize) -> Self {
+        Self { room_max_cells_n }
+    }
+
+    fn is_room(&self, region: &BTreeSet<Position2d>) -> bool {
+        region.len() <= 1 || region.len() <= self.room_max_cells_n
+    }
+
+    fn connected_regions(grid: &dyn Grid2d) -> Vec<BTreeSet<Position2d>> {
+        let mut unvisited_pos: BTreeSet<_> = grid.all_cells_pos_set().into_iter().collect();
+        let mut regions = Vec::new();
+        let mut neighbors = Vec::new();
+        while let Some(start_pos) = unvisited_pos.iter().next().copied() {
+            unvisited_pos.remove(&start_pos);
+            let mut region = BTreeSet::from([start_pos]);
+            let mut stack = vec![start_pos];
+            while let Some(pos) = stack.pop() {
+                neighbors.clear();
+                grid.append_neighbors(&pos, &mut neighbors);
+                for neighbor in neighbors.iter() {
+                    if unvisited_pos.remove(neighbor) {
+                        region.insert(*neighbor);
+                        stack.push(*neighbor);
+                    }
+                }
+            }
+            regions.push(region);
+        }
+
+        regions
     }
 }
 
 pub trait LayerMazeGenerator {
-    fn generate_layer(&self, grid: &mut dyn LayerGrid);
+    /// Carve passages in the grid layer by layer, fail if the grid is empty or has isolated areas.
+    fn generate_layer(&self, grid: &mut dyn LayerGrid) -> Result<(), Error>;
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct EllerMazeGenerator;
 
 impl LayerMazeGenerator for EllerMazeGenerator {
-    fn generate_layer(&self, grid: &mut dyn LayerGrid) {
+    fn generate_layer(&self, grid: &mut dyn LayerGrid) -> Result<(), Error> {
+        check_grid(grid)?;
         let layers_n = grid.layers_n();
-        let mut layer_union = Union::new();
-        let mut rng = rand::rng();
+        // The sets of positions connected in the maze so far.
+        let mut maze_union = Union::new();
+        let mut rng = rng::rng();
+        let mut layer_cells = Vec::new();
         let mut lower_neighbors = Vec::new();
         for layer_ind in 0..layers_n {
-            let layer_cells_n = grid.cells_n_at(layer_ind);
-            for cell_ind in 0..layer_cells_n {
-                let pos = Position2d(layer_ind, cell_ind);
-                layer_union.add(pos);
-                let Some(last_neighbor) = grid.last_neighbor_pos(&pos) else {
+            let is_last_layer = layer_ind == layers_n - 1;
+            layer_cells.clear();
+            layer_cells.extend(
+                (0..grid.cells_n_at(layer_ind))
+                    .map(|cell_ind| Position2d(layer_ind, cell_ind))
+                    .filter(|pos| grid.is_cell(pos)),
+            );
+            for pos in layer_cells.iter() {
+                maze_union.add(*pos);
+            }
+
+            for pos in layer_cells.iter() {
+                let Some(last_neighbor) = grid.last_neighbor_pos(pos) else {
                     continue;
                 };
-                layer_union.add(last_neighbor);
                 // If these two positions aren't in the same set, they can be connected,
                 // if the current layer is the last layer, they should be connected, otherwise, there will be some unconnected area in the final maze.
                 // At the other layers, they will be connected randomly with a probability of 1/2.
-                let should_connect = !layer_union.is_union(&pos, &last_neighbor).unwrap()
-                    && (layer_ind == layers_n - 1 || rng.random_ratio(1, 2));
+                let should_connect = !maze_union.is_union(pos, &last_neighbor).unwrap()
+                    && (is_last_layer || rng.random_ratio(1, 2));
                 if should_connect {
                     // If connected, the sets they belongs to also should be united.
-                    grid.connect_to(&last_neighbor, &pos);
-                    layer_union.merge(&pos, &last_neighbor);
+                    grid.connect_to(&last_neighbor, pos);
+                    maze_union.merge(pos, &last_neighbor);
                 }
             }
 
-            if layer_ind == layers_n - 1 {
+            if is_last_layer {
                 // There's no next layer, exit.
                 break;
             }
 
-            let mut next_layer_union = Union::new();
+            // The set can't extend to the next layer if none of its positions has a lower neighbor,
+            // so connect it to an adjacent set in the current layer as long as possible.
+            let has_lower_neighbor = |pos: &Position2d| {
+                let mut lower_neighbors = Vec::new();
+                grid.append_neighbors_lower_layer(pos, &mut lower_neighbors);
+                !lower_neighbors.is_empty()
+            };
+            let diggable_cells: Vec<_> = layer_cells
+                .iter()
+                .filter(|pos| has_lower_neighbor(pos))
+                .copied()
+                .collect();
+            loop {
+                let diggable_set_ids: HashSet<_> = diggable_cells
+                    .iter()
+                    .map(|pos| maze_union.ele_set_id(pos).unwrap())
+                    .collect();
+                let Some((pos, neighbor)) = layer_cells
+                    .iter()
+                    .filter(|pos| !diggable_set_ids.contains(&maze_union.ele_set_id(pos).unwrap()))
+                    .flat_map(|pos| {
+                        [grid.last_neighbor_pos(pos), grid.next_neighbor_pos(pos)]
+                            .into_iter()
+                            .flatten()
+                            .map(|neighbor| (*pos, neighbor))
+                    })
+                    .find(|(pos, neighbor)| !maze_union.is_union(pos, neighbor).unwrap())
+                else {
+                    break;
+                };
+                grid.connect_to(&pos, &neighbor);
+                maze_union.merge(&pos, &neighbor);
+            }
+
             // Get the final sets in which the positions are connected in the current layer.
-            let sets = layer_union.into_sets();
-            for set in sets {
+            let mut sets = BTreeMap::<_, Vec<_>>::new();
+            for pos in diggable_cells.iter() {
+                sets.entry(maze_union.ele_set_id(pos).unwrap())
+                    .or_default()
+                    .push(*pos);
+            }
+            for set in sets.into_values() {
                 // Randomly choose a position to connect to next layer, to ensure at least one position in the current set connects to the next layer.
                 let dig_pos = set.iter().choose(&mut rng).cloned().unwrap();
-                let mut first_dig_target = None;
                 for pos in set.into_iter() {
                     // Positions which aren't chosen also can connect to the next layer randomly with a probability of 1/3.
                     let should_dig = pos == dig_pos || rng.random_ratio(1, 3);
@@ -505,17 +1173,682 @@ impl LayerMazeGenerator for EllerMazeGenerator {
                         lower_neighbors.clear();
                         grid.append_neighbors_lower_layer(&pos, &mut lower_neighbors);
                         let dig_neighbor = lower_neighbors.iter().choose(&mut rng).unwrap();
-                        grid.connect_to(&pos, dig_neighbor);
-                        // Add the connected target neighbor in the current set to the same set.
-                        next_layer_union.add(*dig_neighbor);
-                        next_layer_union
-                            .merge(dig_neighbor, first_dig_target.get_or_insert(*dig_neighbor));
+                        maze_union.add(*dig_neighbor);
+                        if !maze_union.is_union(&pos, dig_neighbor).unwrap() {
+                            grid.connect_to(&pos, dig_neighbor);
+                            maze_union.merge(&pos, dig_neighbor);
+                        }
                     }
                 }
             }
+        }
+
+        // Some sets may be isolated before(e.g. a run of cells which has no neighbor in the lower layer and the current layer),
+        // connect them to the rest, to make the maze perfect.
+        connect_isolated_sets(grid, &mut maze_union, &mut rng);
+
+        Ok(())
+    }
+}
+
+/// The pair of directions to carve passages in the layer grid, used by the binary tree and the sidewinder algorithm.
+/// The north is to the upper layer, and the east is to the next cell in the same layer(e.g. inward and clockwise in the circular grid).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum, Hash)]
+pub enum DiagonalDirection {
+    Northeast,
+    Southeast,
+    Southwest,
+    Northwest,
+}
+
+impl DiagonalDirection {
+    /// The neighbor to extend the run in the same layer, a run never wraps around the layer.
+    fn run_neighbor_pos(&self, grid: &dyn LayerGrid, pos: &Position2d) -> Option<Position2d> {
+        match self {
+            DiagonalDirection::Northeast | DiagonalDirection::Southeast => grid
+                .next_neighbor_pos(pos)
+                .filter(|neighbor| neighbor.1 > pos.1),
+            DiagonalDirection::Southwest | DiagonalDirection::Northwest => grid
+                .last_neighbor_pos(pos)
+                .filter(|neighbor| neighbor.1 < pos.1),
+        }
+    }
+
+    /// The neighbors to carve out of the run, in the upper or the lower layer.
+    fn append_out_neighbors(
+        &self,
+        grid: &dyn LayerGrid,
+        pos: &Position2d,
+        neighbors: &mut Vec<Position2d>,
+    ) {
+        match self {
+            DiagonalDirection::Northeast | DiagonalDirection::Northwest => {
+                grid.append_neighbors_upper_layer(pos, neighbors)
+            }
+            DiagonalDirection::Southeast | DiagonalDirection::Southwest => {
+                grid.append_neighbors_lower_layer(pos, neighbors)
+            }
+        }
+    }
 
-            // The initial connection settings in the next layer.
-            layer_union = next_layer_union;
+    /// Cells in the layer, in the order of the run.
+    fn layer_cells_pos(&self, grid: &dyn LayerGrid, layer_ind: usize) -> Vec<Position2d> {
+        let mut cells_pos: Vec<_> = (0..grid.cells_n_at(layer_ind))
+            .map(|cell_ind| Position2d(layer_ind, cell_ind))
+            .filter(|pos| grid.is_cell(pos))
+            .collect();
+        if matches!(
+            self,
+            DiagonalDirection::Southwest | DiagonalDirection::Northwest
+        ) {
+            cells_pos.reverse();
         }
+        cells_pos
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BTreeMazeGenerator {
+    con_dir: DiagonalDirection,
+}
+
+impl BTreeMazeGenerator {
+    pub fn new(con_dir: DiagonalDirection) -> Self {
+        Self { con_dir }
+    }
+}
+
+impl LayerMazeGenerator for BTreeMazeGenerator {
+    fn generate_layer(&self, grid: &mut dyn LayerGrid) -> Result<(), Error> {
+        check_grid(grid)?;
+        let mut maze_union: Union<_> = grid.all_cells_pos_set().into_iter().collect();
+        let mut rng = rng::rng();
+        let mut out_neighbors = Vec::new();
+        for layer_ind in 0..grid.layers_n() {
+            for pos in self.con_dir.layer_cells_pos(grid, layer_ind) {
+                let run_neighbor = self.con_dir.run_neighbor_pos(grid, &pos);
+                out_neighbors.clear();
+                self.con_dir
+                    .append_out_neighbors(grid, &pos, &mut out_neighbors);
+                let out_neighbor = out_neighbors.iter().choose(&mut rng).copied();
+                let connect_target = match (run_neighbor, out_neighbor) {
+                    // Choose a direction equally likely to connect.
+                    (Some(run_neighbor), Some(out_neighbor)) => {
+                        if rng.random::<bool>() {
+                            run_neighbor
+                        } else {
+                            out_neighbor
+                        }
+                    }
+                    // Can only connect along one direction, or neither at the border.
+                    (run_neighbor, out_neighbor) => {
+                        let Some(neighbor) = run_neighbor.or(out_neighbor) else {
+                            continue;
+                        };
+                        neighbor
+                    }
+                };
+                grid.connect_to(&pos, &connect_target);
+                maze_union.merge(&pos, &connect_target);
+            }
+        }
+
+        // There're several roots if some cells can connect along neither direction(e.g. at the gap of mask).
+        connect_isolated_sets(grid, &mut maze_union, &mut rng);
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SidewinderMazeGenerator {
+    con_dir: DiagonalDirection,
+}
+
+impl SidewinderMazeGenerator {
+    pub fn new(con_dir: DiagonalDirection) -> Self {
+        Self { con_dir }
+    }
+}
+
+impl LayerMazeGenerator for SidewinderMazeGenerator {
+    fn generate_layer(&self, grid: &mut dyn LayerGrid) -> Result<(), Error> {
+        check_grid(grid)?;
+        let mut maze_union: Union<_> = grid.all_cells_pos_set().into_iter().collect();
+        let mut rng = rng::rng();
+        let mut out_neighbors = Vec::new();
+        // Positions in the current run which can break out.
+        let mut run_out_cells = Vec::new();
+        for layer_ind in 0..grid.layers_n() {
+            run_out_cells.clear();
+            for pos in self.con_dir.layer_cells_pos(grid, layer_ind) {
+                out_neighbors.clear();
+                self.con_dir
+                    .append_out_neighbors(grid, &pos, &mut out_neighbors);
+                if !out_neighbors.is_empty() {
+                    run_out_cells.push(pos);
+                }
+
+                let run_neighbor = self.con_dir.run_neighbor_pos(grid, &pos);
+                let close_out =
+                    !run_out_cells.is_empty() && (run_neighbor.is_none() || rng.random::<bool>());
+                if close_out {
+                    // Select a position to break out(connect to other layers) equally likely in the current run.
+                    let out_pos = run_out_cells.iter().choose(&mut rng).copied().unwrap();
+                    out_neighbors.clear();
+                    self.con_dir
+                        .append_out_neighbors(grid, &out_pos, &mut out_neighbors);
+                    let out_neighbor = out_neighbors.iter().choose(&mut rng).copied().unwrap();
+                    grid.connect_to(&out_pos, &out_neighbor);
+                    maze_union.merge(&out_pos, &out_neighbor);
+                    run_out_cells.clear();
+                } else if let Some(run_neighbor) = run_neighbor {
+                    // if not going to connect to other layers, connect to the neighbor in the same layer.
+                    grid.connect_to(&pos, &run_neighbor);
+                    maze_union.merge(&pos, &run_neighbor);
+                } else {
+                    // The run can neither extend nor break out, it will be connected at last.
+                    run_out_cells.clear();
+                }
+            }
+        }
+
+        connect_isolated_sets(grid, &mut maze_union, &mut rng);
+
+        Ok(())
+    }
+}
+
+/// Connect the sets of positions which are isolated from each other, to make the maze perfect.
+fn connect_isolated_sets<G: Grid2d + ?Sized>(
+    grid: &mut G,
+    maze_union: &mut Union<Position2d>,
+    rng: &mut impl Rng,
+) {
+    if maze_union.sets_n() <= 1 {
+        return;
+    }
+
+    let mut all_pos: Vec<_> = grid.all_cells_pos_set().into_iter().collect();
+    all_pos.sort();
+    all_pos.shuffle(rng);
+    let mut neighbors = Vec::new();
+    for pos in all_pos {
+        neighbors.clear();
+        grid.append_neighbors(&pos, &mut neighbors);
+        for neighbor in neighbors.iter() {
+            if !maze_union.is_union(&pos, neighbor).unwrap() {
+                grid.connect_to(&pos, neighbor);
+                maze_union.merge(&pos, neighbor);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::maze::{
+        Grid2d, LayerGrid, Maze, NoMask, Position2d, WithMask,
+        circ::CircGrid,
+        cube::{CubeFace, CubeGrid},
+        hexa::{HexaGrid, HexaMaze, HexaPosition},
+        rect::{RectDirection, RectGrid, RectMask, RectMaze, RectPosition},
+        tri::{TriDirection, TriGrid, TriPosition},
+    };
+
+    use clap::ValueEnum;
+    use image::{GrayImage, Luma};
+
+    use super::{
+        AldousBroderMazeGenerator, BTreeMazeGenerator, CellWeights, DiagonalDirection,
+        EllerMazeGenerator, Error, GrowingTreeMazeGenerator, GrowingTreeSelection,
+        GrowingTreeStrategy, HuntAndKillMazeGenerator, KruskalMazeGenerator, LayerMazeGenerator,
+        Maze2dGenerator, OriginShift, OriginShiftMazeGenerator, PassageWeight, PrimMazeGenerator,
+        RandomWeight, RecursiveBacktrackerMazeGenerator, RegionDivisionMazeGenerator,
+        SidewinderMazeGenerator, WeightedKruskalMazeGenerator, WeightedPrimMazeGenerator,
+        WilsonMazeGenerator,
+        rect::{RectLayerMazeGenerator, RectMazeGenerator, RecursiveDivisionMazeGenerator},
+        tri::{TriLayerMazeGenerator, TriMazeGenerator},
+    };
+
+    /// Check the maze is perfect, by counting passages and cells reachable from the first cell.
+    fn assert_perfect<P: Copy + Eq + std::hash::Hash>(
+        cells: &[P],
+        connected_neighbors: impl Fn(&P) -> Vec<P>,
+    ) {
+        let passages_n: usize = cells.iter().map(|pos| connected_neighbors(pos).len()).sum();
+        assert_eq!(passages_n, (cells.len() - 1) * 2);
+        let mut visited = HashSet::from([cells[0]]);
+        let mut stack = vec![cells[0]];
+        while let Some(pos) = stack.pop() {
+            for neighbor in connected_neighbors(&pos) {
+                if visited.insert(neighbor) {
+                    stack.push(neighbor);
+                }
+            }
+        }
+        assert_eq!(visited.len(), cells.len());
+    }
+
+    fn make_test_mask() -> RectMask {
+        // The masked out positions split rows into several runs, and leave some cells without lower neighbors.
+        let masked_pos = [
+            (1, 1),
+            (1, 4),
+            (1, 7),
+            (2, 4),
+            (3, 2),
+            (3, 3),
+            (5, 0),
+            (5, 4),
+            (5, 7),
+        ];
+        let mut mask = RectMask::new(9, 7);
+        for r in 0..7 {
+            for c in 0..9 {
+                mask.set_flag(&RectPosition::new(r, c), !masked_pos.contains(&(r, c)));
+            }
+        }
+        mask
+    }
+
+    #[test]
+    fn test_eller_on_masked_rect_grid() {
+        let mask = make_test_mask();
+        let cells: Vec<_> = mask.cell_pos_iter().collect();
+        for _ in 0..20 {
+            let maze = RectLayerMazeGenerator::new(EllerMazeGenerator)
+                .generate(RectGrid::<WithMask>::new(&mask))
+                .unwrap();
+            assert!(matches!(maze, RectMaze::WithMask(_)));
+            assert_perfect(&cells, |pos| {
+                RectDirection::all_dirs()
+                    .iter()
+                    .filter(|dir| maze.is_connected_to(pos, **dir))
+                    .map(|dir| pos.neighbor(*dir).unwrap())
+                    .collect()
+            });
+        }
+    }
+
+    #[test]
+    fn test_eller_on_masked_tri_grid() {
+        let mask = make_test_mask();
+        let cells: Vec<_> = mask
+            .cell_pos_iter()
+            .map(|pos| TriPosition::new(pos.row, pos.col))
+            .collect();
+        for _ in 0..20 {
+            let maze = TriLayerMazeGenerator::new(EllerMazeGenerator)
+                .generate(TriGrid::with_mask(&mask))
+                .unwrap();
+            assert_perfect(&cells, |pos| {
+                let dirs = if maze.is_angle_up(pos) {
+                    TriDirection::angle_up_all_dirs()
+                } else {
+                    TriDirection::angle_down_all_dirs()
+                };
+                dirs.iter()
+                    .filter(|dir| maze.is_connected_to(pos, **dir))
+                    .map(|dir| pos.neighbor(*dir).unwrap())
+                    .collect()
+            });
+        }
+    }
+
+    #[test]
+    fn test_btree_and_sidewinder_on_masked_rect_grid() {
+        let mask = make_test_mask();
+        let cells: Vec<_> = mask.cell_pos_iter().collect();
+        for con_dir in DiagonalDirection::value_variants() {
+            let generators: [Box<dyn LayerMazeGenerator>; 2] = [
+                Box::new(BTreeMazeGenerator::new(*con_dir)),
+                Box::new(SidewinderMazeGenerator::new(*con_dir)),
+            ];
+            for generator in generators {
+                let mut grid = RectGrid::<WithMask>::new(&mask);
+                generator.generate_layer(&mut grid).unwrap();
+                let maze = RectMaze::WithMask(grid);
+                assert_perfect(&cells, |pos| {
+                    RectDirection::all_dirs()
+                        .iter()
+                        .filter(|dir| maze.is_connected_to(pos, **dir))
+                        .map(|dir| pos.neighbor(*dir).unwrap())
+                        .collect()
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn test_btree_and_sidewinder_on_masked_hexa_grid() {
+        let mask = make_test_mask();
+        let cells: Vec<_> = mask
+            .cell_pos_iter()
+            .map(|pos| HexaPosition::new(pos.row, pos.col))
+            .collect();
+        // The upper and the lower neighbors are the inverse of each other, the cell in even column has 3 upper ones.
+        let grid = HexaGrid::<WithMask>::new(&mask);
+        let mut neighbors = Vec::new();
+        for pos in cells.iter().map(|pos| Position2d::from(*pos)) {
+            neighbors.clear();
+            grid.append_neighbors_upper_layer(&pos, &mut neighbors);
+            for neighbor in neighbors.clone() {
+                let mut lower_neighbors = Vec::new();
+                grid.append_neighbors_lower_layer(&neighbor, &mut lower_neighbors);
+                assert!(lower_neighbors.contains(&pos));
+            }
+        }
+        neighbors.clear();
+        grid.append_neighbors_upper_layer(&Position2d(6, 2), &mut neighbors);
+        assert_eq!(
+            neighbors,
+            [Position2d(5, 2), Position2d(5, 3), Position2d(5, 1)]
+        );
+
+        for con_dir in DiagonalDirection::value_variants() {
+            let generators: [Box<dyn LayerMazeGenerator>; 2] = [
+                Box::new(BTreeMazeGenerator::new(*con_dir)),
+                Box::new(SidewinderMazeGenerator::new(*con_dir)),
+            ];
+            for generator in generators {
+                let mut diagonal_passages_n = 0;
+                for _ in 0..10 {
+                    let mut grid = HexaGrid::<WithMask>::new(&mask);
+                    generator.generate_layer(&mut grid).unwrap();
+                    let maze = HexaMaze::WithMask(grid);
+                    assert_perfect(&cells, |pos| {
+                        maze.links(pos)
+                            .into_iter()
+                            .map(|(_, neighbor)| neighbor)
+                            .collect()
+                    });
+                    diagonal_passages_n += maze
+                        .passages()
+                        .filter(|(pos, _, neighbor)| pos.row != neighbor.row)
+                        .filter(|(pos, _, neighbor)| pos.col != neighbor.col)
+                        .count();
+                }
+                // Passages across rows aren't only to the north or the south.
+                assert!(diagonal_passages_n > 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_region_division_on_masked_grids() {
+        let mask = make_test_mask();
+        let cells: Vec<_> = mask.cell_pos_iter().collect();
+        for _ in 0..20 {
+            let mut grid = RectGrid::<WithMask>::new(&mask);
+            RegionDivisionMazeGenerator::new(1)
+                .generate_2d(&mut grid)
+                .unwrap();
+            let maze = RectMaze::WithMask(grid);
+            assert_perfect(&cells, |pos| {
+                RectDirection::all_dirs()
+                    .iter()
+                    .filter(|dir| maze.is_connected_to(pos, **dir))
+                    .map(|dir| pos.neighbor(*dir).unwrap())
+                    .collect()
+            });
+        }
+
+        let cells: Vec<_> = mask
+            .cell_pos_iter()
+            .map(|pos| TriPosition::new(pos.row, pos.col))
+            .collect();
+        for _ in 0..20 {
+            let maze = RegionDivisionMazeGenerator::new(1)
+                .generate(TriGrid::with_mask(&mask))
+                .unwrap();
+            assert_perfect(&cells, |pos| {
+                let dirs = if maze.is_angle_up(pos) {
+                    TriDirection::angle_up_all_dirs()
+                } else {
+                    TriDirection::angle_down_all_dirs()
+                };
+                dirs.iter()
+                    .filter(|dir| maze.is_connected_to(pos, **dir))
+                    .map(|dir| pos.neighbor(*dir).unwrap())
+                    .collect()
+            });
+        }
+    }
+
+    #[test]
+    fn test_parse_growing_tree_strategy() {
+        assert_eq!(
+            "newest".parse::<GrowingTreeStrategy>().unwrap(),
+            GrowingTreeStrategy::new(GrowingTreeSelection::Newest)
+        );
+        let mixed_strategy = GrowingTreeStrategy::with_weights([
+            (GrowingTreeSelection::Newest, 3),
+            (GrowingTreeSelection::Random, 1),
+        ])
+        .unwrap();
+        assert_eq!(
+            "Newest:3, random:1".parse::<GrowingTreeStrategy>().unwrap(),
+            mixed_strategy
+        );
+        assert_eq!(
+            mixed_strategy
+                .to_string()
+                .parse::<GrowingTreeStrategy>()
+                .unwrap(),
+            mixed_strategy
+        );
+        for invalid_str in [
+            "",
+            "latest",
+            "newest:-1",
+            "newest:0,random:0",
+            "newest:4294967295,random:1",
+        ] {
+            assert!(invalid_str.parse::<GrowingTreeStrategy>().is_err());
+        }
+    }
+
+    #[test]
+    fn test_growing_tree_strategies_on_masked_rect_grid() {
+        let mask = make_test_mask();
+        let cells: Vec<_> = mask.cell_pos_iter().collect();
+        for strategy_str in ["newest", "oldest", "random", "middle", "newest:3,random:1"] {
+            let generator = GrowingTreeMazeGenerator::new(strategy_str.parse().unwrap());
+            let mut grid = RectGrid::<WithMask>::new(&mask);
+            generator.generate_2d(&mut grid).unwrap();
+            let maze = RectMaze::WithMask(grid);
+            assert_perfect(&cells, |pos| {
+                RectDirection::all_dirs()
+                    .iter()
+                    .filter(|dir| maze.is_connected_to(pos, **dir))
+                    .map(|dir| pos.neighbor(*dir).unwrap())
+                    .collect()
+            });
+        }
+    }
+
+    #[test]
+    fn test_weighted_generators_on_masked_rect_grid() {
+        let mask = make_test_mask();
+        let cells: Vec<_> = mask.cell_pos_iter().collect();
+        let row_bias = |from: &Position2d, to: &Position2d| u32::from(from.0 != to.0);
+        let generators: [Box<dyn Maze2dGenerator>; 2] = [
+            Box::new(WeightedPrimMazeGenerator::new(row_bias)),
+            Box::new(WeightedKruskalMazeGenerator::new(row_bias)),
+        ];
+        for generator in generators {
+            let mut grid = RectGrid::<WithMask>::new(&mask);
+            generator.generate_2d(&mut grid).unwrap();
+            let maze = RectMaze::WithMask(grid);
+            assert_perfect(&cells, |pos| {
+                RectDirection::all_dirs()
+                    .iter()
+                    .filter(|dir| maze.is_connected_to(pos, **dir))
+                    .map(|dir| pos.neighbor(*dir).unwrap())
+                    .collect()
+            });
+        }
+    }
+
+    #[test]
+    fn test_weighted_generators_follow_weights() {
+        let (width, height) = (8, 5);
+        // Passages in rows are always cheaper, so every row becomes a corridor.
+        let row_bias = |from: &Position2d, to: &Position2d| u32::from(from.0 != to.0);
+        let generators: [Box<dyn Maze2dGenerator>; 2] = [
+            Box::new(WeightedPrimMazeGenerator::new(
+                Box::new(row_bias) as Box<dyn PassageWeight>
+            )),
+            Box::new(WeightedKruskalMazeGenerator::new(row_bias)),
+        ];
+        for generator in generators {
+            let mut grid = RectGrid::<NoMask>::new(width, height);
+            generator.generate_2d(&mut grid).unwrap();
+            let maze = RectMaze::NoMask(grid);
+            for row in 0..height {
+                for col in 0..(width - 1) {
+                    assert!(
+                        maze.is_connected_to(&RectPosition::new(row, col), RectDirection::East)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_origin_shift_keeps_maze_perfect() {
+        let mask = make_test_mask();
+        let cells: Vec<_> = mask.cell_pos_iter().collect();
+        let assert_grid_perfect = |grid: &RectGrid<WithMask>| {
+            assert_perfect(&cells, |pos| {
+                RectDirection::all_dirs()
+                    .iter()
+                    .filter(|dir| grid.is_connected_to(pos, **dir))
+                    .map(|dir| pos.neighbor(*dir).unwrap())
+                    .collect()
+            })
+        };
+
+        let mut grid = RectGrid::<WithMask>::new(&mask);
+        OriginShiftMazeGenerator.generate_2d(&mut grid).unwrap();
+        assert_grid_perfect(&grid);
+
+        // Passages making loops are walled when building the tree from an existing maze.
+        let all_cells_pos = grid.all_cells_pos_set();
+        let mut neighbors = Vec::new();
+        for pos in all_cells_pos.iter() {
+            neighbors.clear();
+            grid.append_neighbors(pos, &mut neighbors);
+            for neighbor in neighbors.iter() {
+                Grid2d::connect_to(&mut grid, pos, neighbor);
+            }
+        }
+        let mut origin_shift = OriginShift::new(&mut grid).unwrap();
+        assert_grid_perfect(&grid);
+        for _ in 0..100 {
+            origin_shift.shift(&mut grid, 7);
+            assert!(all_cells_pos.contains(&origin_shift.origin()));
+            assert_grid_perfect(&grid);
+        }
+    }
+
+    #[test]
+    fn test_generate_on_degenerate_grids() {
+        let generators: Vec<Box<dyn Maze2dGenerator>> = vec![
+            Box::new(AldousBroderMazeGenerator),
+            Box::new(WilsonMazeGenerator),
+            Box::new(HuntAndKillMazeGenerator),
+            Box::new(RecursiveBacktrackerMazeGenerator),
+            Box::new(KruskalMazeGenerator),
+            Box::new(PrimMazeGenerator),
+            Box::new(GrowingTreeMazeGenerator::new(GrowingTreeStrategy::default())),
+            Box::new(WeightedPrimMazeGenerator::new(RandomWeight::uniform(10))),
+            Box::new(WeightedKruskalMazeGenerator::new(RandomWeight::uniform(10))),
+            Box::new(OriginShiftMazeGenerator),
+            Box::new(RegionDivisionMazeGenerator::new(1)),
+        ];
+        let layer_generators: Vec<Box<dyn LayerMazeGenerator>> = vec![
+            Box::new(EllerMazeGenerator),
+            Box::new(BTreeMazeGenerator::new(DiagonalDirection::Northeast)),
+            Box::new(SidewinderMazeGenerator::new(DiagonalDirection::Southwest)),
+        ];
+        // Only the first and the last cells are left, they aren't neighbors.
+        let mut mask = RectMask::new(3, 1);
+        mask.set_flag(&RectPosition::new(0, 0), true);
+        mask.set_flag(&RectPosition::new(0, 2), true);
+
+        for generator in generators.iter() {
+            generator
+                .generate_2d(&mut RectGrid::<NoMask>::new(1, 1))
+                .unwrap();
+            generator.generate_2d(&mut CircGrid::new(1)).unwrap();
+            generator.generate_2d(&mut TriGrid::new(1, 1)).unwrap();
+            assert!(matches!(
+                generator.generate_2d(&mut RectGrid::<NoMask>::new(0, 0)),
+                Err(Error::EmptyGrid)
+            ));
+            assert!(matches!(
+                generator.generate_2d(&mut RectGrid::<WithMask>::new(&mask)),
+                Err(Error::DisconnectedGrid)
+            ));
+        }
+        for generator in layer_generators.iter() {
+            generator
+                .generate_layer(&mut RectGrid::<NoMask>::new(1, 1))
+                .unwrap();
+            generator.generate_layer(&mut CircGrid::new(1)).unwrap();
+            assert!(matches!(
+                generator.generate_layer(&mut RectGrid::<NoMask>::new(0, 0)),
+                Err(Error::EmptyGrid)
+            ));
+            assert!(matches!(
+                generator.generate_layer(&mut RectGrid::<WithMask>::new(&mask)),
+                Err(Error::DisconnectedGrid)
+            ));
+        }
+
+        let maze = RecursiveDivisionMazeGenerator::new(1, 1)
+            .generate(RectGrid::<NoMask>::new(1, 1))
+            .unwrap();
+        assert_eq!(maze.size(), (1, 1));
+    }
+
+    #[test]
+    fn test_cell_weights_from_image() {
+        // Every pixel of the 4 x 3 image has its own brightness.
+        let image = GrayImage::from_fn(4, 3, |x, y| Luma([(x + y * 4) as u8 * 10]));
+        let path = std::env::temp_dir().join(format!("weights_{}.png", std::process::id()));
+        image.save(&path).unwrap();
+
+        let rect_grid = RectGrid::<NoMask>::new(4, 3);
+        let weights = CellWeights::from_layer_image(&path, &rect_grid).unwrap();
+        assert_eq!(weights.cell_weight(&Position2d(0, 0)), 0);
+        assert_eq!(weights.cell_weight(&Position2d(1, 2)), 60);
+        assert_eq!(weights.cell_weight(&Position2d(2, 3)), 110);
+
+        // The cube is weighed by its unfolded net, one cell in a face takes one pixel.
+        let cube_grid = CubeGrid::new(1);
+        let weights =
+            CellWeights::from_image(&path, &cube_grid, |pos| cube_grid.net_point(pos)).unwrap();
+        for face in CubeFace::all_faces() {
+            let (net_col, net_row) = face.net_pos();
+            assert_eq!(
+                weights.cell_weight(&Position2d(face.index(), 0)),
+                (net_col + net_row * 4) as u32 * 10
+            );
+        }
+        std::fs::remove_file(&path).unwrap();
+
+        // The weight of passage saturates instead of overflowing.
+        let weights: CellWeights = [(Position2d(0, 0), u32::MAX), (Position2d(0, 1), 1)]
+            .into_iter()
+            .collect();
+        assert_eq!(
+            weights.weight(&Position2d(0, 0), &Position2d(0, 1)),
+            u32::MAX
+        );
     }
 }
@@ -1,9 +1,9 @@
 use crate::maze::circ::{CircGrid, CircMaze};
 
-use super::{LayerMazeGenerator, Maze2dGenerator};
+use super::{Error, LayerMazeGenerator, Maze2dGenerator};
 
 pub trait CircMazeGenerator {
-    fn generate(&self, grid: CircGrid) -> CircMaze;
+    fn generate(&self, grid: CircGrid) -> Result<CircMaze, Error>;
 }
 
 #[derive(Debug)]
@@ -12,9 +12,9 @@ pub struct CircMaze2dGenerator<G: Maze2dGenerator> {
 }
 
 impl<G: Maze2dGenerator> CircMazeGenerator for CircMaze2dGenerator<G> {
-    fn generate(&self, mut grid: CircGrid) -> CircMaze {
-        self.generator.generate_2d(&mut grid);
-        CircMaze::new(grid)
+    fn generate(&self, mut grid: CircGrid) -> Result<CircMaze, Error> {
+        self.generator.generate_2d(&mut grid)?;
+        Ok(CircMaze::new(grid))
     }
 }
 
@@ -30,9 +30,9 @@ pub struct CircLayerMazeGenerator<G: LayerMazeGenerator> {
 }
 
 impl<G: LayerMazeGenerator> CircMazeGenerator for CircLayerMazeGenerator<G> {
-    fn generate(&self, mut grid: CircGrid) -> CircMaze {
-        self.generator.generate_layer(&mut grid);
-        CircMaze::new(grid)
+    fn generate(&self, mut grid: CircGrid) -> Result<CircMaze, Error> {
+        self.generator.generate_layer(&mut grid)?;
+        Ok(CircMaze::new(grid))
     }
 }
 
@@ -0,0 +1,14 @@
+use crate::maze::cube::{CubeGrid, CubeMaze};
+
+use super::{Error, Maze2dGenerator};
+
+pub trait CubeMazeGenerator {
+    fn generate(&self, grid: CubeGrid) -> Result<CubeMaze, Error>;
+}
+
+impl<G: Maze2dGenerator> CubeMazeGenerator for G {
+    fn generate(&self, mut grid: CubeGrid) -> Result<CubeMaze, Error> {
+        self.generate_2d(&mut grid)?;
+        Ok(CubeMaze::new(grid))
+    }
+}
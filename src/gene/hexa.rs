@@ -3,10 +3,10 @@ use crate::maze::{
     hexa::{HexaGrid, HexaMaze},
 };
 
-use super::{LayerMazeGenerator, Maze2dGenerator};
+use super::{Error, LayerMazeGenerator, Maze2dGenerator};
 
 pub trait HexaMazeGenerator<M: MaskType> {
-    fn generate(&self, grid: HexaGrid<M>) -> HexaMaze;
+    fn generate(&self, grid: HexaGrid<M>) -> Result<HexaMaze, Error>;
 }
 
 #[derive(Debug)]
@@ -15,16 +15,16 @@ pub struct HexaMaze2dGenerator<G: Maze2dGenerator> {
 }
 
 impl<G: Maze2dGenerator> HexaMazeGenerator<NoMask> for HexaMaze2dGenerator<G> {
-    fn generate(&self, mut grid: HexaGrid<NoMask>) -> HexaMaze {
-        self.generator.generate_2d(&mut grid);
-        HexaMaze::NoMask(grid)
+    fn generate(&self, mut grid: HexaGrid<NoMask>) -> Result<HexaMaze, Error> {
+        self.generator.generate_2d(&mut grid)?;
+        Ok(HexaMaze::NoMask(grid))
     }
 }
 
 impl<G: Maze2dGenerator> HexaMazeGenerator<WithMask> for HexaMaze2dGenerator<G> {
-    fn generate(&self, mut grid: HexaGrid<WithMask>) -> HexaMaze {
-        self.generator.generate_2d(&mut grid);
-        HexaMaze::WithMask(grid)
+    fn generate(&self, mut grid: HexaGrid<WithMask>) -> Result<HexaMaze, Error> {
+        self.generator.generate_2d(&mut grid)?;
+        Ok(HexaMaze::WithMask(grid))
     }
 }
 
@@ -40,9 +40,16 @@ pub struct HexaLayerMazeGenerator<G: LayerMazeGenerator> {
 }
 
 impl<G: LayerMazeGenerator> HexaMazeGenerator<NoMask> for HexaLayerMazeGenerator<G> {
-    fn generate(&self, mut grid: HexaGrid<NoMask>) -> HexaMaze {
-        self.generator.generate_layer(&mut grid);
-        HexaMaze::NoMask(grid)
+    fn generate(&self, mut grid: HexaGrid<NoMask>) -> Result<HexaMaze, Error> {
+        self.generator.generate_layer(&mut grid)?;
+        Ok(HexaMaze::NoMask(grid))
+    }
+}
+
+impl<G: LayerMazeGenerator> HexaMazeGenerator<WithMask> for HexaLayerMazeGenerator<G> {
+    fn generate(&self, mut grid: HexaGrid<WithMask>) -> Result<HexaMaze, Error> {
+        self.generator.generate_layer(&mut grid)?;
+        Ok(HexaMaze::WithMask(grid))
     }
 }
 
@@ -1,14 +1,32 @@
 use crate::maze::tri::{TriGrid, TriMaze};
 
-use super::Maze2dGenerator;
+use super::{Error, LayerMazeGenerator, Maze2dGenerator};
 
 pub trait TriMazeGenerator {
-    fn generate(&self, grid: TriGrid) -> TriMaze;
+    fn generate(&self, grid: TriGrid) -> Result<TriMaze, Error>;
 }
 
 impl<G: Maze2dGenerator> TriMazeGenerator for G {
-    fn generate(&self, mut grid: TriGrid) -> TriMaze {
-        self.generate_2d(&mut grid);
-        TriMaze::new(grid)
+    fn generate(&self, mut grid: TriGrid) -> Result<TriMaze, Error> {
+        self.generate_2d(&mut grid)?;
+        Ok(TriMaze::new(grid))
+    }
+}
+
+#[derive(Debug)]
+pub struct TriLayerMazeGenerator<G: LayerMazeGenerator> {
+    generator: G,
+}
+
+impl<G: LayerMazeGenerator> TriMazeGenerator for TriLayerMazeGenerator<G> {
+    fn generate(&self, mut grid: TriGrid) -> Result<TriMaze, Error> {
+        self.generator.generate_layer(&mut grid)?;
+        Ok(TriMaze::new(grid))
+    }
+}
+
+impl<G: LayerMazeGenerator> TriLayerMazeGenerator<G> {
+    pub fn new(generator: G) -> Self {
+        Self { generator }
     }
 }
@@ -0,0 +1,14 @@
+use crate::maze::voronoi::{VoronoiGrid, VoronoiMaze};
+
+use super::{Error, Maze2dGenerator};
+
+pub trait VoronoiMazeGenerator {
+    fn generate(&self, grid: VoronoiGrid) -> Result<VoronoiMaze, Error>;
+}
+
+impl<G: Maze2dGenerator> VoronoiMazeGenerator for G {
+    fn generate(&self, mut grid: VoronoiGrid) -> Result<VoronoiMaze, Error> {
+        self.generate_2d(&mut grid)?;
+        Ok(VoronoiMaze::new(grid))
+    }
+}
@@ -0,0 +1,423 @@
+use std::{collections::BTreeMap, f32::consts::PI, fmt::Write as FmtWrite, fs, path::Path};
+
+use anyhow::Error as AnyError;
+use clap::{Args, ValueEnum};
+
+use crate::{
+    maze::{
+        Grid2d, Position2d,
+        any::AnyMaze,
+        circ::CircMaze,
+        cube::{CubeFace, CubeMaze},
+        hexa::HexaMaze,
+        rect::RectMaze,
+        tri::TriMaze,
+        voronoi::{VoronoiMaze, VoronoiPosition},
+    },
+    stats,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
+pub enum GraphFormat {
+    /// Graphviz DOT file format
+    DOT,
+    /// GraphML file format
+    #[value(name = "graphml")]
+    GraphML,
+}
+
+/// Settings of the exported graph, the passages are always exported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Args)]
+pub struct GraphSettings {
+    /// Export walls(between neighbor cells) as edges too, in another class than passages
+    #[arg(long)]
+    pub walls: bool,
+    /// Export the distance from start(the first cell if start isn't set) through passages as node attribute
+    #[arg(long)]
+    pub distances: bool,
+}
+
+/// Maze whose cells can be placed in plane, for the layout of the exported graph.
+pub trait GraphLayout {
+    fn grid(&self) -> &dyn Grid2d;
+    /// Center of the cell in the maze picture, in unit of cell size(y to the down).
+    fn cell_center(&self, pos: &Position2d) -> (f32, f32);
+}
+
+impl GraphLayout for RectMaze {
+    fn grid(&self) -> &dyn Grid2d {
+        self.grid()
+    }
+
+    fn cell_center(&self, pos: &Position2d) -> (f32, f32) {
+        (pos.1 as f32 + 0.5, pos.0 as f32 + 0.5)
+    }
+}
+
+impl GraphLayout for HexaMaze {
+    fn grid(&self) -> &dyn Grid2d {
+        self.grid()
+    }
+
+    fn cell_center(&self, pos: &Position2d) -> (f32, f32) {
+        // Cells in odd columns are lower by half cell.
+        let cell_radius = 1.0 / 3f32.sqrt();
+        let x = cell_radius + pos.1 as f32 * 1.5 * cell_radius;
+        let y = pos.0 as f32 + if pos.1 % 2 == 1 { 1.0 } else { 0.5 };
+        (x, y)
+    }
+}
+
+impl GraphLayout for TriMaze {
+    fn grid(&self) -> &dyn Grid2d {
+        self.grid()
+    }
+
+    fn cell_center(&self, pos: &Position2d) -> (f32, f32) {
+        // The centroid is at 2/3 height from the angle.
+        let half_side = 1.0 / 3f32.sqrt();
+        let y_in_row = if self.is_angle_up(&(*pos).into()) {
+            2.0 / 3.0
+        } else {
+            1.0 / 3.0
+        };
+        ((pos.1 + 1) as f32 * half_side, pos.0 as f32 + y_in_row)
+    }
+}
+
+impl GraphLayout for CircMaze {
+    fn grid(&self) -> &dyn Grid2d {
+        self.grid()
+    }
+
+    fn cell_center(&self, pos: &Position2d) -> (f32, f32) {
+        let rings_n = self.rings_n() as f32;
+        if pos.0 == 0 {
+            return (rings_n, rings_n);
+        }
+
+        let radius = pos.0 as f32 + 0.5;
+        let angle = (pos.1 as f32 + 0.5) * 2.0 * PI / self.ring_cells_n(pos.0) as f32;
+        (
+            rings_n + radius * angle.cos(),
+            rings_n + radius * angle.sin(),
+        )
+    }
+}
+
+impl GraphLayout for CubeMaze {
+    fn grid(&self) -> &dyn Grid2d {
+        self.grid()
+    }
+
+    fn cell_center(&self, pos: &Position2d) -> (f32, f32) {
+        // Placed in the unfolded net of cube.
+        let size = self.size();
+        let (net_col, net_row) = CubeFace::from_index(pos.0)
+            .map(|face| face.net_pos())
+            .unwrap_or_default();
+        let x = (net_col * size + pos.1 % size.max(1)) as f32 + 0.5;
+        let y = (net_row * size + pos.1 / size.max(1)) as f32 + 0.5;
+        (x, y)
+    }
+}
+
+impl GraphLayout for VoronoiMaze {
+    fn grid(&self) -> &dyn Grid2d {
+        self.grid()
+    }
+
+    fn cell_center(&self, pos: &Position2d) -> (f32, f32) {
+        self.site(&VoronoiPosition::from(*pos)).unwrap_or_default()
+    }
+}
+
+impl GraphLayout for AnyMaze {
+    fn grid(&self) -> &dyn Grid2d {
+        self.grid()
+    }
+
+    fn cell_center(&self, pos: &Position2d) -> (f32, f32) {
+        match self {
+            AnyMaze::Rect(maze) => maze.cell_center(pos),
+            AnyMaze::Hexa(maze) => maze.cell_center(pos),
+            AnyMaze::Tri(maze) => maze.cell_center(pos),
+            AnyMaze::Circ(maze) => maze.cell_center(pos),
+            AnyMaze::Cube(maze) => maze.cell_center(pos),
+            AnyMaze::Voronoi(maze) => maze.cell_center(pos),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GraphNode {
+    pub pos: Position2d,
+    pub center: (f32, f32),
+    /// Distance from start through passages, none if it isn't exported or the cell can't be reached.
+    pub distance: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GraphEdgeKind {
+    Passage,
+    Wall,
+}
+
+impl GraphEdgeKind {
+    fn name(&self) -> &'static str {
+        match self {
+            GraphEdgeKind::Passage => "passage",
+            GraphEdgeKind::Wall => "wall",
+        }
+    }
+}
+
+/// Edge between two nodes, given by their indices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GraphEdge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: GraphEdgeKind,
+}
+
+/// Graph of maze, the cells are nodes, and the passages(and the walls if asked) between neighbor cells are edges.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MazeGraph {
+    nodes: Vec<GraphNode>,
+    edges: Vec<GraphEdge>,
+    has_distances: bool,
+}
+
+impl MazeGraph {
+    pub fn new<M: GraphLayout + ?Sized>(
+        maze: &M,
+        settings: &GraphSettings,
+        start: Option<Position2d>,
+    ) -> Self {
+        let grid = maze.grid();
+        let mut all_pos = grid.all_cells_pos_set().into_iter().collect::<Vec<_>>();
+        all_pos.sort();
+        let pos_inds = all_pos
+            .iter()
+            .enumerate()
+            .map(|(ind, pos)| (*pos, ind))
+            .collect::<BTreeMap<_, _>>();
+        let distances = start
+            .or_else(|| all_pos.first().copied())
+            .filter(|_| settings.distances)
+            .map(|start| stats::distances(grid, &start, true))
+            .unwrap_or_default();
+        let nodes = all_pos
+            .iter()
+            .map(|pos| GraphNode {
+                pos: *pos,
+                center: maze.cell_center(pos),
+                distance: distances.get(pos).copied(),
+            })
+            .collect();
+
+        let mut edges = Vec::new();
+        let mut neighbors = Vec::new();
+        for (from, pos) in all_pos.iter().enumerate() {
+            neighbors.clear();
+            grid.append_neighbors(pos, &mut neighbors);
+            for neighbor in neighbors.iter() {
+                // Every edge is added from its lower node.
+                let Some(to) = pos_inds.get(neighbor).copied().filter(|to| *to > from) else {
+                    continue;
+                };
+                let kind = if grid.is_connected_to(pos, neighbor) {
+                    GraphEdgeKind::Passage
+                } else if settings.walls {
+                    GraphEdgeKind::Wall
+                } else {
+                    continue;
+                };
+                edges.push(GraphEdge { from, to, kind });
+            }
+        }
+        edges.sort();
+        edges.dedup();
+
+        Self {
+            nodes,
+            edges,
+            has_distances: settings.distances,
+        }
+    }
+
+    pub fn nodes(&self) -> &[GraphNode] {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> &[GraphEdge] {
+        &self.edges
+    }
+
+    /// Graph in Graphviz DOT format, nodes are pinned at their centers(one inch per cell, y to the up).
+    pub fn to_dot(&self) -> String {
+        let mut dot =
+            String::from("graph maze {\n    node [shape=circle, width=0.3, fixedsize=true];\n");
+        for (ind, node) in self.nodes.iter().enumerate() {
+            let Position2d(row, col) = node.pos;
+            let (x, y) = node.center;
+            write!(
+                dot,
+                "    n{} [label=\"{},{}\", row={}, col={}, pos=\"{},{}!\"",
+                ind, row, col, row, col, x, -y
+            )
+            .unwrap();
+            if let Some(distance) = node.distance {
+                write!(dot, ", distance={}", distance).unwrap();
+            }
+            dot.push_str("];\n");
+        }
+        for edge in &self.edges {
+            let style = match edge.kind {
+                GraphEdgeKind::Passage => "solid",
+                GraphEdgeKind::Wall => "dashed",
+            };
+            writeln!(
+                dot,
+                "    n{} -- n{} [class={}, style={}];",
+                edge.from,
+                edge.to,
+                edge.kind.name(),
+                style
+            )
+            .unwrap();
+        }
+        dot.push_str("}\n");
+
+        dot
+    }
+
+    pub fn to_graphml(&self) -> String {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n\
+             \x20   <key id=\"row\" for=\"node\" attr.name=\"row\" attr.type=\"int\"/>\n\
+             \x20   <key id=\"col\" for=\"node\" attr.name=\"col\" attr.type=\"int\"/>\n\
+             \x20   <key id=\"x\" for=\"node\" attr.name=\"x\" attr.type=\"double\"/>\n\
+             \x20   <key id=\"y\" for=\"node\" attr.name=\"y\" attr.type=\"double\"/>\n",
+        );
+        if self.has_distances {
+            xml.push_str(
+                "    <key id=\"distance\" for=\"node\" attr.name=\"distance\" attr.type=\"int\"/>\n",
+            );
+        }
+        xml.push_str(
+            "    <key id=\"kind\" for=\"edge\" attr.name=\"kind\" attr.type=\"string\"/>\n\
+             \x20   <graph id=\"maze\" edgedefault=\"undirected\">\n",
+        );
+        for (ind, node) in self.nodes.iter().enumerate() {
+            let Position2d(row, col) = node.pos;
+            let (x, y) = node.center;
+            write!(
+                xml,
+                "        <node id=\"n{}\"><data key=\"row\">{}</data><data key=\"col\">{}</data>\
+                 <data key=\"x\">{}</data><data key=\"y\">{}</data>",
+                ind, row, col, x, y
+            )
+            .unwrap();
+            if let Some(distance) = node.distance {
+                write!(xml, "<data key=\"distance\">{}</data>", distance).unwrap();
+            }
+            xml.push_str("</node>\n");
+        }
+        for edge in &self.edges {
+            writeln!(
+                xml,
+                "        <edge source=\"n{}\" target=\"n{}\"><data key=\"kind\">{}</data></edge>",
+                edge.from,
+                edge.to,
+                edge.kind.name()
+            )
+            .unwrap();
+        }
+        xml.push_str("    </graph>\n</graphml>\n");
+
+        xml
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P, format: GraphFormat) -> Result<(), AnyError> {
+        let text = match format {
+            GraphFormat::DOT => self.to_dot(),
+            GraphFormat::GraphML => self.to_graphml(),
+        };
+        fs::write(path, text)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        gene::{Maze2dGenerator, RecursiveBacktrackerMazeGenerator},
+        maze::{
+            NoMask, Position2d,
+            circ::{CircGrid, CircMaze},
+            rect::{RectGrid, RectMaze},
+        },
+    };
+
+    use super::{GraphEdgeKind, GraphSettings, MazeGraph};
+
+    #[test]
+    fn test_rect_graph() {
+        let mut grid = RectGrid::<NoMask>::new(3, 2);
+        RecursiveBacktrackerMazeGenerator
+            .generate_2d(&mut grid)
+            .unwrap();
+        let maze = RectMaze::NoMask(grid);
+        let settings = GraphSettings {
+            walls: true,
+            distances: true,
+        };
+        let graph = MazeGraph::new(&maze, &settings, Some(Position2d(1, 2)));
+        assert_eq!(graph.nodes().len(), 6);
+        let passages_n = graph
+            .edges()
+            .iter()
+            .filter(|edge| edge.kind == GraphEdgeKind::Passage)
+            .count();
+        // A perfect maze is a tree, and the 3 x 2 grid has 7 pairs of neighbors.
+        assert_eq!(passages_n, 5);
+        assert_eq!(graph.edges().len(), 7);
+        assert!(graph.edges().iter().all(|edge| edge.from < edge.to));
+        let start_node = graph
+            .nodes()
+            .iter()
+            .find(|node| node.pos == Position2d(1, 2))
+            .unwrap();
+        assert_eq!(start_node.center, (2.5, 1.5));
+        assert_eq!(start_node.distance, Some(0));
+        assert!(graph.nodes().iter().all(|node| node.distance.is_some()));
+
+        let dot = graph.to_dot();
+        assert_eq!(dot.matches(" -- ").count(), 7);
+        assert_eq!(dot.matches("class=wall").count(), 2);
+        let graphml = graph.to_graphml();
+        assert_eq!(graphml.matches("<node ").count(), 6);
+        assert_eq!(graphml.matches("<edge ").count(), 7);
+        assert!(graphml.contains("attr.name=\"distance\""));
+
+        let graph = MazeGraph::new(&maze, &GraphSettings::default(), None);
+        assert_eq!(graph.edges().len(), 5);
+        assert!(graph.nodes().iter().all(|node| node.distance.is_none()));
+        assert!(!graph.to_graphml().contains("distance"));
+    }
+
+    #[test]
+    fn test_circ_graph() {
+        let mut grid = CircGrid::new(3);
+        RecursiveBacktrackerMazeGenerator
+            .generate_2d(&mut grid)
+            .unwrap();
+        let maze = CircMaze::new(grid);
+        let graph = MazeGraph::new(&maze, &GraphSettings::default(), None);
+        assert_eq!(graph.nodes().len(), maze.grid().cells_n());
+        assert_eq!(graph.edges().len(), graph.nodes().len() - 1);
+        assert_eq!(graph.nodes()[0].center, (3.0, 3.0));
+    }
+}
@@ -1,5 +1,15 @@
 #[doc(hidden)]
 pub mod cli;
+pub mod edit;
+pub mod file;
 pub mod gene;
+pub mod graph;
 pub mod maze;
+pub mod mesh;
+#[cfg(test)]
+mod prop_test;
+pub mod rng;
 pub mod show;
+pub mod stats;
+pub mod tile;
+pub mod validate;
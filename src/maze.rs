@@ -1,18 +1,33 @@
-use std::{collections::HashSet, fmt::Debug};
+use std::{collections::HashSet, fmt::Debug, hash::Hash};
 
+use bits::{BitReader, BitWriter};
 use rand::{Rng, seq::IteratorRandom};
-use rect::RectMask;
+use rect::{RectMask, RectPosition};
 use serde::{Deserialize, Serialize};
 
+use crate::rng;
+
+pub mod any;
+pub mod bits;
 pub mod circ;
+pub mod cube;
 pub mod hexa;
 pub mod rect;
 pub mod tri;
+pub mod voronoi;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Position2d(pub usize, pub usize);
 
-pub(crate) trait MaskType {}
+/// A passage stored at a position, which doesn't lead to a neighbor cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct StrayPassage {
+    pub from: Position2d,
+    /// The position passage leads to, none if it's out of the grid.
+    pub to: Option<Position2d>,
+}
+
+pub trait MaskType {}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct NoMask;
@@ -28,23 +43,188 @@ pub trait Grid2d {
     fn all_cells_pos_set(&self) -> HashSet<Position2d>;
     fn append_neighbors(&self, pos: &Position2d, neighbors: &mut Vec<Position2d>);
     fn connect_to(&mut self, from: &Position2d, to: &Position2d) -> bool;
+    fn disconnect_from(&mut self, from: &Position2d, to: &Position2d) -> bool;
+    fn is_connected_to(&self, from: &Position2d, to: &Position2d) -> bool;
+
+    /// Break the wall between two neighbors if there's one, or build it if there's a passage. Return false if
+    /// they aren't neighbors.
+    fn toggle_connection(&mut self, from: &Position2d, to: &Position2d) -> bool {
+        if self.is_connected_to(from, to) {
+            self.disconnect_from(from, to)
+        } else {
+            self.connect_to(from, to)
+        }
+    }
+
+    /// Passages stored in the grid which don't join two neighbor cells, e.g. leading to a masked out position or out of
+    /// the grid. They can't be made by connecting neighbors, but may be found in a maze loaded from a modified file.
+    fn stray_passages(&self) -> Vec<StrayPassage> {
+        Vec::new()
+    }
+
+    /// Build walls between all the neighbors, so no cell is connected.
+    fn set_all_walls(&mut self) {
+        let mut neighbors = Vec::new();
+        for pos in self.all_cells_pos_set() {
+            neighbors.clear();
+            self.append_neighbors(&pos, &mut neighbors);
+            for neighbor in neighbors.iter() {
+                self.disconnect_from(&pos, neighbor);
+            }
+        }
+    }
 }
 
+/// The grid consists of layers, the position in it is (layer index, index in layer).
+/// A layer may contain some positions which aren't cells(e.g. masked out), so the cells in a layer can be split into several runs,
+/// and a cell may have no neighbor in the lower layer.
 pub trait LayerGrid: Grid2d {
     fn layers_n(&self) -> usize;
+    /// Count of positions in the given layer, including those aren't cells.
     fn cells_n_at(&self, layer_ind: usize) -> usize;
     fn append_neighbors_upper_layer(&self, pos: &Position2d, neighbors: &mut Vec<Position2d>);
     fn append_neighbors_lower_layer(&self, pos: &Position2d, neighbors: &mut Vec<Position2d>);
 
+    fn is_cell(&self, pos: &Position2d) -> bool {
+        pos.0 < self.layers_n() && pos.1 < self.cells_n_at(pos.0)
+    }
+
     fn last_neighbor_pos(&self, pos: &Position2d) -> Option<Position2d> {
         pos.1
             .checked_sub(1)
             .map(|cell_ind| Position2d(pos.0, cell_ind))
+            .filter(|last_pos| self.is_cell(pos) && self.is_cell(last_pos))
     }
 
     fn next_neighbor_pos(&self, pos: &Position2d) -> Option<Position2d> {
         Some(Position2d(pos.0, pos.1 + 1))
-            .filter(|next_pos| next_pos.1 < self.cells_n_at(next_pos.0))
+            .filter(|next_pos| self.is_cell(pos) && self.is_cell(next_pos))
+    }
+}
+
+/// Read-only queries of maze in the positions and the directions of its own grid, so the analysis of maze can be
+/// written once for different grids.
+pub trait Maze {
+    type Position: Debug
+        + Clone
+        + Copy
+        + PartialEq
+        + Eq
+        + PartialOrd
+        + Ord
+        + Hash
+        + From<Position2d>
+        + Into<Position2d>;
+    type Direction: Debug + Clone + Copy + PartialEq + Eq + Hash + 'static;
+
+    fn cells_n(&self) -> usize;
+    /// Positions of all cells, in the order of rows(or rings).
+    fn cells(&self) -> impl Iterator<Item = Self::Position> + '_;
+    fn is_cell(&self, pos: &Self::Position) -> bool;
+    /// Directions the given cell may have neighbors in, some cells in the grid have different directions.
+    fn dirs(&self, pos: &Self::Position) -> &'static [Self::Direction];
+    /// Neighbor cells in the given direction, there may be more than one(e.g. outward in circular maze).
+    fn neighbors_at(&self, pos: &Self::Position, dir: Self::Direction) -> Vec<Self::Position>;
+    /// Whether there's a passage between the given cells, false if they aren't neighbors.
+    fn is_connected(&self, pos: &Self::Position, other: &Self::Position) -> bool;
+
+    /// Neighbor cells with their directions, connected or not.
+    fn neighbors(&self, pos: &Self::Position) -> Vec<(Self::Direction, Self::Position)> {
+        self.dirs(pos)
+            .iter()
+            .flat_map(|dir| {
+                self.neighbors_at(pos, *dir)
+                    .into_iter()
+                    .map(move |neighbor| (*dir, neighbor))
+            })
+            .collect()
+    }
+
+    /// Whether there's a passage to any neighbor in the given direction.
+    fn is_connected_to(&self, pos: &Self::Position, dir: Self::Direction) -> bool {
+        self.neighbors_at(pos, dir)
+            .iter()
+            .any(|neighbor| self.is_connected(pos, neighbor))
+    }
+
+    /// Neighbor cells connected to the given cell, with their directions.
+    fn links(&self, pos: &Self::Position) -> Vec<(Self::Direction, Self::Position)> {
+        self.neighbors(pos)
+            .into_iter()
+            .filter(|(_, neighbor)| self.is_connected(pos, neighbor))
+            .collect()
+    }
+
+    /// All passages as (cell, direction, neighbor), each one is yielded once, from the cell before the other.
+    fn passages(
+        &self,
+    ) -> impl Iterator<Item = (Self::Position, Self::Direction, Self::Position)> + '_ {
+        self.cells().flat_map(move |pos| {
+            self.links(&pos)
+                .into_iter()
+                .filter(move |(_, neighbor)| pos < *neighbor)
+                .map(move |(dir, neighbor)| (pos, dir, neighbor))
+        })
+    }
+
+    /// All walls as (cell, direction, neighbor), each one is yielded once. The neighbor is none if the wall is on the
+    /// boundary, that is, the border of grid or against a masked out position.
+    fn walls(
+        &self,
+    ) -> impl Iterator<Item = (Self::Position, Self::Direction, Option<Self::Position>)> + '_ {
+        self.cells().flat_map(move |pos| {
+            self.dirs(&pos).iter().flat_map(move |dir| {
+                let neighbors = self.neighbors_at(&pos, *dir);
+                if neighbors.is_empty() {
+                    vec![(pos, *dir, None)]
+                } else {
+                    neighbors
+                        .into_iter()
+                        .filter(|neighbor| pos < *neighbor && !self.is_connected(&pos, neighbor))
+                        .map(|neighbor| (pos, *dir, Some(neighbor)))
+                        .collect()
+                }
+            })
+        })
+    }
+}
+
+/// Direction in the grid which doesn't tell the directions of neighbors, every neighbor is in this direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AnyDirection;
+
+/// Any grid is a maze in the positions of grid, so the analysis of maze works for the grids without their own
+/// positions and directions(e.g. cube and Voronoi grids) too.
+impl Maze for dyn Grid2d + '_ {
+    type Position = Position2d;
+    type Direction = AnyDirection;
+
+    fn cells_n(&self) -> usize {
+        Grid2d::cells_n(self)
+    }
+
+    fn cells(&self) -> impl Iterator<Item = Position2d> + '_ {
+        let mut cells_pos: Vec<_> = self.all_cells_pos_set().into_iter().collect();
+        cells_pos.sort();
+        cells_pos.into_iter()
+    }
+
+    fn is_cell(&self, pos: &Position2d) -> bool {
+        self.all_cells_pos_set().contains(pos)
+    }
+
+    fn dirs(&self, _pos: &Position2d) -> &'static [AnyDirection] {
+        &[AnyDirection]
+    }
+
+    fn neighbors_at(&self, pos: &Position2d, _dir: AnyDirection) -> Vec<Position2d> {
+        let mut neighbors = Vec::new();
+        self.append_neighbors(pos, &mut neighbors);
+        neighbors
+    }
+
+    fn is_connected(&self, pos: &Position2d, other: &Position2d) -> bool {
+        Grid2d::is_connected_to(self, pos, other)
     }
 }
 
@@ -58,6 +238,15 @@ impl<T: Default> DefaultInRectGrid for T {
     }
 }
 
+/// Cell which can be packed in bits for the binary maze file, one bit for each passage it stores.
+pub trait CellBits {
+    const BITS_N: u32;
+
+    fn to_bits(&self) -> u64;
+    /// Cell from its bits, the position tells its shape if cells in grid have different shapes.
+    fn from_bits(pos: &Position2d, bits: u64) -> Self;
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeneralRectGrid<C: DefaultInRectGrid + Debug + Clone> {
     width: usize,
@@ -99,11 +288,13 @@ impl<C: DefaultInRectGrid + Debug + Clone> GeneralRectGrid<C> {
     }
 
     fn random_cell_pos(&self) -> Option<Position2d> {
-        let mut rng = rand::rng();
+        let mut rng = rng::rng();
         if let Some(mask) = self.mask.as_ref() {
             mask.cell_pos_iter()
                 .choose(&mut rng)
                 .map(|rect_pos| rect_pos.into())
+        } else if self.cells.is_empty() {
+            None
         } else {
             Some(Position2d(
                 rng.random_range(0..self.height),
@@ -155,6 +346,22 @@ impl<C: DefaultInRectGrid + Debug + Clone> GeneralRectGrid<C> {
         }
     }
 
+    /// All positions in grid with their cells, including the masked out ones.
+    fn raw_cell_iter(&self) -> impl Iterator<Item = (Position2d, &C)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|_| self.width > 0)
+            .map(|(ind, cell)| (Position2d(ind / self.width, ind % self.width), cell))
+    }
+
+    /// Check the passage stored at given position to its neighbor, return it if it's stray.
+    fn stray_passage(&self, from: &Position2d, to: Option<Position2d>) -> Option<StrayPassage> {
+        let to = to.filter(|to| self.pos_to_ind(to).is_some());
+        (!self.is_cell(from) || to.is_none_or(|to| !self.is_cell(&to)))
+            .then_some(StrayPassage { from: *from, to })
+    }
+
     fn pos_to_ind(&self, pos: &Position2d) -> Option<usize> {
         if pos.0 < self.height && pos.1 < self.width {
             Some(pos.0 * self.width + pos.1)
@@ -163,3 +370,266 @@ impl<C: DefaultInRectGrid + Debug + Clone> GeneralRectGrid<C> {
         }
     }
 }
+
+impl<C: CellBits + DefaultInRectGrid + Debug + Clone> GeneralRectGrid<C> {
+    /// Write the size, the mask(a bit per position if there's a mask) and the bits of cells.
+    pub(crate) fn write_bits(&self, writer: &mut BitWriter) -> Result<(), bits::Error> {
+        writer.write_usize(self.width)?;
+        writer.write_usize(self.height)?;
+        writer.write_bool(self.mask.is_some());
+        if let Some(mask) = self.mask.as_ref() {
+            for r in 0..self.height {
+                for c in 0..self.width {
+                    writer.write_bool(mask.is_cell(&RectPosition::new(r, c)));
+                }
+            }
+        }
+        for cell in &self.cells {
+            writer.write_bits(cell.to_bits(), C::BITS_N);
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn read_bits(reader: &mut BitReader) -> Result<Self, bits::Error> {
+        let width = reader.read_usize()?;
+        let height = reader.read_usize()?;
+        let positions_n = width.saturating_mul(height);
+        reader.expect_bits_n(positions_n.saturating_mul(C::BITS_N as usize))?;
+        let mask = if reader.read_bool()? {
+            reader.expect_bits_n(positions_n)?;
+            let mut mask = RectMask::new(width, height);
+            for r in 0..height {
+                for c in 0..width {
+                    mask.set_flag(&RectPosition::new(r, c), reader.read_bool()?);
+                }
+            }
+            Some(mask)
+        } else {
+            None
+        };
+        let mut cells = Vec::with_capacity(positions_n);
+        for r in 0..height {
+            for c in 0..width {
+                cells.push(C::from_bits(
+                    &Position2d(r, c),
+                    reader.read_bits(C::BITS_N)?,
+                ));
+            }
+        }
+
+        Ok(Self {
+            width,
+            height,
+            cells,
+            mask,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::gene::{Maze2dGenerator, WilsonMazeGenerator};
+
+    use super::{
+        Grid2d, Maze, NoMask, Position2d, WithMask,
+        circ::{CircGrid, CircMaze},
+        cube::CubeGrid,
+        hexa::{HexaGrid, HexaMaze},
+        rect::{RectGrid, RectMask, RectMaze, RectPosition},
+        tri::{TriGrid, TriMaze},
+        voronoi::{VoronoiGrid, VoronoiShape},
+    };
+
+    fn connected_pairs_n(grid: &dyn Grid2d) -> usize {
+        let mut neighbors = Vec::new();
+        grid.all_cells_pos_set()
+            .iter()
+            .map(|pos| {
+                neighbors.clear();
+                grid.append_neighbors(pos, &mut neighbors);
+                neighbors
+                    .iter()
+                    .filter(|neighbor| grid.is_connected_to(pos, neighbor))
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Edit each edge from both of its cells, and check both cells see the same connection.
+    fn assert_edits_symmetric(grid: &mut dyn Grid2d) {
+        grid.set_all_walls();
+        assert_eq!(connected_pairs_n(grid), 0);
+
+        let mut neighbors = Vec::new();
+        for pos in grid.all_cells_pos_set() {
+            neighbors.clear();
+            grid.append_neighbors(&pos, &mut neighbors);
+            for neighbor in neighbors.iter() {
+                assert!(grid.connect_to(&pos, neighbor));
+                assert!(grid.is_connected_to(&pos, neighbor));
+                assert!(grid.is_connected_to(neighbor, &pos));
+                assert_eq!(connected_pairs_n(grid), 2);
+
+                assert!(grid.disconnect_from(neighbor, &pos));
+                assert!(!grid.is_connected_to(&pos, neighbor));
+                assert!(!grid.is_connected_to(neighbor, &pos));
+
+                assert!(grid.toggle_connection(neighbor, &pos));
+                assert!(grid.is_connected_to(&pos, neighbor));
+                assert!(grid.toggle_connection(&pos, neighbor));
+                assert!(!grid.is_connected_to(neighbor, &pos));
+                assert_eq!(connected_pairs_n(grid), 0);
+            }
+        }
+
+        // Cells not adjacent can't be connected.
+        let far_pos = Position2d(0, usize::MAX);
+        if let Some(pos) = grid.random_cell_pos() {
+            assert!(!grid.connect_to(&pos, &far_pos));
+            assert!(!grid.toggle_connection(&pos, &far_pos));
+        }
+    }
+
+    #[test]
+    fn test_edits_symmetric_on_all_grids() {
+        let mut mask = RectMask::new(5, 4);
+        mask.set_flag(&RectPosition::new(1, 1), false);
+        mask.set_flag(&RectPosition::new(2, 3), false);
+        let mut grids: Vec<Box<dyn Grid2d>> = vec![
+            Box::new(RectGrid::<NoMask>::new(5, 4)),
+            Box::new(RectGrid::<WithMask>::new(&mask)),
+            Box::new(HexaGrid::<NoMask>::new(5, 4)),
+            Box::new(HexaGrid::<WithMask>::new(&mask)),
+            Box::new(TriGrid::new(5, 4)),
+            Box::new(TriGrid::with_mask(&mask)),
+            Box::new(CircGrid::new(4)),
+            Box::new(CubeGrid::new(2)),
+            Box::new(VoronoiGrid::new(&VoronoiShape::Circle { radius: 3.0 }, 1.0)),
+        ];
+        for grid in grids.iter_mut() {
+            assert_edits_symmetric(grid.as_mut());
+        }
+    }
+
+    /// Check the queries of a perfect maze agree with each other, only by the common trait.
+    fn assert_maze_queries<M: Maze + ?Sized>(maze: &M) {
+        let cells = maze.cells().collect::<Vec<_>>();
+        assert_eq!(cells.len(), maze.cells_n());
+        let mut links_n = 0;
+        for pos in &cells {
+            assert!(maze.is_cell(pos));
+            for (dir, neighbor) in maze.neighbors(pos) {
+                assert!(maze.dirs(pos).contains(&dir));
+                assert!(maze.neighbors_at(pos, dir).contains(&neighbor));
+                assert!(maze.neighbors(&neighbor).iter().any(|(_, n)| n == pos));
+                assert_eq!(
+                    maze.is_connected(pos, &neighbor),
+                    maze.is_connected(&neighbor, pos)
+                );
+            }
+            for (dir, neighbor) in maze.links(pos) {
+                assert!(maze.is_connected_to(pos, dir));
+                assert!(maze.links(&neighbor).iter().any(|(_, n)| n == pos));
+                links_n += 1;
+            }
+        }
+        // Every passage is counted from both cells, and a perfect maze has one passage less than cells.
+        assert_eq!(links_n, (cells.len() - 1) * 2);
+    }
+
+    #[test]
+    fn test_maze_queries() {
+        let mut mask = RectMask::new(5, 4);
+        for r in 0..4 {
+            for c in 0..5 {
+                mask.set_flag(&RectPosition::new(r, c), !matches!((r, c), (1, 1) | (2, 3)));
+            }
+        }
+        let mut rect_grid = RectGrid::<WithMask>::new(&mask);
+        WilsonMazeGenerator.generate_2d(&mut rect_grid).unwrap();
+        assert_maze_queries(&RectMaze::WithMask(rect_grid));
+        let mut hexa_grid = HexaGrid::<NoMask>::new(5, 4);
+        WilsonMazeGenerator.generate_2d(&mut hexa_grid).unwrap();
+        assert_maze_queries(&HexaMaze::NoMask(hexa_grid));
+        let mut tri_grid = TriGrid::new(5, 4);
+        WilsonMazeGenerator.generate_2d(&mut tri_grid).unwrap();
+        assert_maze_queries(&TriMaze::new(tri_grid));
+        let mut circ_grid = CircGrid::new(4);
+        WilsonMazeGenerator.generate_2d(&mut circ_grid).unwrap();
+        let circ_maze = CircMaze::new(circ_grid);
+        assert_maze_queries(&circ_maze);
+        assert_eq!(
+            circ_maze.cells().next(),
+            Some(super::circ::CircPosition::new(0, 0))
+        );
+        // The grid without its own positions is queried as any grid.
+        let mut cube_grid = CubeGrid::new(2);
+        WilsonMazeGenerator.generate_2d(&mut cube_grid).unwrap();
+        assert_maze_queries(&cube_grid as &dyn Grid2d);
+    }
+
+    /// Check every edge between neighbors is yielded once, as a passage or a wall, and return the count of boundary
+    /// walls.
+    fn assert_edges_once<M: Maze>(maze: &M) -> usize {
+        let passages = maze.passages().collect::<Vec<_>>();
+        assert_eq!(passages.len(), maze.cells_n() - 1);
+        assert!(
+            passages
+                .iter()
+                .all(|(pos, dir, neighbor)| maze.links(pos).contains(&(*dir, *neighbor)))
+        );
+
+        let mut edges = HashSet::new();
+        let mut boundary_walls_n = 0;
+        for (pos, dir, neighbor) in maze.walls() {
+            match neighbor {
+                Some(neighbor) => {
+                    assert!(!maze.is_connected(&pos, &neighbor));
+                    assert!(edges.insert((pos.min(neighbor), pos.max(neighbor))));
+                }
+                None => {
+                    assert!(maze.neighbors_at(&pos, dir).is_empty());
+                    boundary_walls_n += 1;
+                }
+            }
+        }
+        for (pos, _, neighbor) in passages {
+            assert!(edges.insert((pos.min(neighbor), pos.max(neighbor))));
+        }
+        let neighbor_pairs_n = maze
+            .cells()
+            .map(|pos| maze.neighbors(&pos).len())
+            .sum::<usize>()
+            / 2;
+        assert_eq!(edges.len(), neighbor_pairs_n);
+
+        boundary_walls_n
+    }
+
+    #[test]
+    fn test_passages_and_walls() {
+        let mut mask = RectMask::new(5, 4);
+        for r in 0..4 {
+            for c in 0..5 {
+                mask.set_flag(&RectPosition::new(r, c), !matches!((r, c), (1, 1) | (2, 3)));
+            }
+        }
+        let mut rect_grid = RectGrid::<WithMask>::new(&mask);
+        WilsonMazeGenerator.generate_2d(&mut rect_grid).unwrap();
+        // The border of grid, and the walls around the masked out cells.
+        assert_eq!(assert_edges_once(&RectMaze::WithMask(rect_grid)), 18 + 8);
+        let mut hexa_grid = HexaGrid::<NoMask>::new(3, 2);
+        WilsonMazeGenerator.generate_2d(&mut hexa_grid).unwrap();
+        assert_eq!(assert_edges_once(&HexaMaze::NoMask(hexa_grid)), 36 - 9 * 2);
+        let mut tri_grid = TriGrid::new(4, 2);
+        WilsonMazeGenerator.generate_2d(&mut tri_grid).unwrap();
+        assert_eq!(assert_edges_once(&TriMaze::new(tri_grid)), 24 - 8 * 2);
+        let mut circ_grid = CircGrid::new(4);
+        WilsonMazeGenerator.generate_2d(&mut circ_grid).unwrap();
+        let circ_maze = CircMaze::new(circ_grid);
+        assert_eq!(assert_edges_once(&circ_maze), circ_maze.ring_cells_n(3));
+    }
+}
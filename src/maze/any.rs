@@ -0,0 +1,234 @@
+use std::{fs, path::Path};
+
+use anyhow::Error as AnyError;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    file::{
+        self, MazeFile, MazeKind, SavedMaze,
+        binary::{self, BinaryMaze},
+    },
+    show::rect::{CmdBoxCharset, RectMazeCmdDisplay},
+    stats::{self, MazeStats},
+};
+
+use super::{
+    Grid2d, Position2d, bits::BitReader, circ::CircMaze, cube::CubeMaze, hexa::HexaMaze,
+    rect::RectMaze, tri::TriMaze, voronoi::VoronoiMaze,
+};
+
+/// Maze of any kind, for the tools working on all mazes, e.g. opening a saved maze without knowing its kind.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum AnyMaze {
+    Rect(RectMaze),
+    Hexa(HexaMaze),
+    Tri(TriMaze),
+    Circ(CircMaze),
+    Cube(CubeMaze),
+    Voronoi(VoronoiMaze),
+}
+
+impl AnyMaze {
+    /// Read maze from the json of any supported format version, the kind is read from header, or detected by trying
+    /// every kind for the older file without header.
+    pub fn from_json(value: Value) -> Result<SavedMaze<Self>, file::Error> {
+        match value.get("kind") {
+            Some(kind) => {
+                let kind = MazeKind::deserialize(kind).map_err(|_| file::Error::UnknownKind)?;
+                Self::from_kind_json(kind, value)
+            }
+            None => MazeKind::all_kinds()
+                .iter()
+                .find_map(|kind| Self::from_kind_json(*kind, value.clone()).ok())
+                .ok_or(file::Error::UnknownKind),
+        }
+    }
+
+    /// Read maze from binary, the kind is read from header.
+    pub fn from_binary(bytes: &[u8]) -> Result<SavedMaze<Self>, file::Error> {
+        fn read<M: BinaryMaze>(
+            reader: &mut BitReader,
+            to_any: fn(M) -> AnyMaze,
+        ) -> Result<AnyMaze, file::Error> {
+            M::read_bits(reader).map(to_any)
+        }
+
+        let (header, maze_bytes) = binary::split_binary(bytes)?;
+        let reader = &mut BitReader::new(&maze_bytes);
+        let maze = match header.kind {
+            MazeKind::Rect => read(reader, AnyMaze::Rect),
+            MazeKind::Hexa => read(reader, AnyMaze::Hexa),
+            MazeKind::Tri => read(reader, AnyMaze::Tri),
+            MazeKind::Circ => read(reader, AnyMaze::Circ),
+            MazeKind::Cube => read(reader, AnyMaze::Cube),
+            MazeKind::Voronoi => read(reader, AnyMaze::Voronoi),
+        }?;
+
+        Ok(SavedMaze { header, maze })
+    }
+
+    /// Load maze from file saved in json or binary format, the format is detected by the file content.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<SavedMaze<Self>, AnyError> {
+        let bytes = fs::read(path)?;
+        if binary::is_binary(&bytes) {
+            Ok(Self::from_binary(&bytes)?)
+        } else {
+            Ok(Self::from_json(serde_json::from_slice(&bytes)?)?)
+        }
+    }
+
+    fn from_kind_json(kind: MazeKind, value: Value) -> Result<SavedMaze<Self>, file::Error> {
+        fn saved<M: MazeFile>(
+            value: Value,
+            to_any: fn(M) -> AnyMaze,
+        ) -> Result<SavedMaze<AnyMaze>, file::Error> {
+            let SavedMaze { header, maze } = SavedMaze::<M>::from_json(value)?;
+            Ok(SavedMaze {
+                header,
+                maze: to_any(maze),
+            })
+        }
+
+        match kind {
+            MazeKind::Rect => saved(value, AnyMaze::Rect),
+            MazeKind::Hexa => saved(value, AnyMaze::Hexa),
+            MazeKind::Tri => saved(value, AnyMaze::Tri),
+            MazeKind::Circ => saved(value, AnyMaze::Circ),
+            MazeKind::Cube => saved(value, AnyMaze::Cube),
+            MazeKind::Voronoi => saved(value, AnyMaze::Voronoi),
+        }
+    }
+
+    pub fn kind(&self) -> MazeKind {
+        match self {
+            AnyMaze::Rect(_) => RectMaze::KIND,
+            AnyMaze::Hexa(_) => HexaMaze::KIND,
+            AnyMaze::Tri(_) => TriMaze::KIND,
+            AnyMaze::Circ(_) => CircMaze::KIND,
+            AnyMaze::Cube(_) => CubeMaze::KIND,
+            AnyMaze::Voronoi(_) => VoronoiMaze::KIND,
+        }
+    }
+
+    pub fn grid(&self) -> &dyn Grid2d {
+        match self {
+            AnyMaze::Rect(maze) => maze.grid(),
+            AnyMaze::Hexa(maze) => maze.grid(),
+            AnyMaze::Tri(maze) => maze.grid(),
+            AnyMaze::Circ(maze) => maze.grid(),
+            AnyMaze::Cube(maze) => maze.grid(),
+            AnyMaze::Voronoi(maze) => maze.grid(),
+        }
+    }
+
+    pub fn grid_mut(&mut self) -> &mut dyn Grid2d {
+        match self {
+            AnyMaze::Rect(maze) => maze.grid_mut(),
+            AnyMaze::Hexa(maze) => maze.grid_mut(),
+            AnyMaze::Tri(maze) => maze.grid_mut(),
+            AnyMaze::Circ(maze) => maze.grid_mut(),
+            AnyMaze::Cube(maze) => maze.grid_mut(),
+            AnyMaze::Voronoi(maze) => maze.grid_mut(),
+        }
+    }
+
+    /// Display maze in text by given box charset, only the rectangular maze can be displayed in text.
+    pub fn text<T: CmdBoxCharset>(&self, charset: T) -> Option<String> {
+        match self {
+            AnyMaze::Rect(maze) => Some(RectMazeCmdDisplay(maze, charset).to_string()),
+            _ => None,
+        }
+    }
+
+    pub fn stats(&self) -> MazeStats {
+        match self {
+            AnyMaze::Rect(maze) => MazeStats::new_rect(maze),
+            AnyMaze::Hexa(maze) => MazeStats::new(maze),
+            AnyMaze::Tri(maze) => MazeStats::new(maze),
+            AnyMaze::Circ(maze) => MazeStats::new(maze),
+            other_maze => MazeStats::new(other_maze.grid()),
+        }
+    }
+
+    /// The path from start to goal through passages, None if goal can't be reached.
+    pub fn solve(&self, start: &Position2d, goal: &Position2d) -> Option<Vec<Position2d>> {
+        stats::shortest_path(self.grid(), start, goal)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        file::{MazeHeader, MazeKind, SavedMaze},
+        gene::{Maze2dGenerator, RecursiveBacktrackerMazeGenerator},
+        maze::{
+            Grid2d, NoMask, Position2d,
+            circ::{CircGrid, CircMaze},
+            cube::{CubeGrid, CubeMaze},
+            hexa::{HexaGrid, HexaMaze},
+            rect::{RectGrid, RectMaze},
+            tri::{TriGrid, TriMaze},
+            voronoi::{VoronoiGrid, VoronoiMaze, VoronoiShape},
+        },
+        show::rect::AsciiBoxCharset,
+    };
+
+    use super::AnyMaze;
+
+    fn generated<G: Grid2d>(mut grid: G) -> G {
+        RecursiveBacktrackerMazeGenerator
+            .generate_2d(&mut grid)
+            .unwrap();
+        grid
+    }
+
+    #[test]
+    fn test_detect_kind() {
+        let mazes = [
+            AnyMaze::Rect(RectMaze::NoMask(generated(RectGrid::<NoMask>::new(3, 2)))),
+            AnyMaze::Hexa(HexaMaze::NoMask(generated(HexaGrid::<NoMask>::new(3, 2)))),
+            AnyMaze::Tri(TriMaze::new(generated(TriGrid::new(3, 2)))),
+            AnyMaze::Circ(CircMaze::new(generated(CircGrid::new(3)))),
+            AnyMaze::Cube(CubeMaze::new(generated(CubeGrid::new(2)))),
+            AnyMaze::Voronoi(VoronoiMaze::new(generated(VoronoiGrid::new(
+                &VoronoiShape::Circle { radius: 3.0 },
+                1.0,
+            )))),
+        ];
+        for maze in mazes {
+            let kind = maze.kind();
+            let maze_json = serde_json::to_value(&maze).unwrap();
+            let json =
+                serde_json::to_value(SavedMaze::new(MazeHeader::new(kind), maze.clone())).unwrap();
+            // The file of version 1 has no header, so its kind is detected by the content.
+            for json in [json, maze_json.clone()] {
+                let loaded = AnyMaze::from_json(json).unwrap();
+                assert_eq!(loaded.header.kind, kind);
+                assert_eq!(loaded.maze.kind(), kind);
+                assert_eq!(serde_json::to_value(&loaded.maze).unwrap(), maze_json);
+            }
+        }
+
+        assert!(AnyMaze::from_json(serde_json::json!({"cells": []})).is_err());
+    }
+
+    #[test]
+    fn test_dispatch() {
+        let maze = AnyMaze::Rect(RectMaze::NoMask(generated(RectGrid::<NoMask>::new(4, 3))));
+        assert_eq!(maze.kind(), MazeKind::Rect);
+        assert!(maze.text(AsciiBoxCharset).is_some());
+        let stats = maze.stats();
+        assert!(stats.passage_bias.is_some());
+        let solution = stats.solution.unwrap();
+        let path = maze.solve(&solution.start, &solution.goal).unwrap();
+        assert_eq!(path.len(), solution.length + 1);
+        assert_eq!(path.first(), Some(&solution.start));
+        assert_eq!(path.last(), Some(&solution.goal));
+
+        let maze = AnyMaze::Circ(CircMaze::new(CircGrid::new(2)));
+        assert!(maze.text(AsciiBoxCharset).is_none());
+        assert_eq!(maze.solve(&Position2d(0, 0), &Position2d(1, 0)), None);
+    }
+}
@@ -0,0 +1,118 @@
+use thiserror::Error;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum Error {
+    #[error("{0} doesn't fit in 32 bits")]
+    Overflow(usize),
+    #[error("unexpected end of data")]
+    UnexpectedEnd,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    bits_n: usize,
+}
+
+impl BitWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write the lowest bits of value, the lower bit is written first.
+    pub fn write_bits(&mut self, value: u64, bits_n: u32) {
+        for bit_ind in 0..bits_n {
+            if self.bits_n.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            if (value >> bit_ind) & 1 == 1 {
+                *self.bytes.last_mut().unwrap() |= 1 << (self.bits_n % 8);
+            }
+            self.bits_n += 1;
+        }
+    }
+
+    pub fn write_bool(&mut self, value: bool) {
+        self.write_bits(u64::from(value), 1);
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.write_bits(u64::from(value), 32);
+    }
+
+    /// Write count or index, fail if it doesn't fit in 32 bits.
+    pub fn write_usize(&mut self, value: usize) -> Result<(), Error> {
+        let value = u32::try_from(value).map_err(|_| Error::Overflow(value))?;
+        self.write_u32(value);
+        Ok(())
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.write_u32(value.to_bits());
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BitReader<'a> {
+    bytes: &'a [u8],
+    bit_ind: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, bit_ind: 0 }
+    }
+
+    pub fn remaining_bits_n(&self) -> usize {
+        self.bytes.len() * 8 - self.bit_ind
+    }
+
+    /// Fail if there are less bits left than expected, checked before allocating for the data claimed by a count, so
+    /// a broken count can't make a huge allocation.
+    pub fn expect_bits_n(&self, bits_n: usize) -> Result<(), Error> {
+        if self.remaining_bits_n() < bits_n {
+            Err(Error::UnexpectedEnd)
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn read_bits(&mut self, bits_n: u32) -> Result<u64, Error> {
+        self.expect_bits_n(bits_n as usize)?;
+        let mut value = 0;
+        for ind in 0..bits_n {
+            let bit = (self.bytes[self.bit_ind / 8] >> (self.bit_ind % 8)) & 1;
+            value |= u64::from(bit) << ind;
+            self.bit_ind += 1;
+        }
+
+        Ok(value)
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool, Error> {
+        Ok(self.read_bits(1)? == 1)
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, Error> {
+        Ok(self.read_bits(32)? as u32)
+    }
+
+    pub fn read_usize(&mut self) -> Result<usize, Error> {
+        Ok(self.read_u32()? as usize)
+    }
+
+    pub fn read_f32(&mut self) -> Result<f32, Error> {
+        Ok(f32::from_bits(self.read_u32()?))
+    }
+
+    /// Read count of items taking at least the given bits each, fail if the data left is too short for them.
+    pub fn read_count(&mut self, item_bits_n: usize) -> Result<usize, Error> {
+        let count = self.read_usize()?;
+        self.expect_bits_n(count.saturating_mul(item_bits_n))?;
+        Ok(count)
+    }
+}
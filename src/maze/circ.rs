@@ -6,7 +6,15 @@ use std::{
 use rand::seq::IteratorRandom;
 use serde::{Deserialize, Serialize};
 
-use super::{Grid2d, LayerGrid, Position2d};
+use crate::{
+    file::{self, binary::BinaryMaze},
+    rng,
+};
+
+use super::{
+    CellBits, Grid2d, LayerGrid, Maze, Position2d, StrayPassage,
+    bits::{BitReader, BitWriter},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum CircDirection {
@@ -65,6 +73,21 @@ struct CircCell {
     pub is_connected_clockwise: bool,
 }
 
+impl CellBits for CircCell {
+    const BITS_N: u32 = 2;
+
+    fn to_bits(&self) -> u64 {
+        u64::from(self.is_connected_inward) | u64::from(self.is_connected_clockwise) << 1
+    }
+
+    fn from_bits(_pos: &Position2d, bits: u64) -> Self {
+        Self {
+            is_connected_inward: bits & 1 != 0,
+            is_connected_clockwise: bits & 2 != 0,
+        }
+    }
+}
+
 pub enum CircCellPosIter {
     Once(Option<CircPosition>),
     CellRange {
@@ -128,7 +151,7 @@ impl Grid2d for CircGrid {
     }
 
     fn random_cell_pos(&self) -> Option<super::Position2d> {
-        let mut rng = rand::rng();
+        let mut rng = rng::rng();
         (0..self.cells_n())
             .choose(&mut rng)
             .and_then(|cell_ind| self.ind_to_pos(cell_ind))
@@ -152,6 +175,14 @@ impl Grid2d for CircGrid {
     }
 
     fn connect_to(&mut self, from: &Position2d, to: &Position2d) -> bool {
+        self.set_connection(from, to, true)
+    }
+
+    fn disconnect_from(&mut self, from: &Position2d, to: &Position2d) -> bool {
+        self.set_connection(from, to, false)
+    }
+
+    fn is_connected_to(&self, from: &Position2d, to: &Position2d) -> bool {
         let from = (*from).into();
         let to = (*to).into();
         let Some(dir) = CircDirection::all_dirs()
@@ -165,15 +196,26 @@ impl Grid2d for CircGrid {
             return false;
         };
         match dir {
-            CircDirection::Inward => self.cell_mut(&from).unwrap().is_connected_inward = true,
-            CircDirection::Clockwise => self.cell_mut(&from).unwrap().is_connected_clockwise = true,
-            CircDirection::Counterclockwise => {
-                self.cell_mut(&to).unwrap().is_connected_clockwise = true
-            }
-            CircDirection::Outward => self.cell_mut(&to).unwrap().is_connected_inward = true,
+            CircDirection::Inward => self.cell(&from).unwrap().is_connected_inward,
+            CircDirection::Clockwise => self.cell(&from).unwrap().is_connected_clockwise,
+            CircDirection::Counterclockwise => self.cell(&to).unwrap().is_connected_clockwise,
+            CircDirection::Outward => self.cell(&to).unwrap().is_connected_inward,
         }
+    }
 
-        true
+    fn stray_passages(&self) -> Vec<StrayPassage> {
+        // The center cell has no inner or clockwise neighbor, and the cells beyond the last ring aren't cells.
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell.is_connected_inward || cell.is_connected_clockwise)
+            .filter_map(|(ind, _)| self.ind_to_pos(ind))
+            .filter(|pos| pos.ring == 0 || pos.ring >= self.rings_n)
+            .map(|pos| StrayPassage {
+                from: pos.into(),
+                to: None,
+            })
+            .collect()
     }
 }
 
@@ -286,6 +328,37 @@ impl CircGrid {
         }
     }
 
+    fn set_connection(&mut self, from: &Position2d, to: &Position2d, is_connected: bool) -> bool {
+        let from = (*from).into();
+        let to = (*to).into();
+        let Some(dir) = CircDirection::all_dirs()
+            .iter()
+            .find(|dir| {
+                self.neighbor_pos_iter(&from, **dir)
+                    .any(|neighbor| neighbor == to)
+            })
+            .copied()
+        else {
+            return false;
+        };
+        match dir {
+            CircDirection::Inward => {
+                self.cell_mut(&from).unwrap().is_connected_inward = is_connected
+            }
+            CircDirection::Clockwise => {
+                self.cell_mut(&from).unwrap().is_connected_clockwise = is_connected
+            }
+            CircDirection::Counterclockwise => {
+                self.cell_mut(&to).unwrap().is_connected_clockwise = is_connected
+            }
+            CircDirection::Outward => {
+                self.cell_mut(&to).unwrap().is_connected_inward = is_connected
+            }
+        }
+
+        true
+    }
+
     fn cell_mut(&mut self, pos: &CircPosition) -> Option<&mut CircCell> {
         self.pos_to_ind(pos).and_then(|ind| self.cells.get_mut(ind))
     }
@@ -367,13 +440,82 @@ impl CircMaze {
             .cell(pos)
             .is_some_and(|cell| cell.is_connected_clockwise)
     }
+
+    /// The grid under maze, for analyzing its passages.
+    pub fn grid(&self) -> &dyn Grid2d {
+        &self.grid
+    }
+
+    /// Mutable access to the grid under maze, for editing its walls.
+    pub fn grid_mut(&mut self) -> &mut dyn Grid2d {
+        &mut self.grid
+    }
+}
+
+impl Maze for CircMaze {
+    type Position = CircPosition;
+    type Direction = CircDirection;
+
+    fn cells_n(&self) -> usize {
+        self.grid.cells_n()
+    }
+
+    fn cells(&self) -> impl Iterator<Item = CircPosition> + '_ {
+        (0..self.rings_n()).flat_map(move |ring| {
+            (0..self.ring_cells_n(ring)).map(move |cell| CircPosition::new(ring, cell))
+        })
+    }
+
+    fn is_cell(&self, pos: &CircPosition) -> bool {
+        self.grid.pos_to_ind(pos).is_some()
+    }
+
+    fn dirs(&self, pos: &CircPosition) -> &'static [CircDirection] {
+        // The center cell is a whole disk, it only has outward neighbors.
+        if pos.ring == 0 {
+            &[CircDirection::Outward]
+        } else {
+            CircDirection::all_dirs()
+        }
+    }
+
+    fn neighbors_at(&self, pos: &CircPosition, dir: CircDirection) -> Vec<CircPosition> {
+        self.grid.neighbor_pos_iter(pos, dir).collect()
+    }
+
+    fn is_connected(&self, pos: &CircPosition, other: &CircPosition) -> bool {
+        self.grid.is_connected_to(&(*pos).into(), &(*other).into())
+    }
+}
+
+impl BinaryMaze for CircMaze {
+    fn write_bits(&self, writer: &mut BitWriter) -> Result<(), file::Error> {
+        writer.write_usize(self.grid.rings_n)?;
+        for cell in &self.grid.cells {
+            writer.write_bits(cell.to_bits(), CircCell::BITS_N);
+        }
+
+        Ok(())
+    }
+
+    fn read_bits(reader: &mut BitReader) -> Result<Self, file::Error> {
+        // Every ring except the innermost one has more than 2 * ring_ind cells, so there are at least rings_n^2 bits.
+        let rings_n = reader.read_usize()?;
+        reader.expect_bits_n(rings_n.saturating_mul(rings_n))?;
+        let mut grid = CircGrid::new(rings_n);
+        for cell in grid.cells.iter_mut() {
+            *cell = CircCell::from_bits(&Position2d(0, 0), reader.read_bits(CircCell::BITS_N)?);
+        }
+
+        Ok(Self { grid })
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::maze::circ::CircPosition;
+    use crate::maze::{Position2d, circ::CircPosition};
 
-    use super::{CircDirection, CircGrid};
+    use super::{CircDirection, CircGrid, CircMaze};
 
     #[test]
     fn test_grid_make_rings() {
@@ -509,4 +651,39 @@ mod test {
 
         assert_eq!(pos_ind, expect_pos.len());
     }
+
+    #[test]
+    fn test_maze_edit_seen_from_both_cells() {
+        let mut maze = CircMaze::new(CircGrid::new(4));
+        let pos = CircPosition::new(2, 0);
+        let outer = CircPosition::new(3, 1);
+        let counterclockwise = CircPosition::new(2, 11);
+        // Passages are stored as inward in the outer cell and clockwise in the counterclockwise cell.
+        assert!(maze.grid_mut().connect_to(&pos.into(), &outer.into()));
+        assert!(
+            maze.grid_mut()
+                .connect_to(&pos.into(), &counterclockwise.into())
+        );
+        assert!(maze.is_connected_inward(&outer));
+        assert!(maze.is_connected_clockwise(&counterclockwise));
+        assert!(!maze.is_connected_inward(&pos));
+        assert!(!maze.is_connected_clockwise(&pos));
+        assert!(maze.grid_mut().is_connected_to(&outer.into(), &pos.into()));
+
+        assert!(maze.grid_mut().disconnect_from(&outer.into(), &pos.into()));
+        assert!(!maze.is_connected_inward(&outer));
+        assert!(
+            maze.grid_mut()
+                .toggle_connection(&counterclockwise.into(), &pos.into())
+        );
+        assert!(!maze.is_connected_clockwise(&counterclockwise));
+        assert!(
+            maze.grid_mut()
+                .toggle_connection(&pos.into(), &Position2d(1, 0))
+        );
+        assert!(maze.is_connected_inward(&pos));
+
+        maze.grid_mut().set_all_walls();
+        assert!(!maze.is_connected_inward(&pos));
+    }
 }
@@ -0,0 +1,469 @@
+use std::collections::HashSet;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    file::{self, binary::BinaryMaze},
+    rng,
+};
+
+use super::{
+    CellBits, Grid2d, Position2d,
+    bits::{BitReader, BitWriter},
+    rect::RectDirection,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CubeFace {
+    Front,
+    Right,
+    Back,
+    Left,
+    Top,
+    Bottom,
+}
+
+impl CubeFace {
+    pub fn all_faces() -> &'static [CubeFace; 6] {
+        static ALL_FACES: [CubeFace; 6] = [
+            CubeFace::Front,
+            CubeFace::Right,
+            CubeFace::Back,
+            CubeFace::Left,
+            CubeFace::Top,
+            CubeFace::Bottom,
+        ];
+        &ALL_FACES
+    }
+
+    pub fn index(&self) -> usize {
+        match self {
+            CubeFace::Front => 0,
+            CubeFace::Right => 1,
+            CubeFace::Back => 2,
+            CubeFace::Left => 3,
+            CubeFace::Top => 4,
+            CubeFace::Bottom => 5,
+        }
+    }
+
+    pub fn from_index(ind: usize) -> Option<Self> {
+        Self::all_faces().get(ind).copied()
+    }
+
+    /// The frame of the face on the cube [0, 1]^3(x to the right, y to the up, z to the viewer).
+    /// It's (origin, column direction, row direction, outward normal), the origin is the corner at row 0 and column 0.
+    pub fn frame(&self) -> ([i64; 3], [i64; 3], [i64; 3], [i64; 3]) {
+        match self {
+            CubeFace::Front => ([0, 1, 1], [1, 0, 0], [0, -1, 0], [0, 0, 1]),
+            CubeFace::Right => ([1, 1, 1], [0, 0, -1], [0, -1, 0], [1, 0, 0]),
+            CubeFace::Back => ([1, 1, 0], [-1, 0, 0], [0, -1, 0], [0, 0, -1]),
+            CubeFace::Left => ([0, 1, 0], [0, 0, 1], [0, -1, 0], [-1, 0, 0]),
+            CubeFace::Top => ([0, 1, 0], [1, 0, 0], [0, 0, 1], [0, 1, 0]),
+            CubeFace::Bottom => ([0, 0, 1], [1, 0, 0], [0, 0, -1], [0, -1, 0]),
+        }
+    }
+
+    /// The position(column, row) of the face in the unfolded net, which is a cross of 4 x 3 faces.
+    pub fn net_pos(&self) -> (usize, usize) {
+        match self {
+            CubeFace::Front => (1, 1),
+            CubeFace::Right => (2, 1),
+            CubeFace::Back => (3, 1),
+            CubeFace::Left => (0, 1),
+            CubeFace::Top => (1, 0),
+            CubeFace::Bottom => (1, 2),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CubePosition {
+    pub face: CubeFace,
+    pub row: usize,
+    pub col: usize,
+}
+
+impl CubePosition {
+    pub fn new(face: CubeFace, row: usize, col: usize) -> Self {
+        Self { face, row, col }
+    }
+
+    fn from_pos2d(pos: &Position2d, size: usize) -> Option<Self> {
+        CubeFace::from_index(pos.0)
+            .filter(|_| pos.1 < size * size)
+            .map(|face| Self::new(face, pos.1 / size, pos.1 % size))
+    }
+
+    fn to_pos2d(self, size: usize) -> Position2d {
+        Position2d(self.face.index(), self.row * size + self.col)
+    }
+}
+
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+struct CubeCell {
+    #[serde(rename = "n")]
+    is_connected_to_north: bool,
+    #[serde(rename = "e")]
+    is_connected_to_east: bool,
+    #[serde(rename = "s")]
+    is_connected_to_south: bool,
+    #[serde(rename = "w")]
+    is_connected_to_west: bool,
+}
+
+impl CubeCell {
+    fn link_mut(&mut self, dir: RectDirection) -> &mut bool {
+        match dir {
+            RectDirection::North => &mut self.is_connected_to_north,
+            RectDirection::East => &mut self.is_connected_to_east,
+            RectDirection::South => &mut self.is_connected_to_south,
+            RectDirection::West => &mut self.is_connected_to_west,
+        }
+    }
+
+    fn link(&self, dir: RectDirection) -> bool {
+        match dir {
+            RectDirection::North => self.is_connected_to_north,
+            RectDirection::East => self.is_connected_to_east,
+            RectDirection::South => self.is_connected_to_south,
+            RectDirection::West => self.is_connected_to_west,
+        }
+    }
+}
+
+impl CellBits for CubeCell {
+    const BITS_N: u32 = 4;
+
+    fn to_bits(&self) -> u64 {
+        RectDirection::all_dirs()
+            .iter()
+            .enumerate()
+            .map(|(ind, dir)| u64::from(self.link(*dir)) << ind)
+            .sum()
+    }
+
+    fn from_bits(_pos: &Position2d, bits: u64) -> Self {
+        let mut cell = Self::default();
+        for (ind, dir) in RectDirection::all_dirs().iter().enumerate() {
+            *cell.link_mut(*dir) = (bits >> ind) & 1 != 0;
+        }
+
+        cell
+    }
+}
+
+/// The grid on the surface of a cube, each face is a square of size x size cells.
+/// The directions of a cell are local to its face, and the neighbors of border cells are on the adjacent faces.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CubeGrid {
+    size: usize,
+    cells: Vec<CubeCell>,
+}
+
+impl Grid2d for CubeGrid {
+    fn cells_n(&self) -> usize {
+        self.cells.len()
+    }
+
+    fn random_cell_pos(&self) -> Option<Position2d> {
+        if self.size == 0 {
+            return None;
+        }
+
+        let mut rng = rng::rng();
+        Some(Position2d(
+            rng.random_range(0..CubeFace::all_faces().len()),
+            rng.random_range(0..self.size * self.size),
+        ))
+    }
+
+    fn all_cells_pos_set(&self) -> HashSet<Position2d> {
+        (0..CubeFace::all_faces().len())
+            .flat_map(|face| (0..self.size * self.size).map(move |ind| Position2d(face, ind)))
+            .collect()
+    }
+
+    fn append_neighbors(&self, pos: &Position2d, neighbors: &mut Vec<Position2d>) {
+        if let Some(pos) = CubePosition::from_pos2d(pos, self.size) {
+            neighbors.extend(
+                RectDirection::all_dirs()
+                    .iter()
+                    .filter_map(|dir| self.neighbor_pos(&pos, *dir))
+                    .map(|neighbor| neighbor.to_pos2d(self.size)),
+            );
+        }
+    }
+
+    fn connect_to(&mut self, from: &Position2d, to: &Position2d) -> bool {
+        let Some(from) = CubePosition::from_pos2d(from, self.size) else {
+            return false;
+        };
+        let Some(to) = CubePosition::from_pos2d(to, self.size) else {
+            return false;
+        };
+        RectDirection::all_dirs()
+            .iter()
+            .find(|dir| self.neighbor_pos(&from, **dir) == Some(to))
+            .is_some_and(|dir| self.set_connection(&from, *dir, true))
+    }
+
+    fn disconnect_from(&mut self, from: &Position2d, to: &Position2d) -> bool {
+        let Some(from) = CubePosition::from_pos2d(from, self.size) else {
+            return false;
+        };
+        let Some(to) = CubePosition::from_pos2d(to, self.size) else {
+            return false;
+        };
+        RectDirection::all_dirs()
+            .iter()
+            .find(|dir| self.neighbor_pos(&from, **dir) == Some(to))
+            .is_some_and(|dir| self.set_connection(&from, *dir, false))
+    }
+
+    fn is_connected_to(&self, from: &Position2d, to: &Position2d) -> bool {
+        let Some(from) = CubePosition::from_pos2d(from, self.size) else {
+            return false;
+        };
+        let Some(to) = CubePosition::from_pos2d(to, self.size) else {
+            return false;
+        };
+        RectDirection::all_dirs()
+            .iter()
+            .find(|dir| self.neighbor_pos(&from, **dir) == Some(to))
+            .is_some_and(|dir| self.is_connected_to(&from, *dir))
+    }
+}
+
+impl CubeGrid {
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            cells: vec![CubeCell::default(); CubeFace::all_faces().len() * size * size],
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// The center of cell in the unfolded net(as the maze picture), relative to the size of net.
+    pub fn net_point(&self, pos: &Position2d) -> Option<(f64, f64)> {
+        let pos = CubePosition::from_pos2d(pos, self.size)?;
+        let (net_col, net_row) = pos.face.net_pos();
+        let cells_n = |net_ind: usize, ind: usize| (net_ind * self.size + ind) as f64 + 0.5;
+        Some((
+            cells_n(net_col, pos.col) / (4 * self.size) as f64,
+            cells_n(net_row, pos.row) / (3 * self.size) as f64,
+        ))
+    }
+
+    pub fn neighbor_pos(&self, pos: &CubePosition, dir: RectDirection) -> Option<CubePosition> {
+        if pos.row >= self.size || pos.col >= self.size {
+            return None;
+        }
+
+        let in_face_neighbor = match dir {
+            RectDirection::North => pos.row.checked_sub(1).map(|row| (row, pos.col)),
+            RectDirection::South => Some(pos.row + 1)
+                .filter(|row| *row < self.size)
+                .map(|row| (row, pos.col)),
+            RectDirection::West => pos.col.checked_sub(1).map(|col| (pos.row, col)),
+            RectDirection::East => Some(pos.col + 1)
+                .filter(|col| *col < self.size)
+                .map(|col| (pos.row, col)),
+        };
+        if let Some((row, col)) = in_face_neighbor {
+            return Some(CubePosition::new(pos.face, row, col));
+        }
+
+        // Walk across the cube edge in the doubled coordinates, so every cell center is on integers.
+        let size = i64::try_from(self.size).ok()?;
+        let (origin, col_dir, row_dir, normal) = pos.face.frame();
+        let col = i64::try_from(pos.col).ok()? * 2 + 1;
+        let row = i64::try_from(pos.row).ok()? * 2 + 1;
+        let (step_col, step_row) = match dir {
+            RectDirection::North => (0, -1),
+            RectDirection::South => (0, 1),
+            RectDirection::West => (-1, 0),
+            RectDirection::East => (1, 0),
+        };
+        // From the cell center to the edge, then half a cell into the cube, reaching the center of neighbor on the adjacent face.
+        let neighbor_center: [i64; 3] = std::array::from_fn(|axis| {
+            origin[axis] * size * 2
+                + col_dir[axis] * (col + step_col)
+                + row_dir[axis] * (row + step_row)
+                - normal[axis]
+        });
+        CubeFace::all_faces().iter().find_map(|face| {
+            let (origin, col_dir, row_dir, _) = face.frame();
+            let offset: [i64; 3] =
+                std::array::from_fn(|axis| neighbor_center[axis] - origin[axis] * size * 2);
+            let dot = |dir: [i64; 3]| (0..3).map(|axis| offset[axis] * dir[axis]).sum::<i64>();
+            let (col, row) = (dot(col_dir), dot(row_dir));
+            // The offset should be in the plane of the face.
+            let in_plane = (0..3)
+                .map(|axis| col * col_dir[axis] + row * row_dir[axis])
+                .eq(offset.iter().copied());
+            (in_plane && (0..size * 2).contains(&col) && (0..size * 2).contains(&row))
+                .then(|| CubePosition::new(*face, (row / 2) as usize, (col / 2) as usize))
+        })
+    }
+
+    pub fn is_connected_to(&self, pos: &CubePosition, dir: RectDirection) -> bool {
+        self.cell(pos).is_some_and(|cell| cell.link(dir))
+    }
+
+    fn set_connection(
+        &mut self,
+        pos: &CubePosition,
+        dir: RectDirection,
+        is_connected: bool,
+    ) -> bool {
+        let Some(neighbor) = self.neighbor_pos(pos, dir) else {
+            return false;
+        };
+        // The direction back from the neighbor, which may differ from the reverse direction across faces.
+        let Some(back_dir) = RectDirection::all_dirs()
+            .iter()
+            .find(|back_dir| self.neighbor_pos(&neighbor, **back_dir) == Some(*pos))
+            .copied()
+        else {
+            return false;
+        };
+        *self.cell_mut(pos).unwrap().link_mut(dir) = is_connected;
+        *self.cell_mut(&neighbor).unwrap().link_mut(back_dir) = is_connected;
+        true
+    }
+
+    fn cell(&self, pos: &CubePosition) -> Option<&CubeCell> {
+        self.pos_to_ind(pos).map(|ind| &self.cells[ind])
+    }
+
+    fn cell_mut(&mut self, pos: &CubePosition) -> Option<&mut CubeCell> {
+        self.pos_to_ind(pos).map(|ind| &mut self.cells[ind])
+    }
+
+    fn pos_to_ind(&self, pos: &CubePosition) -> Option<usize> {
+        if pos.row < self.size && pos.col < self.size {
+            Some((pos.face.index() * self.size + pos.row) * self.size + pos.col)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CubeMaze(CubeGrid);
+
+impl CubeMaze {
+    pub fn new(grid: CubeGrid) -> Self {
+        Self(grid)
+    }
+
+    pub fn size(&self) -> usize {
+        self.0.size()
+    }
+
+    pub fn neighbor_pos(&self, pos: &CubePosition, dir: RectDirection) -> Option<CubePosition> {
+        self.0.neighbor_pos(pos, dir)
+    }
+
+    pub fn is_connected_to(&self, pos: &CubePosition, dir: RectDirection) -> bool {
+        self.0.is_connected_to(pos, dir)
+    }
+
+    /// The grid under maze, for analyzing its passages.
+    pub fn grid(&self) -> &dyn Grid2d {
+        &self.0
+    }
+
+    /// Mutable access to the grid under maze, for editing its walls.
+    pub fn grid_mut(&mut self) -> &mut dyn Grid2d {
+        &mut self.0
+    }
+}
+
+impl BinaryMaze for CubeMaze {
+    fn write_bits(&self, writer: &mut BitWriter) -> Result<(), file::Error> {
+        writer.write_usize(self.0.size)?;
+        for cell in &self.0.cells {
+            writer.write_bits(cell.to_bits(), CubeCell::BITS_N);
+        }
+
+        Ok(())
+    }
+
+    fn read_bits(reader: &mut BitReader) -> Result<Self, file::Error> {
+        let size = reader.read_usize()?;
+        let cells_n = size
+            .saturating_mul(size)
+            .saturating_mul(CubeFace::all_faces().len());
+        reader.expect_bits_n(cells_n.saturating_mul(CubeCell::BITS_N as usize))?;
+        let mut grid = CubeGrid::new(size);
+        for cell in grid.cells.iter_mut() {
+            *cell = CubeCell::from_bits(&Position2d(0, 0), reader.read_bits(CubeCell::BITS_N)?);
+        }
+
+        Ok(Self(grid))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maze::{Grid2d, rect::RectDirection};
+
+    use super::{CubeFace, CubeGrid, CubePosition};
+
+    #[test]
+    fn test_grid_neighbor_pos_across_faces() {
+        let grid = CubeGrid::new(3);
+        let front_top_left = CubePosition::new(CubeFace::Front, 0, 0);
+        assert_eq!(
+            grid.neighbor_pos(&front_top_left, RectDirection::North),
+            Some(CubePosition::new(CubeFace::Top, 2, 0))
+        );
+        assert_eq!(
+            grid.neighbor_pos(&front_top_left, RectDirection::West),
+            Some(CubePosition::new(CubeFace::Left, 0, 2))
+        );
+        let top_top_left = CubePosition::new(CubeFace::Top, 0, 0);
+        assert_eq!(
+            grid.neighbor_pos(&top_top_left, RectDirection::North),
+            Some(CubePosition::new(CubeFace::Back, 0, 2))
+        );
+        assert_eq!(
+            grid.neighbor_pos(&top_top_left, RectDirection::West),
+            Some(CubePosition::new(CubeFace::Left, 0, 0))
+        );
+        let bottom_bot_right = CubePosition::new(CubeFace::Bottom, 2, 2);
+        assert_eq!(
+            grid.neighbor_pos(&bottom_bot_right, RectDirection::South),
+            Some(CubePosition::new(CubeFace::Back, 2, 0))
+        );
+        assert_eq!(
+            grid.neighbor_pos(&bottom_bot_right, RectDirection::East),
+            Some(CubePosition::new(CubeFace::Right, 2, 2))
+        );
+    }
+
+    #[test]
+    fn test_grid_neighbors_symmetric() {
+        let grid = CubeGrid::new(4);
+        let mut neighbors = Vec::new();
+        let mut back_neighbors = Vec::new();
+        for pos in grid.all_cells_pos_set() {
+            neighbors.clear();
+            grid.append_neighbors(&pos, &mut neighbors);
+            assert_eq!(neighbors.len(), 4);
+            for neighbor in neighbors.iter() {
+                back_neighbors.clear();
+                grid.append_neighbors(neighbor, &mut back_neighbors);
+                assert!(back_neighbors.contains(&pos));
+            }
+        }
+    }
+}
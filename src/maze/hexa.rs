@@ -2,8 +2,13 @@ use std::marker::PhantomData;
 
 use serde::{Deserialize, Serialize};
 
+use crate::file::{self, binary::BinaryMaze};
+
 use super::{
-    GeneralRectGrid, Grid2d, LayerGrid, MaskType, NoMask, Position2d, WithMask, rect::RectMask,
+    CellBits, GeneralRectGrid, Grid2d, LayerGrid, MaskType, Maze, NoMask, Position2d, StrayPassage,
+    WithMask,
+    bits::{BitReader, BitWriter},
+    rect::RectMask,
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -56,29 +61,31 @@ impl HexaPosition {
     pub fn neighbor(&self, dir: HexaDirection) -> Option<Self> {
         match dir {
             HexaDirection::North if self.row > 0 => Some(Self::new(self.row - 1, self.col)),
-            HexaDirection::NorthEast if self.col % 2 == 0 && self.row > 0 => {
+            HexaDirection::NorthEast if self.col.is_multiple_of(2) && self.row > 0 => {
                 Some(Self::new(self.row - 1, self.col + 1))
             }
-            HexaDirection::NorthEast if self.col % 2 == 1 => {
+            HexaDirection::NorthEast if !self.col.is_multiple_of(2) => {
                 Some(Self::new(self.row, self.col + 1))
             }
-            HexaDirection::SouthEast if self.col % 2 == 0 => {
+            HexaDirection::SouthEast if self.col.is_multiple_of(2) => {
                 Some(Self::new(self.row, self.col + 1))
             }
-            HexaDirection::SouthEast if self.col % 2 == 1 => {
+            HexaDirection::SouthEast if !self.col.is_multiple_of(2) => {
                 Some(Self::new(self.row + 1, self.col + 1))
             }
             HexaDirection::South => Some(Self::new(self.row + 1, self.col)),
-            HexaDirection::SouthWest if self.col > 0 && self.col % 2 == 0 => {
+            HexaDirection::SouthWest if self.col > 0 && self.col.is_multiple_of(2) => {
                 Some(Self::new(self.row, self.col - 1))
             }
-            HexaDirection::SouthWest if self.col > 0 && self.col % 2 == 1 => {
+            HexaDirection::SouthWest if self.col > 0 && !self.col.is_multiple_of(2) => {
                 Some(Self::new(self.row + 1, self.col - 1))
             }
-            HexaDirection::NorthWest if self.col > 0 && self.col % 2 == 0 && self.row > 0 => {
+            HexaDirection::NorthWest
+                if self.col > 0 && self.col.is_multiple_of(2) && self.row > 0 =>
+            {
                 Some(Self::new(self.row - 1, self.col - 1))
             }
-            HexaDirection::NorthWest if self.col > 0 && self.col % 2 == 1 => {
+            HexaDirection::NorthWest if self.col > 0 && !self.col.is_multiple_of(2) => {
                 Some(Self::new(self.row, self.col - 1))
             }
             _ => None,
@@ -96,6 +103,24 @@ struct HexaCell {
     is_connected_to_southwest: bool,
 }
 
+impl CellBits for HexaCell {
+    const BITS_N: u32 = 3;
+
+    fn to_bits(&self) -> u64 {
+        u64::from(self.is_connected_to_north)
+            | u64::from(self.is_connected_to_northwest) << 1
+            | u64::from(self.is_connected_to_southwest) << 2
+    }
+
+    fn from_bits(_pos: &Position2d, bits: u64) -> Self {
+        Self {
+            is_connected_to_north: bits & 1 != 0,
+            is_connected_to_northwest: bits & 2 != 0,
+            is_connected_to_southwest: bits & 4 != 0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HexaGrid<M: MaskType>(GeneralRectGrid<HexaCell>, PhantomData<M>);
 
@@ -123,40 +148,44 @@ impl<M: MaskType> Grid2d for HexaGrid<M> {
     }
 
     fn connect_to(&mut self, from: &Position2d, to: &Position2d) -> bool {
-        let hex_from = (*from).into();
-        let hex_to = (*to).into();
-        let Some(dir) = HexaDirection::all_dirs()
+        self.set_connection(from, to, true)
+    }
+
+    fn disconnect_from(&mut self, from: &Position2d, to: &Position2d) -> bool {
+        self.set_connection(from, to, false)
+    }
+
+    fn is_connected_to(&self, from: &Position2d, to: &Position2d) -> bool {
+        let hexa_from = (*from).into();
+        let hexa_to = (*to).into();
+        HexaDirection::all_dirs()
             .iter()
-            .find(|dir| {
-                self.neighbor_pos(&hex_from, **dir)
-                    .is_some_and(|neighbor| neighbor == hex_to)
-            })
-            .copied()
-        else {
-            return false;
-        };
-        match dir {
-            HexaDirection::North => self.0.cell_mut(from).unwrap().is_connected_to_north = true,
-            HexaDirection::NorthEast => {
-                self.0.cell_mut(to).unwrap().is_connected_to_southwest = true
-            }
-            HexaDirection::SouthEast => {
-                self.0.cell_mut(to).unwrap().is_connected_to_northwest = true
-            }
-            HexaDirection::South => self.0.cell_mut(to).unwrap().is_connected_to_north = true,
-            HexaDirection::SouthWest => {
-                self.0.cell_mut(from).unwrap().is_connected_to_southwest = true
-            }
-            HexaDirection::NorthWest => {
-                self.0.cell_mut(from).unwrap().is_connected_to_northwest = true
-            }
-        }
+            .find(|dir| self.neighbor_pos(&hexa_from, **dir) == Some(hexa_to))
+            .is_some_and(|dir| self.is_connected_to(&hexa_from, *dir))
+    }
 
-        true
+    fn stray_passages(&self) -> Vec<StrayPassage> {
+        self.0
+            .raw_cell_iter()
+            .flat_map(|(pos, cell)| {
+                let hexa_pos = HexaPosition::from(pos);
+                [
+                    (cell.is_connected_to_north, HexaDirection::North),
+                    (cell.is_connected_to_northwest, HexaDirection::NorthWest),
+                    (cell.is_connected_to_southwest, HexaDirection::SouthWest),
+                ]
+                .into_iter()
+                .filter(|(is_connected, _)| *is_connected)
+                .filter_map(move |(_, dir)| {
+                    self.0
+                        .stray_passage(&pos, hexa_pos.neighbor(dir).map(Position2d::from))
+                })
+            })
+            .collect()
     }
 }
 
-impl LayerGrid for HexaGrid<NoMask> {
+impl<M: MaskType> LayerGrid for HexaGrid<M> {
     fn layers_n(&self) -> usize {
         self.0.height
     }
@@ -165,17 +194,39 @@ impl LayerGrid for HexaGrid<NoMask> {
         self.0.width
     }
 
+    fn is_cell(&self, pos: &Position2d) -> bool {
+        self.0.is_cell(pos)
+    }
+
     fn append_neighbors_upper_layer(&self, pos: &Position2d, neighbors: &mut Vec<Position2d>) {
+        // The cell in the even column also has the northeast and the northwest neighbors in the upper layer.
+        let hexa_pos = (*pos).into();
         neighbors.extend(
-            self.neighbor_pos(&(*pos).into(), HexaDirection::North)
-                .map(Position2d::from),
+            [
+                HexaDirection::North,
+                HexaDirection::NorthEast,
+                HexaDirection::NorthWest,
+            ]
+            .into_iter()
+            .filter_map(|dir| self.neighbor_pos(&hexa_pos, dir))
+            .filter(|neighbor| neighbor.row < pos.0)
+            .map(Position2d::from),
         )
     }
 
     fn append_neighbors_lower_layer(&self, pos: &Position2d, neighbors: &mut Vec<Position2d>) {
+        // The cell in the odd column also has the southeast and the southwest neighbors in the lower layer.
+        let hexa_pos = (*pos).into();
         neighbors.extend(
-            self.neighbor_pos(&(*pos).into(), HexaDirection::South)
-                .map(Position2d::from),
+            [
+                HexaDirection::South,
+                HexaDirection::SouthEast,
+                HexaDirection::SouthWest,
+            ]
+            .into_iter()
+            .filter_map(|dir| self.neighbor_pos(&hexa_pos, dir))
+            .filter(|neighbor| neighbor.row > pos.0)
+            .map(Position2d::from),
         )
     }
 }
@@ -199,6 +250,38 @@ impl HexaGrid<WithMask> {
 }
 
 impl<M: MaskType> HexaGrid<M> {
+    pub fn is_connected_to(&self, hexa_pos: &HexaPosition, dir: HexaDirection) -> bool {
+        let pos = (*hexa_pos).into();
+        let grid = &self.0;
+        grid.is_cell(&pos)
+            && match dir {
+                direct_dir @ (HexaDirection::North
+                | HexaDirection::SouthWest
+                | HexaDirection::NorthWest) => {
+                    let cell = grid.cell(&pos).unwrap();
+                    match direct_dir {
+                        HexaDirection::North => cell.is_connected_to_north,
+                        HexaDirection::SouthWest => cell.is_connected_to_southwest,
+                        HexaDirection::NorthWest => cell.is_connected_to_northwest,
+                        _ => unreachable!(),
+                    }
+                }
+                indirect_dir @ (HexaDirection::NorthEast
+                | HexaDirection::SouthEast
+                | HexaDirection::South) => {
+                    hexa_pos.neighbor(indirect_dir).is_some_and(|neighbor| {
+                        grid.cell(&neighbor.into())
+                            .is_some_and(|cell| match indirect_dir {
+                                HexaDirection::NorthEast => cell.is_connected_to_southwest,
+                                HexaDirection::SouthEast => cell.is_connected_to_northwest,
+                                HexaDirection::South => cell.is_connected_to_north,
+                                _ => unreachable!(),
+                            })
+                    })
+                }
+            }
+    }
+
     pub fn neighbor_pos(
         &self,
         hexa_pos: &HexaPosition,
@@ -213,6 +296,43 @@ impl<M: MaskType> HexaGrid<M> {
             None
         }
     }
+
+    fn set_connection(&mut self, from: &Position2d, to: &Position2d, is_connected: bool) -> bool {
+        let hex_from = (*from).into();
+        let hex_to = (*to).into();
+        let Some(dir) = HexaDirection::all_dirs()
+            .iter()
+            .find(|dir| {
+                self.neighbor_pos(&hex_from, **dir)
+                    .is_some_and(|neighbor| neighbor == hex_to)
+            })
+            .copied()
+        else {
+            return false;
+        };
+        match dir {
+            HexaDirection::North => {
+                self.0.cell_mut(from).unwrap().is_connected_to_north = is_connected
+            }
+            HexaDirection::NorthEast => {
+                self.0.cell_mut(to).unwrap().is_connected_to_southwest = is_connected
+            }
+            HexaDirection::SouthEast => {
+                self.0.cell_mut(to).unwrap().is_connected_to_northwest = is_connected
+            }
+            HexaDirection::South => {
+                self.0.cell_mut(to).unwrap().is_connected_to_north = is_connected
+            }
+            HexaDirection::SouthWest => {
+                self.0.cell_mut(from).unwrap().is_connected_to_southwest = is_connected
+            }
+            HexaDirection::NorthWest => {
+                self.0.cell_mut(from).unwrap().is_connected_to_northwest = is_connected
+            }
+        }
+
+        true
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -223,49 +343,102 @@ pub enum HexaMaze {
 
 impl HexaMaze {
     pub fn size(&self) -> (usize, usize) {
-        self.grid().size()
+        self.rect_grid().size()
     }
 
     pub fn is_cell(&self, pos: &HexaPosition) -> bool {
-        self.grid().is_cell(&(*pos).into())
+        self.rect_grid().is_cell(&(*pos).into())
     }
 
     pub fn is_connected_to(&self, hexa_pos: &HexaPosition, dir: HexaDirection) -> bool {
-        let pos = (*hexa_pos).into();
-        let grid = self.grid();
-        self.is_cell(hexa_pos)
-            && match dir {
-                direct_dir @ (HexaDirection::North
-                | HexaDirection::SouthWest
-                | HexaDirection::NorthWest) => {
-                    let cell = grid.cell(&pos).unwrap();
-                    match direct_dir {
-                        HexaDirection::North => cell.is_connected_to_north,
-                        HexaDirection::SouthWest => cell.is_connected_to_southwest,
-                        HexaDirection::NorthWest => cell.is_connected_to_northwest,
-                        _ => unreachable!(),
-                    }
-                }
-                indirect_dir @ (HexaDirection::NorthEast
-                | HexaDirection::SouthEast
-                | HexaDirection::South) => {
-                    hexa_pos.neighbor(indirect_dir).is_some_and(|neighbor| {
-                        grid.cell(&neighbor.into())
-                            .is_some_and(|cell| match indirect_dir {
-                                HexaDirection::NorthEast => cell.is_connected_to_southwest,
-                                HexaDirection::SouthEast => cell.is_connected_to_northwest,
-                                HexaDirection::South => cell.is_connected_to_north,
-                                _ => unreachable!(),
-                            })
-                    })
-                }
-            }
+        match self {
+            HexaMaze::NoMask(hexa_grid) => hexa_grid.is_connected_to(hexa_pos, dir),
+            HexaMaze::WithMask(hexa_grid) => hexa_grid.is_connected_to(hexa_pos, dir),
+        }
+    }
+
+    /// The grid under maze, for analyzing its passages.
+    pub fn grid(&self) -> &dyn Grid2d {
+        match self {
+            HexaMaze::NoMask(hexa_grid) => hexa_grid,
+            HexaMaze::WithMask(hexa_grid) => hexa_grid,
+        }
+    }
+
+    /// Mutable access to the grid under maze, for editing its walls.
+    pub fn grid_mut(&mut self) -> &mut dyn Grid2d {
+        match self {
+            HexaMaze::NoMask(hexa_grid) => hexa_grid,
+            HexaMaze::WithMask(hexa_grid) => hexa_grid,
+        }
     }
 
-    fn grid(&self) -> &GeneralRectGrid<HexaCell> {
+    fn rect_grid(&self) -> &GeneralRectGrid<HexaCell> {
         match self {
             HexaMaze::NoMask(hexa_grid) => &hexa_grid.0,
             HexaMaze::WithMask(hexa_grid) => &hexa_grid.0,
         }
     }
 }
+
+impl Maze for HexaMaze {
+    type Position = HexaPosition;
+    type Direction = HexaDirection;
+
+    fn cells_n(&self) -> usize {
+        self.rect_grid().cells_n()
+    }
+
+    fn cells(&self) -> impl Iterator<Item = HexaPosition> + '_ {
+        let (width, height) = self.size();
+        (0..height)
+            .flat_map(move |r| (0..width).map(move |c| HexaPosition::new(r, c)))
+            .filter(|pos| HexaMaze::is_cell(self, pos))
+    }
+
+    fn is_cell(&self, pos: &HexaPosition) -> bool {
+        HexaMaze::is_cell(self, pos)
+    }
+
+    fn dirs(&self, _pos: &HexaPosition) -> &'static [HexaDirection] {
+        HexaDirection::all_dirs()
+    }
+
+    fn neighbors_at(&self, pos: &HexaPosition, dir: HexaDirection) -> Vec<HexaPosition> {
+        match self {
+            HexaMaze::NoMask(hexa_grid) => hexa_grid.neighbor_pos(pos, dir),
+            HexaMaze::WithMask(hexa_grid) => hexa_grid.neighbor_pos(pos, dir),
+        }
+        .into_iter()
+        .collect()
+    }
+
+    fn is_connected(&self, pos: &HexaPosition, other: &HexaPosition) -> bool {
+        self.grid()
+            .is_connected_to(&(*pos).into(), &(*other).into())
+    }
+
+    fn is_connected_to(&self, pos: &HexaPosition, dir: HexaDirection) -> bool {
+        HexaMaze::is_connected_to(self, pos, dir)
+    }
+}
+
+impl BinaryMaze for HexaMaze {
+    fn write_bits(&self, writer: &mut BitWriter) -> Result<(), file::Error> {
+        match self {
+            HexaMaze::NoMask(hexa_grid) => hexa_grid.0.write_bits(writer)?,
+            HexaMaze::WithMask(hexa_grid) => hexa_grid.0.write_bits(writer)?,
+        }
+
+        Ok(())
+    }
+
+    fn read_bits(reader: &mut BitReader) -> Result<Self, file::Error> {
+        let grid = GeneralRectGrid::<HexaCell>::read_bits(reader)?;
+        Ok(if grid.mask.is_some() {
+            HexaMaze::WithMask(HexaGrid(grid, PhantomData))
+        } else {
+            HexaMaze::NoMask(HexaGrid(grid, PhantomData))
+        })
+    }
+}
@@ -14,9 +14,17 @@ use rand::Rng;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::show::rect::{AsciiBoxCharset, RectMazeCmdDisplay};
+use crate::{
+    file::{self, binary::BinaryMaze},
+    show::rect::{AsciiBoxCharset, RectMazeCmdDisplay},
+    tile::{TileMap, TileSettings},
+};
 
-use super::{GeneralRectGrid, Grid2d, LayerGrid, MaskType, NoMask, Position2d, WithMask};
+use super::{
+    CellBits, GeneralRectGrid, Grid2d, LayerGrid, MaskType, Maze, NoMask, Position2d, StrayPassage,
+    WithMask,
+    bits::{BitReader, BitWriter},
+};
 
 #[derive(Debug, Clone, Error)]
 enum Error {
@@ -253,6 +261,21 @@ struct RectCell {
     is_connected_to_east: bool,
 }
 
+impl CellBits for RectCell {
+    const BITS_N: u32 = 2;
+
+    fn to_bits(&self) -> u64 {
+        u64::from(self.is_connected_to_north) | u64::from(self.is_connected_to_east) << 1
+    }
+
+    fn from_bits(_pos: &Position2d, bits: u64) -> Self {
+        Self {
+            is_connected_to_north: bits & 1 != 0,
+            is_connected_to_east: bits & 2 != 0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RectGrid<M: MaskType>(GeneralRectGrid<RectCell>, PhantomData<M>);
 
@@ -286,22 +309,41 @@ impl<M: MaskType + Clone> Grid2d for RectGrid<M> {
     }
 
     fn connect_to(&mut self, from: &Position2d, to: &Position2d) -> bool {
-        let rect_from: RectPosition = (*from).into();
-        let rect_to: RectPosition = (*to).into();
-        self.0.is_cell(from)
-            && self.0.is_cell(to)
-            && RectDirection::all_dirs()
-                .iter()
-                .find(|dir| {
-                    rect_from
-                        .neighbor(**dir)
-                        .is_some_and(|neighbor| neighbor == rect_to)
+        self.neighbor_dir(from, to)
+            .is_some_and(|dir| self.connect_to(&(*from).into(), dir))
+    }
+
+    fn disconnect_from(&mut self, from: &Position2d, to: &Position2d) -> bool {
+        self.neighbor_dir(from, to)
+            .is_some_and(|dir| self.disconnect_from(&(*from).into(), dir))
+    }
+
+    fn is_connected_to(&self, from: &Position2d, to: &Position2d) -> bool {
+        self.neighbor_dir(from, to)
+            .is_some_and(|dir| self.is_connected_to(&(*from).into(), dir))
+    }
+
+    fn stray_passages(&self) -> Vec<StrayPassage> {
+        self.0
+            .raw_cell_iter()
+            .flat_map(|(pos, cell)| {
+                let rect_pos = RectPosition::from(pos);
+                [
+                    (cell.is_connected_to_north, RectDirection::North),
+                    (cell.is_connected_to_east, RectDirection::East),
+                ]
+                .into_iter()
+                .filter(|(is_connected, _)| *is_connected)
+                .filter_map(move |(_, dir)| {
+                    self.0
+                        .stray_passage(&pos, rect_pos.neighbor(dir).map(Position2d::from))
                 })
-                .is_some_and(|connect_dir| self.connect_to(&rect_from, *connect_dir))
+            })
+            .collect()
     }
 }
 
-impl LayerGrid for RectGrid<NoMask> {
+impl<M: MaskType + Clone> LayerGrid for RectGrid<M> {
     fn layers_n(&self) -> usize {
         self.0.height
     }
@@ -310,6 +352,10 @@ impl LayerGrid for RectGrid<NoMask> {
         self.0.width
     }
 
+    fn is_cell(&self, pos: &Position2d) -> bool {
+        self.0.is_cell(pos)
+    }
+
     fn append_neighbors_upper_layer(&self, pos: &Position2d, neighbors: &mut Vec<Position2d>) {
         neighbors.extend(
             self.neighbor_pos(&(*pos).into(), RectDirection::North)
@@ -373,24 +419,46 @@ impl<M: MaskType> RectGrid<M> {
     }
 
     pub fn connect_to(&mut self, pos: &RectPosition, dir: RectDirection) -> bool {
-        if let Some(neighbor) = self.neighbor_pos(pos, dir) {
-            if let Some(cell) = self.0.cell_mut(&(*pos).into()) {
-                return match dir {
-                    RectDirection::North => {
-                        cell.is_connected_to_north = true;
-                        true
-                    }
-                    RectDirection::East => {
-                        cell.is_connected_to_east = true;
-                        true
-                    }
-                    other_dir => self.connect_to(&neighbor, other_dir.reverse()),
-                };
-            }
+        self.set_connection(pos, dir, true)
+    }
+
+    pub fn disconnect_from(&mut self, pos: &RectPosition, dir: RectDirection) -> bool {
+        self.set_connection(pos, dir, false)
+    }
+
+    fn set_connection(
+        &mut self,
+        pos: &RectPosition,
+        dir: RectDirection,
+        is_connected: bool,
+    ) -> bool {
+        if let Some(neighbor) = self.neighbor_pos(pos, dir)
+            && let Some(cell) = self.0.cell_mut(&(*pos).into())
+        {
+            return match dir {
+                RectDirection::North => {
+                    cell.is_connected_to_north = is_connected;
+                    true
+                }
+                RectDirection::East => {
+                    cell.is_connected_to_east = is_connected;
+                    true
+                }
+                other_dir => self.set_connection(&neighbor, other_dir.reverse(), is_connected),
+            };
         }
 
         false
     }
+
+    fn neighbor_dir(&self, from: &Position2d, to: &Position2d) -> Option<RectDirection> {
+        let rect_from = (*from).into();
+        let rect_to = (*to).into();
+        RectDirection::all_dirs()
+            .iter()
+            .find(|dir| self.neighbor_pos(&rect_from, **dir) == Some(rect_to))
+            .copied()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -406,6 +474,16 @@ impl Display for RectMaze {
 }
 
 impl RectMaze {
+    /// Read maze drawn in box characters(ascii or unicode), as it's shown or saved in text.
+    pub fn try_from_text_file<P: AsRef<Path>>(path: P) -> Result<Self, AnyError> {
+        Ok(TileMap::try_from_box_text_file(path)?.to_rect_maze()?)
+    }
+
+    /// Read maze from the block bitmap, every cell and wall is a pixel, and the dark opaque pixel is the wall.
+    pub fn try_from_image_file<P: AsRef<Path>>(path: P) -> Result<Self, AnyError> {
+        Ok(TileMap::try_from_image_file(path, &TileSettings::default())?.to_rect_maze()?)
+    }
+
     pub fn size(&self) -> (usize, usize) {
         match self {
             RectMaze::NoMask(rect_grid) => rect_grid.size(),
@@ -426,4 +504,124 @@ impl RectMaze {
             RectMaze::WithMask(rect_grid) => rect_grid.is_connected_to(pos, dir),
         }
     }
+
+    /// The grid under maze, for analyzing its passages.
+    pub fn grid(&self) -> &dyn Grid2d {
+        match self {
+            RectMaze::NoMask(rect_grid) => rect_grid,
+            RectMaze::WithMask(rect_grid) => rect_grid,
+        }
+    }
+
+    /// Mutable access to the grid under maze, for editing its walls.
+    pub fn grid_mut(&mut self) -> &mut dyn Grid2d {
+        match self {
+            RectMaze::NoMask(rect_grid) => rect_grid,
+            RectMaze::WithMask(rect_grid) => rect_grid,
+        }
+    }
+}
+
+impl Maze for RectMaze {
+    type Position = RectPosition;
+    type Direction = RectDirection;
+
+    fn cells_n(&self) -> usize {
+        self.grid().cells_n()
+    }
+
+    fn cells(&self) -> impl Iterator<Item = RectPosition> + '_ {
+        let (width, height) = self.size();
+        (0..height)
+            .flat_map(move |r| (0..width).map(move |c| RectPosition::new(r, c)))
+            .filter(|pos| RectMaze::is_cell(self, pos))
+    }
+
+    fn is_cell(&self, pos: &RectPosition) -> bool {
+        RectMaze::is_cell(self, pos)
+    }
+
+    fn dirs(&self, _pos: &RectPosition) -> &'static [RectDirection] {
+        RectDirection::all_dirs()
+    }
+
+    fn neighbors_at(&self, pos: &RectPosition, dir: RectDirection) -> Vec<RectPosition> {
+        match self {
+            RectMaze::NoMask(rect_grid) => rect_grid.neighbor_pos(pos, dir),
+            RectMaze::WithMask(rect_grid) => rect_grid.neighbor_pos(pos, dir),
+        }
+        .into_iter()
+        .collect()
+    }
+
+    fn is_connected(&self, pos: &RectPosition, other: &RectPosition) -> bool {
+        self.grid()
+            .is_connected_to(&(*pos).into(), &(*other).into())
+    }
+
+    fn is_connected_to(&self, pos: &RectPosition, dir: RectDirection) -> bool {
+        RectMaze::is_connected_to(self, pos, dir)
+    }
+}
+
+impl BinaryMaze for RectMaze {
+    fn write_bits(&self, writer: &mut BitWriter) -> Result<(), file::Error> {
+        match self {
+            RectMaze::NoMask(rect_grid) => rect_grid.0.write_bits(writer)?,
+            RectMaze::WithMask(rect_grid) => rect_grid.0.write_bits(writer)?,
+        }
+
+        Ok(())
+    }
+
+    fn read_bits(reader: &mut BitReader) -> Result<Self, file::Error> {
+        let grid = GeneralRectGrid::<RectCell>::read_bits(reader)?;
+        Ok(if grid.mask.is_some() {
+            RectMaze::WithMask(RectGrid(grid, PhantomData))
+        } else {
+            RectMaze::NoMask(RectGrid(grid, PhantomData))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maze::{NoMask, Position2d};
+
+    use super::{RectDirection, RectGrid, RectMaze, RectPosition};
+
+    #[test]
+    fn test_maze_edit_seen_from_both_cells() {
+        let mut maze = RectMaze::NoMask(RectGrid::<NoMask>::new(3, 3));
+        let center = RectPosition::new(1, 1);
+        // The passage to north is stored in the center cell, the one to south in the cell below.
+        maze.grid_mut()
+            .connect_to(&center.into(), &Position2d(0, 1));
+        maze.grid_mut()
+            .connect_to(&Position2d(2, 1), &center.into());
+        for dir in RectDirection::all_dirs() {
+            let neighbor = center.neighbor(*dir).unwrap();
+            let is_connected = matches!(dir, RectDirection::North | RectDirection::South);
+            assert_eq!(maze.is_connected_to(&center, *dir), is_connected);
+            assert_eq!(maze.is_connected_to(&neighbor, dir.reverse()), is_connected);
+        }
+
+        assert!(
+            maze.grid_mut()
+                .toggle_connection(&Position2d(1, 2), &center.into())
+        );
+        assert!(maze.is_connected_to(&center, RectDirection::East));
+        assert!(
+            maze.grid_mut()
+                .disconnect_from(&Position2d(0, 1), &center.into())
+        );
+        assert!(!maze.is_connected_to(&center, RectDirection::North));
+
+        maze.grid_mut().set_all_walls();
+        assert!(
+            RectDirection::all_dirs()
+                .iter()
+                .all(|dir| !maze.is_connected_to(&center, *dir))
+        );
+    }
 }
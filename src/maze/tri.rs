@@ -1,6 +1,13 @@
 use serde::{Deserialize, Serialize};
 
-use super::{DefaultInRectGrid, GeneralRectGrid, Grid2d, Position2d, rect::RectMask};
+use crate::file::{self, binary::BinaryMaze};
+
+use super::{
+    CellBits, DefaultInRectGrid, GeneralRectGrid, Grid2d, LayerGrid, Maze, Position2d,
+    StrayPassage,
+    bits::{BitReader, BitWriter},
+    rect::RectMask,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum TriDirection {
@@ -88,7 +95,7 @@ enum TriCell {
 
 impl DefaultInRectGrid for TriCell {
     fn default_at(pos: &Position2d) -> Self {
-        if (pos.0 + pos.1) % 2 == 0 {
+        if (pos.0 + pos.1).is_multiple_of(2) {
             Self::AngelUp {
                 is_connected_to_northwest: false,
                 is_connected_to_south: false,
@@ -101,6 +108,34 @@ impl DefaultInRectGrid for TriCell {
     }
 }
 
+impl CellBits for TriCell {
+    const BITS_N: u32 = 2;
+
+    fn to_bits(&self) -> u64 {
+        match self {
+            TriCell::AngelUp {
+                is_connected_to_northwest,
+                is_connected_to_south,
+            } => u64::from(*is_connected_to_northwest) | u64::from(*is_connected_to_south) << 1,
+            TriCell::AngelDown {
+                is_connected_to_southwest,
+            } => u64::from(*is_connected_to_southwest),
+        }
+    }
+
+    fn from_bits(pos: &Position2d, bits: u64) -> Self {
+        match Self::default_at(pos) {
+            TriCell::AngelUp { .. } => TriCell::AngelUp {
+                is_connected_to_northwest: bits & 1 != 0,
+                is_connected_to_south: bits & 2 != 0,
+            },
+            TriCell::AngelDown { .. } => TriCell::AngelDown {
+                is_connected_to_southwest: bits & 1 != 0,
+            },
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TriGrid(GeneralRectGrid<TriCell>);
 
@@ -130,7 +165,140 @@ impl Grid2d for TriGrid {
         }));
     }
 
-    fn connect_to(&mut self, from: &super::Position2d, to: &super::Position2d) -> bool {
+    fn connect_to(&mut self, from: &Position2d, to: &Position2d) -> bool {
+        self.set_connection(from, to, true)
+    }
+
+    fn disconnect_from(&mut self, from: &Position2d, to: &Position2d) -> bool {
+        self.set_connection(from, to, false)
+    }
+
+    fn is_connected_to(&self, from: &Position2d, to: &Position2d) -> bool {
+        let tri_from = (*from).into();
+        let tri_to = (*to).into();
+        TriDirection::angle_up_all_dirs()
+            .iter()
+            .chain(TriDirection::angle_down_all_dirs())
+            .find(|dir| self.neighbor_pos(&tri_from, **dir) == Some(tri_to))
+            .is_some_and(|dir| self.is_connected_to(&tri_from, *dir))
+    }
+
+    fn stray_passages(&self) -> Vec<StrayPassage> {
+        self.0
+            .raw_cell_iter()
+            .flat_map(|(pos, cell)| {
+                let tri_pos = TriPosition::from(pos);
+                match cell {
+                    TriCell::AngelUp {
+                        is_connected_to_northwest,
+                        is_connected_to_south,
+                    } => vec![
+                        (*is_connected_to_northwest, TriDirection::Northwest),
+                        (*is_connected_to_south, TriDirection::South),
+                    ],
+                    TriCell::AngelDown {
+                        is_connected_to_southwest,
+                    } => vec![(*is_connected_to_southwest, TriDirection::SouthWest)],
+                }
+                .into_iter()
+                .filter(|(is_connected, _)| *is_connected)
+                .filter_map(move |(_, dir)| {
+                    self.0
+                        .stray_passage(&pos, tri_pos.neighbor(dir).map(Position2d::from))
+                })
+            })
+            .collect()
+    }
+}
+
+impl LayerGrid for TriGrid {
+    fn layers_n(&self) -> usize {
+        self.0.height
+    }
+
+    fn cells_n_at(&self, _layer_ind: usize) -> usize {
+        self.0.width
+    }
+
+    fn is_cell(&self, pos: &Position2d) -> bool {
+        self.0.is_cell(pos)
+    }
+
+    fn append_neighbors_upper_layer(&self, pos: &Position2d, neighbors: &mut Vec<Position2d>) {
+        // Only the angle down cell has the neighbor in the upper layer.
+        neighbors.extend(
+            self.neighbor_pos(&(*pos).into(), TriDirection::North)
+                .map(Position2d::from),
+        );
+    }
+
+    fn append_neighbors_lower_layer(&self, pos: &Position2d, neighbors: &mut Vec<Position2d>) {
+        // Only the angle up cell has the neighbor in the lower layer.
+        neighbors.extend(
+            self.neighbor_pos(&(*pos).into(), TriDirection::South)
+                .map(Position2d::from),
+        );
+    }
+}
+
+impl TriGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self(GeneralRectGrid::new(width, height))
+    }
+
+    pub fn with_mask(mask: &RectMask) -> Self {
+        Self(GeneralRectGrid::with_mask(mask))
+    }
+
+    pub fn neighbor_pos(&self, pos: &TriPosition, dir: TriDirection) -> Option<TriPosition> {
+        self.0.cell(&(*pos).into()).and_then(|cell| {
+            match cell {
+                TriCell::AngelUp { .. } => TriDirection::angle_up_all_dirs(),
+                TriCell::AngelDown { .. } => TriDirection::angle_down_all_dirs(),
+            }
+            .iter()
+            .find(|neighbor_dir| **neighbor_dir == dir)
+            .and_then(|dir| {
+                pos.neighbor(*dir)
+                    .filter(|neighbor| self.0.is_cell(&(*neighbor).into()))
+            })
+        })
+    }
+
+    pub fn is_connected_to(&self, tri_pos: &TriPosition, dir: TriDirection) -> bool {
+        let pos = (*tri_pos).into();
+        self.0.cell(&pos).is_some_and(|cell| match cell {
+            TriCell::AngelUp {
+                is_connected_to_northwest,
+                is_connected_to_south,
+            } => match dir {
+                TriDirection::Northwest => *is_connected_to_northwest,
+                TriDirection::South => *is_connected_to_south,
+                TriDirection::Northeast => self
+                    .neighbor_pos(tri_pos, TriDirection::Northeast)
+                    .is_some_and(|neighbor| {
+                        self.is_connected_to(&neighbor, TriDirection::SouthWest)
+                    }),
+                _ => false,
+            },
+            TriCell::AngelDown {
+                is_connected_to_southwest,
+            } => match dir {
+                TriDirection::SouthWest => *is_connected_to_southwest,
+                TriDirection::North => self
+                    .neighbor_pos(tri_pos, TriDirection::North)
+                    .is_some_and(|neighbor| self.is_connected_to(&neighbor, TriDirection::South)),
+                TriDirection::Southeast => self
+                    .neighbor_pos(tri_pos, TriDirection::Southeast)
+                    .is_some_and(|neighbor| {
+                        self.is_connected_to(&neighbor, TriDirection::Northwest)
+                    }),
+                _ => false,
+            },
+        })
+    }
+
+    fn set_connection(&mut self, from: &Position2d, to: &Position2d, is_connected: bool) -> bool {
         let tri_from = (*from).into();
         let tri_to = (*to).into();
         let Some(dir) = self.0.cell(from).and_then(|cell| {
@@ -152,40 +320,40 @@ impl Grid2d for TriGrid {
                 TriCell::AngelUp {
                     is_connected_to_northwest,
                     ..
-                } => *is_connected_to_northwest = true,
+                } => *is_connected_to_northwest = is_connected,
                 TriCell::AngelDown { .. } => unreachable!(),
             },
             TriDirection::Northeast => match self.0.cell_mut(to).unwrap() {
                 TriCell::AngelUp { .. } => unreachable!(),
                 TriCell::AngelDown {
                     is_connected_to_southwest,
-                } => *is_connected_to_southwest = true,
+                } => *is_connected_to_southwest = is_connected,
             },
             TriDirection::South => match self.0.cell_mut(from).unwrap() {
                 TriCell::AngelUp {
                     is_connected_to_south,
                     ..
-                } => *is_connected_to_south = true,
+                } => *is_connected_to_south = is_connected,
                 TriCell::AngelDown { .. } => unreachable!(),
             },
             TriDirection::SouthWest => match self.0.cell_mut(from).unwrap() {
                 TriCell::AngelUp { .. } => unreachable!(),
                 TriCell::AngelDown {
                     is_connected_to_southwest,
-                } => *is_connected_to_southwest = true,
+                } => *is_connected_to_southwest = is_connected,
             },
             TriDirection::North => match self.0.cell_mut(to).unwrap() {
                 TriCell::AngelUp {
                     is_connected_to_south,
                     ..
-                } => *is_connected_to_south = true,
+                } => *is_connected_to_south = is_connected,
                 TriCell::AngelDown { .. } => unreachable!(),
             },
             TriDirection::Southeast => match self.0.cell_mut(to).unwrap() {
                 TriCell::AngelUp {
                     is_connected_to_northwest,
                     ..
-                } => *is_connected_to_northwest = true,
+                } => *is_connected_to_northwest = is_connected,
                 TriCell::AngelDown { .. } => unreachable!(),
             },
         }
@@ -194,31 +362,6 @@ impl Grid2d for TriGrid {
     }
 }
 
-impl TriGrid {
-    pub fn new(width: usize, height: usize) -> Self {
-        Self(GeneralRectGrid::new(width, height))
-    }
-
-    pub fn with_mask(mask: &RectMask) -> Self {
-        Self(GeneralRectGrid::with_mask(mask))
-    }
-
-    pub fn neighbor_pos(&self, pos: &TriPosition, dir: TriDirection) -> Option<TriPosition> {
-        self.0.cell(&(*pos).into()).and_then(|cell| {
-            match cell {
-                TriCell::AngelUp { .. } => TriDirection::angle_up_all_dirs(),
-                TriCell::AngelDown { .. } => TriDirection::angle_down_all_dirs(),
-            }
-            .iter()
-            .find(|neighbor_dir| **neighbor_dir == dir)
-            .and_then(|dir| {
-                pos.neighbor(*dir)
-                    .filter(|neighbor| self.0.is_cell(&(*neighbor).into()))
-            })
-        })
-    }
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TriMaze(TriGrid);
 
@@ -236,39 +379,17 @@ impl TriMaze {
     }
 
     pub fn is_connected_to(&self, tri_pos: &TriPosition, dir: TriDirection) -> bool {
-        let pos = (*tri_pos).into();
-        self.0.0.cell(&pos).is_some_and(|cell| match cell {
-            TriCell::AngelUp {
-                is_connected_to_northwest,
-                is_connected_to_south,
-            } => match dir {
-                TriDirection::Northwest => *is_connected_to_northwest,
-                TriDirection::South => *is_connected_to_south,
-                TriDirection::Northeast => self
-                    .0
-                    .neighbor_pos(tri_pos, TriDirection::Northeast)
-                    .is_some_and(|neighbor| {
-                        self.is_connected_to(&neighbor, TriDirection::SouthWest)
-                    }),
-                _ => false,
-            },
-            TriCell::AngelDown {
-                is_connected_to_southwest,
-            } => match dir {
-                TriDirection::SouthWest => *is_connected_to_southwest,
-                TriDirection::North => self
-                    .0
-                    .neighbor_pos(tri_pos, TriDirection::North)
-                    .is_some_and(|neighbor| self.is_connected_to(&neighbor, TriDirection::South)),
-                TriDirection::Southeast => self
-                    .0
-                    .neighbor_pos(tri_pos, TriDirection::Southeast)
-                    .is_some_and(|neighbor| {
-                        self.is_connected_to(&neighbor, TriDirection::Northwest)
-                    }),
-                _ => false,
-            },
-        })
+        self.0.is_connected_to(tri_pos, dir)
+    }
+
+    /// The grid under maze, for analyzing its passages.
+    pub fn grid(&self) -> &dyn Grid2d {
+        &self.0
+    }
+
+    /// Mutable access to the grid under maze, for editing its walls.
+    pub fn grid_mut(&mut self) -> &mut dyn Grid2d {
+        &mut self.0
     }
 
     pub fn is_angle_up(&self, pos: &TriPosition) -> bool {
@@ -278,3 +399,54 @@ impl TriMaze {
         }
     }
 }
+
+impl Maze for TriMaze {
+    type Position = TriPosition;
+    type Direction = TriDirection;
+
+    fn cells_n(&self) -> usize {
+        self.0.cells_n()
+    }
+
+    fn cells(&self) -> impl Iterator<Item = TriPosition> + '_ {
+        let (width, height) = self.size();
+        (0..height)
+            .flat_map(move |r| (0..width).map(move |c| TriPosition::new(r, c)))
+            .filter(|pos| TriMaze::is_cell(self, pos))
+    }
+
+    fn is_cell(&self, pos: &TriPosition) -> bool {
+        TriMaze::is_cell(self, pos)
+    }
+
+    fn dirs(&self, pos: &TriPosition) -> &'static [TriDirection] {
+        if self.is_angle_up(pos) {
+            TriDirection::angle_up_all_dirs()
+        } else {
+            TriDirection::angle_down_all_dirs()
+        }
+    }
+
+    fn neighbors_at(&self, pos: &TriPosition, dir: TriDirection) -> Vec<TriPosition> {
+        self.0.neighbor_pos(pos, dir).into_iter().collect()
+    }
+
+    fn is_connected(&self, pos: &TriPosition, other: &TriPosition) -> bool {
+        self.grid()
+            .is_connected_to(&(*pos).into(), &(*other).into())
+    }
+
+    fn is_connected_to(&self, pos: &TriPosition, dir: TriDirection) -> bool {
+        TriMaze::is_connected_to(self, pos, dir)
+    }
+}
+
+impl BinaryMaze for TriMaze {
+    fn write_bits(&self, writer: &mut BitWriter) -> Result<(), file::Error> {
+        Ok(self.0.0.write_bits(writer)?)
+    }
+
+    fn read_bits(reader: &mut BitReader) -> Result<Self, file::Error> {
+        Ok(TriMaze(TriGrid(GeneralRectGrid::read_bits(reader)?)))
+    }
+}
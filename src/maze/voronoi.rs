@@ -0,0 +1,682 @@
+use std::{
+    collections::{HashSet, LinkedList},
+    iter,
+};
+
+use rand::{Rng, seq::IteratorRandom};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    file::{self, binary::BinaryMaze},
+    rng,
+};
+
+use super::{
+    Grid2d, Position2d, StrayPassage,
+    bits::{BitReader, BitWriter},
+    rect::{RectMask, RectPosition},
+};
+
+// Number of candidates tried around an active point before it's retired in the Poisson disk sampling.
+const POISSON_DISK_CANDIDATES_N: usize = 30;
+// Voronoi edges shorter than this ratio of the minimum site distance are treated as a single vertex.
+const MIN_EDGE_LENGTH_RATIO: f64 = 1e-4;
+
+/// The area where sites of the Voronoi grid are scattered.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VoronoiShape {
+    /// A rectangle with given width and height.
+    Rect { width: f32, height: f32 },
+    /// A circle with given radius.
+    Circle { radius: f32 },
+    /// A rectangular mask, every cell(or pixel) of the mask is a square of unit length.
+    Mask(RectMask),
+}
+
+impl VoronoiShape {
+    pub fn size(&self) -> (f32, f32) {
+        match self {
+            VoronoiShape::Rect { width, height } => (*width, *height),
+            VoronoiShape::Circle { radius } => (radius * 2.0, radius * 2.0),
+            VoronoiShape::Mask(mask) => {
+                let (width, height) = mask.size();
+                (width as f32, height as f32)
+            }
+        }
+    }
+
+    fn contains(&self, point: (f64, f64)) -> bool {
+        match self {
+            VoronoiShape::Rect { .. } => true,
+            VoronoiShape::Circle { radius } => {
+                let radius = f64::from(*radius);
+                let (dx, dy) = (point.0 - radius, point.1 - radius);
+                dx * dx + dy * dy <= radius * radius
+            }
+            VoronoiShape::Mask(mask) => {
+                mask.is_cell(&RectPosition::new(point.1 as usize, point.0 as usize))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VoronoiPosition {
+    pub site: usize,
+}
+
+impl From<Position2d> for VoronoiPosition {
+    fn from(value: Position2d) -> Self {
+        debug_assert!(value.0 == 0);
+        Self::new(value.1)
+    }
+}
+
+impl From<VoronoiPosition> for Position2d {
+    fn from(value: VoronoiPosition) -> Self {
+        // All sites are in one layer.
+        Position2d(0, value.site)
+    }
+}
+
+impl VoronoiPosition {
+    pub fn new(site: usize) -> Self {
+        Self { site }
+    }
+}
+
+/// An edge of a Voronoi cell, from the start vertex to the end vertex in clockwise order(in the y-down coordinates).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VoronoiEdge {
+    pub start: (f32, f32),
+    pub end: (f32, f32),
+    /// The site on the other side of this edge, none for the border of the whole grid.
+    pub neighbor: Option<VoronoiPosition>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct VoronoiCell {
+    #[serde(rename = "s")]
+    site: (f32, f32),
+    #[serde(rename = "m")]
+    is_cell: bool,
+    #[serde(rename = "v")]
+    vertices: Vec<(f32, f32)>,
+    // The neighbor across the edge from vertices[i] to vertices[i + 1].
+    #[serde(rename = "n")]
+    edge_neighbors: Vec<Option<usize>>,
+    // The connected neighbors with greater index, so every passage is only stored in its lower cell.
+    #[serde(rename = "l")]
+    links: Vec<usize>,
+}
+
+/// The grid made of Voronoi cells around sites scattered randomly(Poisson disk) in the given shape.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoronoiGrid {
+    width: f32,
+    height: f32,
+    cells: Vec<VoronoiCell>,
+}
+
+impl Grid2d for VoronoiGrid {
+    fn cells_n(&self) -> usize {
+        self.cells.iter().filter(|cell| cell.is_cell).count()
+    }
+
+    fn random_cell_pos(&self) -> Option<Position2d> {
+        let mut rng = rng::rng();
+        (0..self.cells.len())
+            .filter(|ind| self.cells[*ind].is_cell)
+            .choose(&mut rng)
+            .map(|ind| VoronoiPosition::new(ind).into())
+    }
+
+    fn all_cells_pos_set(&self) -> HashSet<Position2d> {
+        (0..self.cells.len())
+            .filter(|ind| self.cells[*ind].is_cell)
+            .map(|ind| VoronoiPosition::new(ind).into())
+            .collect()
+    }
+
+    fn append_neighbors(&self, pos: &Position2d, neighbors: &mut Vec<Position2d>) {
+        neighbors.extend(self.neighbor_pos_iter(&(*pos).into()).map(Position2d::from));
+    }
+
+    fn connect_to(&mut self, from: &Position2d, to: &Position2d) -> bool {
+        let from: VoronoiPosition = (*from).into();
+        let to: VoronoiPosition = (*to).into();
+        if !self.neighbor_pos_iter(&from).any(|neighbor| neighbor == to) {
+            return false;
+        }
+
+        let (low, high) = (from.site.min(to.site), from.site.max(to.site));
+        let links = &mut self.cells[low].links;
+        if !links.contains(&high) {
+            links.push(high);
+        }
+
+        true
+    }
+
+    fn disconnect_from(&mut self, from: &Position2d, to: &Position2d) -> bool {
+        let from: VoronoiPosition = (*from).into();
+        let to: VoronoiPosition = (*to).into();
+        if !self.neighbor_pos_iter(&from).any(|neighbor| neighbor == to) {
+            return false;
+        }
+
+        let (low, high) = (from.site.min(to.site), from.site.max(to.site));
+        self.cells[low].links.retain(|link| *link != high);
+        true
+    }
+
+    fn is_connected_to(&self, from: &Position2d, to: &Position2d) -> bool {
+        self.is_connected_to(&(*from).into(), &(*to).into())
+    }
+
+    fn stray_passages(&self) -> Vec<StrayPassage> {
+        let pos_of = |site: usize| VoronoiPosition::new(site).into();
+        self.cells
+            .iter()
+            .enumerate()
+            .flat_map(|(site, cell)| {
+                cell.links
+                    .iter()
+                    .filter(move |link| {
+                        !cell.is_cell
+                            || !self.is_cell(&VoronoiPosition::new(**link))
+                            || !cell.edge_neighbors.contains(&Some(**link))
+                    })
+                    .map(move |link| StrayPassage {
+                        from: pos_of(site),
+                        to: (*link < self.cells.len()).then(|| pos_of(*link)),
+                    })
+            })
+            .collect()
+    }
+}
+
+impl VoronoiGrid {
+    /// Scatter sites in given shape, the distance between any two sites isn't less than `min_distance`.
+    pub fn new(shape: &VoronoiShape, min_distance: f32) -> Self {
+        let (width, height) = shape.size();
+        let min_distance = f64::from(min_distance);
+        let sites = poisson_disk_sample(f64::from(width), f64::from(height), min_distance);
+        let site_buckets = SiteBuckets::new(&sites, min_distance);
+        let mut cells: Vec<_> = sites
+            .iter()
+            .enumerate()
+            .map(|(ind, site)| {
+                let (vertices, edge_neighbors) = voronoi_polygon(
+                    ind,
+                    &sites,
+                    &site_buckets,
+                    f64::from(width),
+                    f64::from(height),
+                    min_distance,
+                );
+                VoronoiCell {
+                    site: (site.0 as f32, site.1 as f32),
+                    is_cell: shape.contains(*site),
+                    vertices,
+                    edge_neighbors,
+                    links: Vec::new(),
+                }
+            })
+            .collect();
+
+        // Only keep the neighbor relations agreed by both sides, it may be broken by round-off error of tiny edges.
+        let neighbor_pairs: HashSet<_> = cells
+            .iter()
+            .enumerate()
+            .flat_map(|(ind, cell)| {
+                cell.edge_neighbors
+                    .iter()
+                    .flatten()
+                    .map(move |neighbor| (ind, *neighbor))
+            })
+            .collect();
+        for (ind, cell) in cells.iter_mut().enumerate() {
+            for neighbor in cell.edge_neighbors.iter_mut() {
+                if neighbor.is_some_and(|neighbor| !neighbor_pairs.contains(&(neighbor, ind))) {
+                    *neighbor = None;
+                }
+            }
+        }
+
+        let mut grid = Self {
+            width,
+            height,
+            cells,
+        };
+        grid.keep_largest_area();
+        grid
+    }
+
+    pub fn size(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    pub fn sites_n(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_cell(&self, pos: &VoronoiPosition) -> bool {
+        self.cells.get(pos.site).is_some_and(|cell| cell.is_cell)
+    }
+
+    pub fn site(&self, pos: &VoronoiPosition) -> Option<(f32, f32)> {
+        self.cells.get(pos.site).map(|cell| cell.site)
+    }
+
+    pub fn neighbor_pos_iter(
+        &self,
+        pos: &VoronoiPosition,
+    ) -> impl Iterator<Item = VoronoiPosition> + '_ {
+        self.cells
+            .get(pos.site)
+            .filter(|cell| cell.is_cell)
+            .into_iter()
+            .flat_map(|cell| cell.edge_neighbors.iter().flatten())
+            .map(|neighbor| VoronoiPosition::new(*neighbor))
+            .filter(|neighbor| self.is_cell(neighbor))
+    }
+
+    pub fn is_connected_to(&self, pos: &VoronoiPosition, neighbor: &VoronoiPosition) -> bool {
+        let (low, high) = (pos.site.min(neighbor.site), pos.site.max(neighbor.site));
+        self.is_cell(pos) && self.is_cell(neighbor) && self.cells[low].links.contains(&high)
+    }
+
+    // Make positions outside the largest connected area not cell, so that every cell in the grid is reachable.
+    fn keep_largest_area(&mut self) {
+        let mut area_ids = vec![None; self.cells.len()];
+        let mut largest_area = None;
+        let mut largest_area_cells_n = 0;
+        for start_ind in 0..self.cells.len() {
+            if !self.cells[start_ind].is_cell || area_ids[start_ind].is_some() {
+                continue;
+            }
+
+            let mut area_cells_n = 0;
+            let mut visit_list = LinkedList::from_iter(iter::once(start_ind));
+            area_ids[start_ind] = Some(start_ind);
+            while let Some(cur_ind) = visit_list.pop_front() {
+                area_cells_n += 1;
+                for neighbor in self.neighbor_pos_iter(&VoronoiPosition::new(cur_ind)) {
+                    if area_ids[neighbor.site].is_none() {
+                        area_ids[neighbor.site] = Some(start_ind);
+                        visit_list.push_back(neighbor.site);
+                    }
+                }
+            }
+
+            if area_cells_n > largest_area_cells_n {
+                largest_area_cells_n = area_cells_n;
+                largest_area = Some(start_ind);
+            }
+        }
+
+        for (cell, area_id) in self.cells.iter_mut().zip(area_ids) {
+            cell.is_cell = cell.is_cell && area_id == largest_area;
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VoronoiMaze(VoronoiGrid);
+
+impl VoronoiMaze {
+    pub fn new(grid: VoronoiGrid) -> Self {
+        Self(grid)
+    }
+
+    pub fn size(&self) -> (f32, f32) {
+        self.0.size()
+    }
+
+    pub fn sites_n(&self) -> usize {
+        self.0.sites_n()
+    }
+
+    pub fn is_cell(&self, pos: &VoronoiPosition) -> bool {
+        self.0.is_cell(pos)
+    }
+
+    pub fn site(&self, pos: &VoronoiPosition) -> Option<(f32, f32)> {
+        self.0.site(pos)
+    }
+
+    pub fn edge_iter(&self, pos: &VoronoiPosition) -> impl Iterator<Item = VoronoiEdge> + '_ {
+        self.0.cells.get(pos.site).into_iter().flat_map(|cell| {
+            let vertices_n = cell.vertices.len();
+            (0..vertices_n).map(move |ind| VoronoiEdge {
+                start: cell.vertices[ind],
+                end: cell.vertices[(ind + 1) % vertices_n],
+                neighbor: cell.edge_neighbors[ind].map(VoronoiPosition::new),
+            })
+        })
+    }
+
+    pub fn is_connected_to(&self, pos: &VoronoiPosition, neighbor: &VoronoiPosition) -> bool {
+        self.0.is_connected_to(pos, neighbor)
+    }
+
+    /// The grid under maze, for analyzing its passages.
+    pub fn grid(&self) -> &dyn Grid2d {
+        &self.0
+    }
+
+    /// Mutable access to the grid under maze, for editing its walls.
+    pub fn grid_mut(&mut self) -> &mut dyn Grid2d {
+        &mut self.0
+    }
+}
+
+// Written for the edge without neighbor in the binary maze file.
+const NO_NEIGHBOR_IND: u32 = u32::MAX;
+
+impl BinaryMaze for VoronoiMaze {
+    fn write_bits(&self, writer: &mut BitWriter) -> Result<(), file::Error> {
+        let grid = &self.0;
+        writer.write_f32(grid.width);
+        writer.write_f32(grid.height);
+        writer.write_usize(grid.cells.len())?;
+        for cell in &grid.cells {
+            writer.write_f32(cell.site.0);
+            writer.write_f32(cell.site.1);
+            writer.write_bool(cell.is_cell);
+            writer.write_usize(cell.vertices.len())?;
+            for (vertex, neighbor) in cell.vertices.iter().zip(&cell.edge_neighbors) {
+                writer.write_f32(vertex.0);
+                writer.write_f32(vertex.1);
+                match neighbor {
+                    Some(neighbor) => writer.write_usize(*neighbor)?,
+                    None => writer.write_u32(NO_NEIGHBOR_IND),
+                }
+            }
+            writer.write_usize(cell.links.len())?;
+            for link in &cell.links {
+                writer.write_usize(*link)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_bits(reader: &mut BitReader) -> Result<Self, file::Error> {
+        let width = reader.read_f32()?;
+        let height = reader.read_f32()?;
+        // Every cell has at least its site, the mask bit and the counts of vertices and links.
+        let cells_n = reader.read_count(32 * 4 + 1)?;
+        let mut cells = Vec::with_capacity(cells_n);
+        for _ in 0..cells_n {
+            let site = (reader.read_f32()?, reader.read_f32()?);
+            let is_cell = reader.read_bool()?;
+            let vertices_n = reader.read_count(32 * 3)?;
+            let mut vertices = Vec::with_capacity(vertices_n);
+            let mut edge_neighbors = Vec::with_capacity(vertices_n);
+            for _ in 0..vertices_n {
+                vertices.push((reader.read_f32()?, reader.read_f32()?));
+                let neighbor = reader.read_u32()?;
+                edge_neighbors.push((neighbor != NO_NEIGHBOR_IND).then_some(neighbor as usize));
+            }
+            let links_n = reader.read_count(32)?;
+            let links = (0..links_n)
+                .map(|_| reader.read_usize())
+                .collect::<Result<_, _>>()?;
+            cells.push(VoronoiCell {
+                site,
+                is_cell,
+                vertices,
+                edge_neighbors,
+                links,
+            });
+        }
+
+        Ok(Self(VoronoiGrid {
+            width,
+            height,
+            cells,
+        }))
+    }
+}
+
+/// Buckets to find sites near some point quickly.
+struct SiteBuckets {
+    bucket_width: f64,
+    cols_n: usize,
+    rows_n: usize,
+    buckets: Vec<Vec<usize>>,
+}
+
+impl SiteBuckets {
+    fn new(sites: &[(f64, f64)], bucket_width: f64) -> Self {
+        let (max_x, max_y) = sites.iter().fold((0f64, 0f64), |(max_x, max_y), site| {
+            (max_x.max(site.0), max_y.max(site.1))
+        });
+        let cols_n = (max_x / bucket_width) as usize + 1;
+        let rows_n = (max_y / bucket_width) as usize + 1;
+        let mut buckets = vec![Vec::new(); cols_n * rows_n];
+        for (ind, site) in sites.iter().enumerate() {
+            let col = (site.0 / bucket_width) as usize;
+            let row = (site.1 / bucket_width) as usize;
+            buckets[row * cols_n + col].push(ind);
+        }
+
+        Self {
+            bucket_width,
+            cols_n,
+            rows_n,
+            buckets,
+        }
+    }
+
+    fn near_sites(&self, center: (f64, f64), radius: f64) -> impl Iterator<Item = usize> + '_ {
+        let col_start = ((center.0 - radius) / self.bucket_width).max(0.0) as usize;
+        let col_end = (((center.0 + radius) / self.bucket_width) as usize + 1).min(self.cols_n);
+        let row_start = ((center.1 - radius) / self.bucket_width).max(0.0) as usize;
+        let row_end = (((center.1 + radius) / self.bucket_width) as usize + 1).min(self.rows_n);
+        (row_start..row_end)
+            .flat_map(move |row| (col_start..col_end).map(move |col| row * self.cols_n + col))
+            .flat_map(|bucket_ind| self.buckets[bucket_ind].iter().copied())
+    }
+}
+
+/// Scatter points in the rectangle by Bridson's Poisson disk sampling.
+fn poisson_disk_sample(width: f64, height: f64, min_distance: f64) -> Vec<(f64, f64)> {
+    if width <= 0.0 || height <= 0.0 || min_distance <= 0.0 {
+        return Vec::new();
+    }
+
+    let mut rng = rng::rng();
+    // Every bucket contains at most one point, since its diagonal is the minimum distance.
+    let bucket_width = min_distance / 2f64.sqrt();
+    let cols_n = (width / bucket_width).ceil() as usize;
+    let rows_n = (height / bucket_width).ceil() as usize;
+    let bucket_ind = |point: (f64, f64)| {
+        let col = ((point.0 / bucket_width) as usize).min(cols_n - 1);
+        let row = ((point.1 / bucket_width) as usize).min(rows_n - 1);
+        (row, col)
+    };
+    let mut buckets: Vec<Option<usize>> = vec![None; cols_n * rows_n];
+    let mut points = Vec::new();
+    let first_point = (rng.random_range(0.0..width), rng.random_range(0.0..height));
+    let (row, col) = bucket_ind(first_point);
+    buckets[row * cols_n + col] = Some(0);
+    points.push(first_point);
+    let mut active_inds = vec![0];
+    while !active_inds.is_empty() {
+        let active_ind = rng.random_range(0..active_inds.len());
+        let center = points[active_inds[active_ind]];
+        let candidate = (0..POISSON_DISK_CANDIDATES_N)
+            .map(|_| {
+                // Pick a point in the annulus between the minimum distance and its double.
+                let angle = rng.random_range(0.0..std::f64::consts::TAU);
+                let distance = rng.random_range(min_distance..min_distance * 2.0);
+                (
+                    center.0 + distance * angle.cos(),
+                    center.1 + distance * angle.sin(),
+                )
+            })
+            .find(|candidate| {
+                if !(0.0..width).contains(&candidate.0) || !(0.0..height).contains(&candidate.1) {
+                    return false;
+                }
+
+                let (row, col) = bucket_ind(*candidate);
+                (row.saturating_sub(2)..(row + 3).min(rows_n))
+                    .flat_map(|r| {
+                        (col.saturating_sub(2)..(col + 3).min(cols_n)).map(move |c| (r, c))
+                    })
+                    .filter_map(|(r, c)| buckets[r * cols_n + c])
+                    .all(|point_ind| {
+                        let point = points[point_ind];
+                        let (dx, dy) = (point.0 - candidate.0, point.1 - candidate.1);
+                        dx * dx + dy * dy >= min_distance * min_distance
+                    })
+            });
+        if let Some(candidate) = candidate {
+            let (row, col) = bucket_ind(candidate);
+            buckets[row * cols_n + col] = Some(points.len());
+            active_inds.push(points.len());
+            points.push(candidate);
+        } else {
+            active_inds.swap_remove(active_ind);
+        }
+    }
+
+    points
+}
+
+/// Calculate the Voronoi cell of the given site, clipped by the bounding rectangle.
+/// Return the vertices of the cell and the neighbor across each edge.
+fn voronoi_polygon(
+    site_ind: usize,
+    sites: &[(f64, f64)],
+    site_buckets: &SiteBuckets,
+    width: f64,
+    height: f64,
+    min_distance: f64,
+) -> (Vec<(f32, f32)>, Vec<Option<usize>>) {
+    let site = sites[site_ind];
+    // Poisson disk sampling leaves no hole larger than twice of the minimum distance,
+    // so the cell and its neighbors are usually in this radius, and it will be enlarged if not.
+    let mut search_radius = min_distance * 4.0;
+    loop {
+        let mut polygon = vec![
+            ((0.0, 0.0), None),
+            ((width, 0.0), None),
+            ((width, height), None),
+            ((0.0, height), None),
+        ];
+        for other_ind in site_buckets.near_sites(site, search_radius) {
+            if other_ind != site_ind {
+                polygon = clip_by_bisector(&polygon, site, sites[other_ind], other_ind);
+            }
+        }
+
+        let cell_radius = polygon
+            .iter()
+            .map(|((x, y), _)| ((x - site.0).powi(2) + (y - site.1).powi(2)).sqrt())
+            .fold(0f64, f64::max);
+        // Any site which can cut the cell is within the double of the cell radius.
+        if cell_radius * 2.0 <= search_radius || search_radius > width.max(height) * 2.0 {
+            let min_edge_length = min_distance * MIN_EDGE_LENGTH_RATIO;
+            let vertices_n = polygon.len();
+            let polygon: Vec<_> = (0..vertices_n)
+                .filter(|ind| {
+                    let (start, _) = polygon[*ind];
+                    let (end, _) = polygon[(ind + 1) % vertices_n];
+                    ((end.0 - start.0).powi(2) + (end.1 - start.1).powi(2)).sqrt()
+                        >= min_edge_length
+                })
+                .map(|ind| polygon[ind])
+                .collect();
+            return polygon
+                .into_iter()
+                .map(|((x, y), neighbor)| ((x as f32, y as f32), neighbor))
+                .unzip();
+        }
+
+        search_radius *= 2.0;
+    }
+}
+
+/// Clip the convex polygon by the bisector between the site and the other site, keep the part near the site.
+/// Each vertex is paired with the label of the edge starting from it.
+fn clip_by_bisector(
+    polygon: &[((f64, f64), Option<usize>)],
+    site: (f64, f64),
+    other_site: (f64, f64),
+    other_ind: usize,
+) -> Vec<((f64, f64), Option<usize>)> {
+    let normal = (other_site.0 - site.0, other_site.1 - site.1);
+    let middle = ((other_site.0 + site.0) / 2.0, (other_site.1 + site.1) / 2.0);
+    // Positive if the point is nearer to the other site.
+    let side =
+        |point: (f64, f64)| (point.0 - middle.0) * normal.0 + (point.1 - middle.1) * normal.1;
+    let mut clipped = Vec::with_capacity(polygon.len() + 1);
+    for (ind, (start, label)) in polygon.iter().copied().enumerate() {
+        let (end, _) = polygon[(ind + 1) % polygon.len()];
+        let start_side = side(start);
+        let end_side = side(end);
+        let intersection = || {
+            let t = start_side / (start_side - end_side);
+            (
+                start.0 + (end.0 - start.0) * t,
+                start.1 + (end.1 - start.1) * t,
+            )
+        };
+        match (start_side <= 0.0, end_side <= 0.0) {
+            (true, true) => clipped.push((start, label)),
+            (true, false) => {
+                clipped.push((start, label));
+                // The new edge along the bisector starts from where the polygon leaves.
+                clipped.push((intersection(), Some(other_ind)));
+            }
+            (false, true) => clipped.push((intersection(), label)),
+            (false, false) => {}
+        }
+    }
+
+    clipped
+}
+
+#[cfg(test)]
+mod test {
+    use crate::maze::Grid2d;
+
+    use super::{VoronoiGrid, VoronoiPosition, VoronoiShape, clip_by_bisector};
+
+    #[test]
+    fn test_clip_by_bisector() {
+        let square = [
+            ((0.0, 0.0), None),
+            ((2.0, 0.0), None),
+            ((2.0, 2.0), None),
+            ((0.0, 2.0), None),
+        ];
+        let clipped = clip_by_bisector(&square, (0.5, 1.0), (1.5, 1.0), 7);
+        assert_eq!(
+            clipped,
+            vec![
+                ((0.0, 0.0), None),
+                ((1.0, 0.0), Some(7)),
+                ((1.0, 2.0), None),
+                ((0.0, 2.0), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_grid_neighbors_symmetric() {
+        let grid = VoronoiGrid::new(&VoronoiShape::Circle { radius: 6.0 }, 1.0);
+        assert!(grid.cells_n() > 0);
+        for pos in grid.all_cells_pos_set() {
+            let pos: VoronoiPosition = pos.into();
+            for neighbor in grid.neighbor_pos_iter(&pos) {
+                assert!(grid.neighbor_pos_iter(&neighbor).any(|other| other == pos));
+            }
+        }
+    }
+}
@@ -0,0 +1,456 @@
+use std::{
+    collections::BTreeMap,
+    f32::consts::PI,
+    fs::File,
+    io::{BufWriter, Write},
+    ops::Range,
+    path::Path,
+};
+
+use anyhow::Error as AnyError;
+use clap::{Args, ValueEnum};
+
+pub mod circ;
+pub mod cube;
+pub mod hexa;
+pub mod rect;
+pub mod tri;
+pub mod voronoi;
+
+const DEF_CELL_SIZE: f32 = 10.0;
+const DEF_WALL_HEIGHT: f32 = 5.0;
+const DEF_WALL_THICKNESS: f32 = 1.5;
+const DEF_BASE_THICKNESS: f32 = 2.0;
+// Tolerances to decide whether two wall segments are on the same line.
+const LINE_ANGLE_TOLERANCE: f32 = 1e-3;
+const LINE_OFFSET_TOLERANCE: f32 = 1e-3;
+
+/// Settings to build the 3D model of maze, all lengths are in the same unit(millimeter for printing usually).
+#[derive(Debug, Clone, Copy, PartialEq, Args)]
+pub struct MeshSettings {
+    /// Size of the cell(from the center of a wall to the center of the opposite one, or the opposite corner of a triangle).
+    #[arg(long, default_value_t = DEF_CELL_SIZE)]
+    pub cell_size: f32,
+    /// Height of walls above the base
+    #[arg(long, default_value_t = DEF_WALL_HEIGHT)]
+    pub wall_height: f32,
+    /// Thickness of walls
+    #[arg(long, default_value_t = DEF_WALL_THICKNESS)]
+    pub wall_thickness: f32,
+    /// Thickness of the base plate under walls(not used by the cube maze, which is built on a solid cube)
+    #[arg(long, default_value_t = DEF_BASE_THICKNESS)]
+    pub base_thickness: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
+pub enum MeshFormat {
+    /// Wavefront OBJ file format
+    OBJ,
+    /// Binary STL file format
+    STL,
+}
+
+pub trait MazeMesh {
+    fn mesh(&self) -> Result<Mesh, AnyError>;
+}
+
+/// A triangle mesh, the vertices of every triangle are in counterclockwise order seen from the outside.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Mesh {
+    vertices: Vec<[f32; 3]>,
+    triangles: Vec<[usize; 3]>,
+}
+
+impl Mesh {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn vertices_n(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn triangles_n(&self) -> usize {
+        self.triangles.len()
+    }
+
+    /// Add a quadrilateral, whose corners are in counterclockwise order seen from the outside.
+    pub fn add_quad(&mut self, corners: [[f32; 3]; 4]) {
+        let start_ind = self.vertices.len();
+        self.vertices.extend(corners);
+        self.triangles
+            .push([start_ind, start_ind + 1, start_ind + 2]);
+        self.triangles
+            .push([start_ind, start_ind + 2, start_ind + 3]);
+    }
+
+    /// Add a parallelepiped given by a corner and three edges from it.
+    pub fn add_box(&mut self, origin: [f32; 3], edges: [[f32; 3]; 3]) {
+        let [mut a, mut b, c] = edges;
+        if dot(cross(a, b), c) < 0.0 {
+            // Make the edges right-handed, so the faces below point outward.
+            (a, b) = (b, a);
+        }
+
+        let o = origin;
+        let oa = add(o, a);
+        let ob = add(o, b);
+        let oc = add(o, c);
+        let oab = add(oa, b);
+        let oac = add(oa, c);
+        let obc = add(ob, c);
+        let oabc = add(oab, c);
+        self.add_quad([o, ob, oab, oa]);
+        self.add_quad([oc, oac, oabc, obc]);
+        self.add_quad([o, oa, oac, oc]);
+        self.add_quad([ob, obc, oabc, oab]);
+        self.add_quad([o, oc, obc, ob]);
+        self.add_quad([oa, oab, oabc, oac]);
+    }
+
+    /// Add a prism standing on the plane z = z0, whose bottom is the given convex polygon.
+    pub fn add_prism(&mut self, outline: &[[f32; 2]], z0: f32, height: f32) {
+        let mut outline = outline.to_vec();
+        if polygon_area(&outline) < 0.0 {
+            // Make the outline counterclockwise, so the faces below point outward.
+            outline.reverse();
+        }
+
+        let bottom_start_ind = self.vertices.len();
+        self.vertices
+            .extend(outline.iter().map(|[x, y]| [*x, *y, z0]));
+        let top_start_ind = self.vertices.len();
+        self.vertices
+            .extend(outline.iter().map(|[x, y]| [*x, *y, z0 + height]));
+        for ind in 1..outline.len().saturating_sub(1) {
+            self.triangles.push([
+                bottom_start_ind,
+                bottom_start_ind + ind + 1,
+                bottom_start_ind + ind,
+            ]);
+            self.triangles
+                .push([top_start_ind, top_start_ind + ind, top_start_ind + ind + 1]);
+        }
+        for ind in 0..outline.len() {
+            let next_ind = (ind + 1) % outline.len();
+            let [x0, y0] = outline[ind];
+            let [x1, y1] = outline[next_ind];
+            self.add_quad([
+                [x0, y0, z0],
+                [x1, y1, z0],
+                [x1, y1, z0 + height],
+                [x0, y0, z0 + height],
+            ]);
+        }
+    }
+
+    pub fn write_obj<W: Write>(&self, writer: &mut W) -> Result<(), AnyError> {
+        writeln!(writer, "o maze")?;
+        for [x, y, z] in self.vertices.iter() {
+            writeln!(writer, "v {} {} {}", x, y, z)?;
+        }
+        for [v0, v1, v2] in self.triangles.iter() {
+            // The index of vertex in OBJ starts from 1.
+            writeln!(writer, "f {} {} {}", v0 + 1, v1 + 1, v2 + 1)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn write_stl<W: Write>(&self, writer: &mut W) -> Result<(), AnyError> {
+        let mut header = [0u8; 80];
+        let title = b"maze";
+        header[..title.len()].copy_from_slice(title);
+        writer.write_all(&header)?;
+        writer.write_all(&u32::try_from(self.triangles.len())?.to_le_bytes())?;
+        for [v0, v1, v2] in self.triangles.iter().copied() {
+            let (p0, p1, p2) = (self.vertices[v0], self.vertices[v1], self.vertices[v2]);
+            let normal = cross(sub(p1, p0), sub(p2, p0));
+            let length = dot(normal, normal).sqrt();
+            let normal = if length > 0.0 {
+                scale(normal, 1.0 / length)
+            } else {
+                normal
+            };
+            for v in [normal, p0, p1, p2] {
+                for c in v {
+                    writer.write_all(&c.to_le_bytes())?;
+                }
+            }
+            // The attribute byte count, unused.
+            writer.write_all(&0u16.to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P, format: MeshFormat) -> Result<(), AnyError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        match format {
+            MeshFormat::OBJ => self.write_obj(&mut writer)?,
+            MeshFormat::STL => self.write_stl(&mut writer)?,
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Collector of the wall segments on a plane, which are built into wall prisms on a base plate.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct WallLayout {
+    walls: Vec<[[f32; 2]; 2]>,
+}
+
+impl WallLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the wall along the center line from start to end, in the picture coordinates(y axis points down).
+    pub fn add_wall(&mut self, start: [f32; 2], end: [f32; 2]) {
+        self.walls.push([start, end]);
+    }
+
+    /// Add the walls along the arc centered at the origin, from the start angle to the end angle(in radians).
+    pub fn add_arc_wall(&mut self, radius: f32, start_angle: f32, end_angle: f32) {
+        let max_step = 5f32.to_radians();
+        let steps_n = ((end_angle - start_angle).abs() / max_step).ceil().max(1.0) as usize;
+        let angle_step = (end_angle - start_angle) / steps_n as f32;
+        let point_at = |angle: f32| [radius * angle.cos(), radius * angle.sin()];
+        for step in 0..steps_n {
+            let angle = start_angle + angle_step * step as f32;
+            self.add_wall(point_at(angle), point_at(angle + angle_step));
+        }
+    }
+
+    /// Build the mesh of walls on the base plate, the plate is the given convex outline, or the bounding box of walls.
+    pub fn build(&self, settings: &MeshSettings, plate_outline: Option<&[[f32; 2]]>) -> Mesh {
+        let MeshSettings {
+            wall_height,
+            wall_thickness,
+            base_thickness,
+            ..
+        } = *settings;
+        let half_thickness = wall_thickness / 2.0;
+        // Flip the y axis, so the model isn't mirrored.
+        let flip = |[x, y]: [f32; 2]| [x, -y];
+        let mut mesh = Mesh::new();
+        let plate_outline = match plate_outline {
+            Some(outline) => outline.iter().copied().map(flip).collect::<Vec<_>>(),
+            None => {
+                let (mut x0, mut y0) = (f32::MAX, f32::MAX);
+                let (mut x1, mut y1) = (f32::MIN, f32::MIN);
+                for [x, y] in self.walls.iter().flatten().copied().map(flip) {
+                    (x0, y0) = (x0.min(x), y0.min(y));
+                    (x1, y1) = (x1.max(x), y1.max(y));
+                }
+                if x0 > x1 {
+                    // No walls at all.
+                    return mesh;
+                }
+
+                let (x0, y0) = (x0 - half_thickness, y0 - half_thickness);
+                let (x1, y1) = (x1 + half_thickness, y1 + half_thickness);
+                vec![[x0, y0], [x1, y0], [x1, y1], [x0, y1]]
+            }
+        };
+        mesh.add_prism(&plate_outline, 0.0, base_thickness);
+
+        for [start, end] in merge_collinear_walls(&self.walls) {
+            let [x0, y0] = flip(start);
+            let [x1, y1] = flip(end);
+            let length = ((x1 - x0).powi(2) + (y1 - y0).powi(2)).sqrt();
+            let dir = [(x1 - x0) / length, (y1 - y0) / length, 0.0];
+            let normal = [-dir[1], dir[0], 0.0];
+            // Extend the wall by half of thickness at both ends, to fill the corners.
+            let origin = add(
+                [x0, y0, base_thickness],
+                add(scale(dir, -half_thickness), scale(normal, -half_thickness)),
+            );
+            mesh.add_box(
+                origin,
+                [
+                    scale(dir, length + wall_thickness),
+                    scale(normal, wall_thickness),
+                    [0.0, 0.0, wall_height],
+                ],
+            );
+        }
+
+        mesh
+    }
+}
+
+/// Merge the consecutive walls along a line into runs, given whether there's a wall at each cell length.
+pub(crate) fn wall_runs(has_walls: impl IntoIterator<Item = bool>) -> Vec<Range<usize>> {
+    let mut runs = Vec::new();
+    let mut run_start = None;
+    let mut ind = 0;
+    for has_wall in has_walls {
+        match (has_wall, run_start) {
+            (true, None) => run_start = Some(ind),
+            (false, Some(start)) => {
+                runs.push(start..ind);
+                run_start = None;
+            }
+            _ => {}
+        }
+        ind += 1;
+    }
+    if let Some(start) = run_start {
+        runs.push(start..ind);
+    }
+
+    runs
+}
+
+/// Merge the walls on the same line which overlap or touch each other, so there're fewer wall prisms.
+fn merge_collinear_walls(walls: &[[[f32; 2]; 2]]) -> Vec<[[f32; 2]; 2]> {
+    // Group walls by the direction first, two opposite directions are the same.
+    let angle_keys_n = (PI / LINE_ANGLE_TOLERANCE).round() as i64;
+    let mut parallel_walls = BTreeMap::<i64, Vec<[[f32; 2]; 2]>>::new();
+    for [start, end] in walls.iter().copied() {
+        let (dx, dy) = (end[0] - start[0], end[1] - start[1]);
+        if dx.hypot(dy) > LINE_OFFSET_TOLERANCE {
+            let angle_key = (dy.atan2(dx) / LINE_ANGLE_TOLERANCE).round() as i64;
+            parallel_walls
+                .entry(angle_key.rem_euclid(angle_keys_n))
+                .or_default()
+                .push([start, end]);
+        }
+    }
+
+    let mut merged_walls = Vec::new();
+    for walls in parallel_walls.into_values() {
+        // Then group by the offset of line, and project walls onto the line.
+        let [start, end] = walls[0];
+        let length = (end[0] - start[0]).hypot(end[1] - start[1]);
+        let [dx, dy] = [(end[0] - start[0]) / length, (end[1] - start[1]) / length];
+        let mut lines = BTreeMap::<i64, (f32, Vec<(f32, f32)>)>::new();
+        for [start, end] in walls {
+            let offset = dx * start[1] - dy * start[0];
+            let (_, spans) = lines
+                .entry((offset / LINE_OFFSET_TOLERANCE).round() as i64)
+                .or_insert_with(|| (offset, Vec::new()));
+            let t0 = dx * start[0] + dy * start[1];
+            let t1 = dx * end[0] + dy * end[1];
+            spans.push((t0.min(t1), t0.max(t1)));
+        }
+
+        for (offset, mut spans) in lines.into_values() {
+            spans.sort_by(|s0, s1| s0.0.total_cmp(&s1.0));
+            let point_at = |t: f32| [dx * t - dy * offset, dy * t + dx * offset];
+            let mut cur_span: Option<(f32, f32)> = None;
+            for (t0, t1) in spans {
+                cur_span = match cur_span {
+                    Some((span_start, span_end)) if t0 <= span_end + LINE_OFFSET_TOLERANCE => {
+                        Some((span_start, span_end.max(t1)))
+                    }
+                    Some((span_start, span_end)) => {
+                        merged_walls.push([point_at(span_start), point_at(span_end)]);
+                        Some((t0, t1))
+                    }
+                    None => Some((t0, t1)),
+                };
+            }
+            if let Some((span_start, span_end)) = cur_span {
+                merged_walls.push([point_at(span_start), point_at(span_end)]);
+            }
+        }
+    }
+
+    merged_walls
+}
+
+fn add(v0: [f32; 3], v1: [f32; 3]) -> [f32; 3] {
+    [v0[0] + v1[0], v0[1] + v1[1], v0[2] + v1[2]]
+}
+
+fn sub(v0: [f32; 3], v1: [f32; 3]) -> [f32; 3] {
+    [v0[0] - v1[0], v0[1] - v1[1], v0[2] - v1[2]]
+}
+
+fn scale(v: [f32; 3], factor: f32) -> [f32; 3] {
+    [v[0] * factor, v[1] * factor, v[2] * factor]
+}
+
+fn cross(v0: [f32; 3], v1: [f32; 3]) -> [f32; 3] {
+    [
+        v0[1] * v1[2] - v0[2] * v1[1],
+        v0[2] * v1[0] - v0[0] * v1[2],
+        v0[0] * v1[1] - v0[1] * v1[0],
+    ]
+}
+
+fn dot(v0: [f32; 3], v1: [f32; 3]) -> f32 {
+    v0[0] * v1[0] + v0[1] * v1[1] + v0[2] * v1[2]
+}
+
+/// Signed area of the polygon, positive if its vertices are in counterclockwise order.
+fn polygon_area(polygon: &[[f32; 2]]) -> f32 {
+    (0..polygon.len())
+        .map(|ind| {
+            let [x0, y0] = polygon[ind];
+            let [x1, y1] = polygon[(ind + 1) % polygon.len()];
+            x0 * y1 - x1 * y0
+        })
+        .sum::<f32>()
+        / 2.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Mesh, add, cross, dot, merge_collinear_walls, wall_runs};
+
+    #[test]
+    fn test_wall_runs() {
+        assert_eq!(
+            wall_runs([true, true, false, true, false, false, true]),
+            vec![0..2, 3..4, 6..7]
+        );
+        assert!(wall_runs([false, false]).is_empty());
+    }
+
+    #[test]
+    fn test_merge_collinear_walls() {
+        let mut merged_walls = merge_collinear_walls(&[
+            [[0.0, 0.0], [1.0, 0.0]],
+            [[2.0, 0.0], [1.0, 0.0]],
+            [[3.0, 0.0], [4.0, 0.0]],
+            [[0.0, 1.0], [1.0, 1.0]],
+            [[1.0, 1.0], [2.0, 2.0]],
+            [[2.0, 2.0], [3.0, 3.0]],
+        ])
+        .into_iter()
+        .map(|wall| wall.map(|p| p.map(|c| c.round() as i32)))
+        .collect::<Vec<_>>();
+        merged_walls.sort();
+        assert_eq!(
+            merged_walls,
+            vec![
+                [[0, 0], [2, 0]],
+                [[0, 1], [1, 1]],
+                [[1, 1], [3, 3]],
+                [[3, 0], [4, 0]],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_box_faces_outward() {
+        let mut mesh = Mesh::new();
+        // Left-handed edges, should be fixed by the mesh.
+        mesh.add_box(
+            [1.0, 1.0, 1.0],
+            [[0.0, 2.0, 0.0], [2.0, 0.0, 0.0], [0.0, 0.0, 2.0]],
+        );
+        assert_eq!(mesh.triangles_n(), 12);
+        let center = [2.0, 2.0, 2.0];
+        for [v0, v1, v2] in mesh.triangles.iter().copied() {
+            let (p0, p1, p2) = (mesh.vertices[v0], mesh.vertices[v1], mesh.vertices[v2]);
+            let neg = |v: [f32; 3]| [-v[0], -v[1], -v[2]];
+            let normal = cross(add(p1, neg(p0)), add(p2, neg(p0)));
+            assert!(dot(normal, add(p0, neg(center))) > 0.0);
+        }
+    }
+}
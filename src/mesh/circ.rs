@@ -0,0 +1,65 @@
+use std::f32::consts::PI;
+
+use anyhow::Error as AnyError;
+
+use crate::maze::circ::{CircMaze, CircPosition};
+
+use super::{MazeMesh, Mesh, MeshSettings, WallLayout};
+
+// Count of sides of the polygon approximating the base plate.
+const PLATE_SIDES_N: usize = 128;
+
+pub struct CircMazeMesher<'a> {
+    maze: &'a CircMaze,
+    settings: MeshSettings,
+}
+
+impl MazeMesh for CircMazeMesher<'_> {
+    fn mesh(&self) -> Result<Mesh, AnyError> {
+        let maze = self.maze;
+        let rings_n = maze.rings_n();
+        // The cell size is the width of a ring.
+        let ring_width = self.settings.cell_size;
+        let mut layout = WallLayout::new();
+        for ring in 1..rings_n {
+            let inner_radius = ring as f32 * ring_width;
+            let ring_cells_n = maze.ring_cells_n(ring);
+            let cell_angle = 2.0 * PI / ring_cells_n as f32;
+            for cell in 0..ring_cells_n {
+                let pos = CircPosition::new(ring, cell);
+                let start_angle = cell as f32 * cell_angle;
+                let end_angle = start_angle + cell_angle;
+                if !maze.is_connected_inward(&pos) {
+                    layout.add_arc_wall(inner_radius, start_angle, end_angle);
+                }
+
+                if !maze.is_connected_clockwise(&pos) {
+                    let (sin, cos) = end_angle.sin_cos();
+                    let outer_radius = inner_radius + ring_width;
+                    layout.add_wall(
+                        [inner_radius * cos, inner_radius * sin],
+                        [outer_radius * cos, outer_radius * sin],
+                    );
+                }
+            }
+        }
+        // The outer border.
+        let total_radius = rings_n as f32 * ring_width;
+        layout.add_arc_wall(total_radius, 0.0, 2.0 * PI);
+
+        let plate_radius = total_radius + self.settings.wall_thickness / 2.0;
+        let plate_outline = (0..PLATE_SIDES_N)
+            .map(|ind| {
+                let (sin, cos) = (2.0 * PI * ind as f32 / PLATE_SIDES_N as f32).sin_cos();
+                [plate_radius * cos, plate_radius * sin]
+            })
+            .collect::<Vec<_>>();
+        Ok(layout.build(&self.settings, Some(&plate_outline)))
+    }
+}
+
+impl<'a> CircMazeMesher<'a> {
+    pub fn new(maze: &'a CircMaze, settings: MeshSettings) -> Self {
+        Self { maze, settings }
+    }
+}
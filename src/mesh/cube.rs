@@ -0,0 +1,111 @@
+use anyhow::Error as AnyError;
+
+use crate::maze::{
+    cube::{CubeFace, CubeMaze, CubePosition},
+    rect::RectDirection,
+};
+
+use super::{MazeMesh, Mesh, MeshSettings, add, scale, wall_runs};
+
+/// Build the model of the cube maze, a solid cube with walls extruded outward from its faces.
+pub struct CubeMazeMesher<'a> {
+    maze: &'a CubeMaze,
+    settings: MeshSettings,
+}
+
+impl MazeMesh for CubeMazeMesher<'_> {
+    fn mesh(&self) -> Result<Mesh, AnyError> {
+        let maze = self.maze;
+        let size = maze.size();
+        let MeshSettings {
+            cell_size,
+            wall_height,
+            wall_thickness,
+            ..
+        } = self.settings;
+        let cube_length = f32::from(u16::try_from(size)?) * cell_size;
+        let mut mesh = Mesh::new();
+        mesh.add_box(
+            [0.0; 3],
+            [
+                [cube_length, 0.0, 0.0],
+                [0.0, cube_length, 0.0],
+                [0.0, 0.0, cube_length],
+            ],
+        );
+
+        for face in CubeFace::all_faces() {
+            let (origin, col_dir, row_dir, normal) = face.frame();
+            let to_f32 = |v: [i64; 3]| v.map(|c| c as f32);
+            let (origin, col_dir, row_dir, normal) = (
+                scale(to_f32(origin), cube_length),
+                to_f32(col_dir),
+                to_f32(row_dir),
+                to_f32(normal),
+            );
+            // Add the wall covering [x0, x1] x [y0, y1] on the face, the part out of the face is cut off,
+            // so the wall on the cube edge is made of two halves on the two faces.
+            let mut add_wall = |x0: f32, x1: f32, y0: f32, y1: f32| {
+                let (x0, x1) = (x0.max(0.0), x1.min(cube_length));
+                let (y0, y1) = (y0.max(0.0), y1.min(cube_length));
+                mesh.add_box(
+                    add(add(origin, scale(col_dir, x0)), scale(row_dir, y0)),
+                    [
+                        scale(col_dir, x1 - x0),
+                        scale(row_dir, y1 - y0),
+                        scale(normal, wall_height),
+                    ],
+                );
+            };
+
+            let half_thickness = wall_thickness / 2.0;
+            for line in 0..=size {
+                // The walls along the north border of the row, or the south border of the last row.
+                let (row, dir) = if line < size {
+                    (line, RectDirection::North)
+                } else {
+                    (line - 1, RectDirection::South)
+                };
+                let line_y = line as f32 * cell_size;
+                for run in wall_runs(
+                    (0..size)
+                        .map(|col| !maze.is_connected_to(&CubePosition::new(*face, row, col), dir)),
+                ) {
+                    add_wall(
+                        run.start as f32 * cell_size - half_thickness,
+                        run.end as f32 * cell_size + half_thickness,
+                        line_y - half_thickness,
+                        line_y + half_thickness,
+                    );
+                }
+
+                // The walls along the west border of the column, or the east border of the last column.
+                let (col, dir) = if line < size {
+                    (line, RectDirection::West)
+                } else {
+                    (line - 1, RectDirection::East)
+                };
+                let line_x = line as f32 * cell_size;
+                for run in wall_runs(
+                    (0..size)
+                        .map(|row| !maze.is_connected_to(&CubePosition::new(*face, row, col), dir)),
+                ) {
+                    add_wall(
+                        line_x - half_thickness,
+                        line_x + half_thickness,
+                        run.start as f32 * cell_size - half_thickness,
+                        run.end as f32 * cell_size + half_thickness,
+                    );
+                }
+            }
+        }
+
+        Ok(mesh)
+    }
+}
+
+impl<'a> CubeMazeMesher<'a> {
+    pub fn new(maze: &'a CubeMaze, settings: MeshSettings) -> Self {
+        Self { maze, settings }
+    }
+}
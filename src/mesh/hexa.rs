@@ -0,0 +1,74 @@
+use anyhow::Error as AnyError;
+
+use crate::maze::hexa::{HexaDirection, HexaMaze, HexaPosition};
+
+use super::{MazeMesh, Mesh, MeshSettings, WallLayout};
+
+pub struct HexaMazeMesher<'a> {
+    maze: &'a HexaMaze,
+    settings: MeshSettings,
+}
+
+impl MazeMesh for HexaMazeMesher<'_> {
+    fn mesh(&self) -> Result<Mesh, AnyError> {
+        let maze = self.maze;
+        let (width, height) = maze.size();
+        // The cell size is the distance between the centers of two vertical neighbors.
+        let cell_size = self.settings.cell_size;
+        let cell_radius = cell_size / 3f32.sqrt();
+        // Edges with the indices of vertices, the vertex i is at the angle of 60 * i degrees(clockwise from the east).
+        let edges = [
+            (HexaDirection::SouthEast, 0, 1),
+            (HexaDirection::South, 1, 2),
+            (HexaDirection::SouthWest, 2, 3),
+            (HexaDirection::NorthWest, 3, 4),
+            (HexaDirection::North, 4, 5),
+            (HexaDirection::NorthEast, 5, 0),
+        ];
+        let mut layout = WallLayout::new();
+        for r in 0..height {
+            for c in 0..width {
+                let pos = HexaPosition::new(r, c);
+                if !maze.is_cell(&pos) {
+                    continue;
+                }
+
+                let center_x = cell_radius + c as f32 * cell_radius * 1.5;
+                // The centers in odd columns are lower by half of the cell.
+                let center_y = (r as f32 + if c % 2 == 0 { 0.5 } else { 1.0 }) * cell_size;
+                let vertex = |ind: usize| {
+                    let rad = (60.0 * ind as f32).to_radians();
+                    [
+                        center_x + cell_radius * rad.cos(),
+                        center_y + cell_radius * rad.sin(),
+                    ]
+                };
+                for (dir, start_ind, end_ind) in edges {
+                    let has_wall = match pos.neighbor(dir) {
+                        // The wall between two cells is added by the one on the north, the northwest or the southwest.
+                        Some(neighbor) if maze.is_cell(&neighbor) => {
+                            matches!(
+                                dir,
+                                HexaDirection::South
+                                    | HexaDirection::SouthEast
+                                    | HexaDirection::NorthEast
+                            ) && !maze.is_connected_to(&pos, dir)
+                        }
+                        _ => true,
+                    };
+                    if has_wall {
+                        layout.add_wall(vertex(start_ind), vertex(end_ind));
+                    }
+                }
+            }
+        }
+
+        Ok(layout.build(&self.settings, None))
+    }
+}
+
+impl<'a> HexaMazeMesher<'a> {
+    pub fn new(maze: &'a HexaMaze, settings: MeshSettings) -> Self {
+        Self { maze, settings }
+    }
+}
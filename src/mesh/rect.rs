@@ -0,0 +1,57 @@
+use anyhow::Error as AnyError;
+
+use crate::maze::rect::{RectDirection, RectMaze, RectPosition};
+
+use super::{MazeMesh, Mesh, MeshSettings, WallLayout};
+
+pub struct RectMazeMesher<'a> {
+    maze: &'a RectMaze,
+    settings: MeshSettings,
+}
+
+impl MazeMesh for RectMazeMesher<'_> {
+    fn mesh(&self) -> Result<Mesh, AnyError> {
+        let maze = self.maze;
+        let (width, height) = maze.size();
+        let cell_size = self.settings.cell_size;
+        let mut layout = WallLayout::new();
+        for r_ind in 0..height {
+            for c_ind in 0..width {
+                let pos = RectPosition::new(r_ind, c_ind);
+                if !maze.is_cell(&pos) {
+                    continue;
+                }
+
+                let x0 = c_ind as f32 * cell_size;
+                let y0 = r_ind as f32 * cell_size;
+                let (x1, y1) = (x0 + cell_size, y0 + cell_size);
+                for dir in RectDirection::all_dirs() {
+                    let has_wall = match pos.neighbor(*dir) {
+                        // The wall between two cells is added by the one on the north or the west.
+                        Some(neighbor) if maze.is_cell(&neighbor) => {
+                            matches!(dir, RectDirection::South | RectDirection::East)
+                                && !maze.is_connected_to(&pos, *dir)
+                        }
+                        _ => true,
+                    };
+                    if has_wall {
+                        match dir {
+                            RectDirection::North => layout.add_wall([x0, y0], [x1, y0]),
+                            RectDirection::East => layout.add_wall([x1, y0], [x1, y1]),
+                            RectDirection::South => layout.add_wall([x0, y1], [x1, y1]),
+                            RectDirection::West => layout.add_wall([x0, y0], [x0, y1]),
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(layout.build(&self.settings, None))
+    }
+}
+
+impl<'a> RectMazeMesher<'a> {
+    pub fn new(maze: &'a RectMaze, settings: MeshSettings) -> Self {
+        Self { maze, settings }
+    }
+}
@@ -0,0 +1,67 @@
+use anyhow::Error as AnyError;
+
+use crate::maze::tri::{TriDirection, TriMaze, TriPosition};
+
+use super::{MazeMesh, Mesh, MeshSettings, WallLayout};
+
+pub struct TriMazeMesher<'a> {
+    maze: &'a TriMaze,
+    settings: MeshSettings,
+}
+
+impl MazeMesh for TriMazeMesher<'_> {
+    fn mesh(&self) -> Result<Mesh, AnyError> {
+        let maze = self.maze;
+        let (width, height) = maze.size();
+        // The cell size is the height of the triangle.
+        let cell_height = self.settings.cell_size;
+        let half_cell_width = cell_height / 3f32.sqrt();
+        let mut layout = WallLayout::new();
+        for r in 0..height {
+            for c in 0..width {
+                let pos = TriPosition::new(r, c);
+                if !maze.is_cell(&pos) {
+                    continue;
+                }
+
+                let top_y = r as f32 * cell_height;
+                let bot_y = top_y + cell_height;
+                let center_x = (c + 1) as f32 * half_cell_width;
+                let (left_x, right_x) = (center_x - half_cell_width, center_x + half_cell_width);
+                let dirs = if maze.is_angle_up(&pos) {
+                    TriDirection::angle_up_all_dirs()
+                } else {
+                    TriDirection::angle_down_all_dirs()
+                };
+                for dir in dirs.iter().copied() {
+                    let has_wall = match pos.neighbor(dir) {
+                        // The wall between two cells is added by the angle up one.
+                        Some(neighbor) if maze.is_cell(&neighbor) => {
+                            maze.is_angle_up(&pos) && !maze.is_connected_to(&pos, dir)
+                        }
+                        _ => true,
+                    };
+                    if has_wall {
+                        let (start, end) = match dir {
+                            TriDirection::Northwest => ([center_x, top_y], [left_x, bot_y]),
+                            TriDirection::Northeast => ([center_x, top_y], [right_x, bot_y]),
+                            TriDirection::South => ([left_x, bot_y], [right_x, bot_y]),
+                            TriDirection::SouthWest => ([left_x, top_y], [center_x, bot_y]),
+                            TriDirection::Southeast => ([right_x, top_y], [center_x, bot_y]),
+                            TriDirection::North => ([left_x, top_y], [right_x, top_y]),
+                        };
+                        layout.add_wall(start, end);
+                    }
+                }
+            }
+        }
+
+        Ok(layout.build(&self.settings, None))
+    }
+}
+
+impl<'a> TriMazeMesher<'a> {
+    pub fn new(maze: &'a TriMaze, settings: MeshSettings) -> Self {
+        Self { maze, settings }
+    }
+}
@@ -0,0 +1,50 @@
+use anyhow::Error as AnyError;
+
+use crate::maze::voronoi::{VoronoiMaze, VoronoiPosition};
+
+use super::{MazeMesh, Mesh, MeshSettings, WallLayout};
+
+pub struct VoronoiMazeMesher<'a> {
+    maze: &'a VoronoiMaze,
+    settings: MeshSettings,
+}
+
+impl MazeMesh for VoronoiMazeMesher<'_> {
+    fn mesh(&self) -> Result<Mesh, AnyError> {
+        let maze = self.maze;
+        // The cell size is the length of the unit in the grid.
+        let unit_size = self.settings.cell_size;
+        let mut layout = WallLayout::new();
+        for site in 0..maze.sites_n() {
+            let pos = VoronoiPosition::new(site);
+            if !maze.is_cell(&pos) {
+                continue;
+            }
+
+            for edge in maze.edge_iter(&pos) {
+                let has_wall = match edge.neighbor {
+                    None => true,
+                    Some(neighbor) if !maze.is_cell(&neighbor) => true,
+                    // Add the wall between two cells only once, by the one with the lower index.
+                    Some(neighbor) => {
+                        neighbor.site > site && !maze.is_connected_to(&pos, &neighbor)
+                    }
+                };
+                if has_wall {
+                    layout.add_wall(
+                        [edge.start.0 * unit_size, edge.start.1 * unit_size],
+                        [edge.end.0 * unit_size, edge.end.1 * unit_size],
+                    );
+                }
+            }
+        }
+
+        Ok(layout.build(&self.settings, None))
+    }
+}
+
+impl<'a> VoronoiMazeMesher<'a> {
+    pub fn new(maze: &'a VoronoiMaze, settings: MeshSettings) -> Self {
+        Self { maze, settings }
+    }
+}
@@ -0,0 +1,682 @@
+//! Property tests running every generator on every grid it supports, with random sizes and masks.
+
+use std::{
+    env,
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::{
+    file::{MazeHeader, SavedMaze, binary::BinaryMaze},
+    gene::{
+        self, AldousBroderMazeGenerator, BTreeMazeGenerator, DiagonalDirection, EllerMazeGenerator,
+        GrowingTreeMazeGenerator, GrowingTreeSelection, GrowingTreeStrategy,
+        HuntAndKillMazeGenerator, KruskalMazeGenerator, LayerMazeGenerator, Maze2dGenerator,
+        OriginShiftMazeGenerator, PrimMazeGenerator, RandomWeight,
+        RecursiveBacktrackerMazeGenerator, RegionDivisionMazeGenerator, SidewinderMazeGenerator,
+        WeightedKruskalMazeGenerator, WeightedPrimMazeGenerator, WilsonMazeGenerator,
+        rect::{RectMazeGenerator, RecursiveDivisionMazeGenerator},
+    },
+    maze::{
+        Grid2d, LayerGrid, NoMask, WithMask,
+        circ::{CircGrid, CircMaze},
+        cube::{CubeGrid, CubeMaze},
+        hexa::{HexaGrid, HexaMaze},
+        rect::{RectDirection, RectGrid, RectMask, RectMaze, RectPosition},
+        tri::{TriGrid, TriMaze},
+        voronoi::{VoronoiGrid, VoronoiMaze, VoronoiShape},
+    },
+    rng,
+    show::{
+        self, MazePaint, circ::CircMazePainter, cube::CubeMazePainter, hexa::HexaMazePainter,
+        rect::RectMazePainter, tri::TriMazePainter, voronoi::VoronoiMazePainter,
+    },
+    validate::{ValidationReport, Violation},
+};
+use rand::{Rng, seq::IndexedRandom};
+
+/// Environment variable to run the cases with the given seed only, to reproduce a failure.
+const SEED_ENV: &str = "TRY_MAZES_TEST_SEED";
+/// Environment variable to run the given number of random cases, e.g. more cases in a long run.
+const CASES_ENV: &str = "TRY_MAZES_TEST_CASES";
+const DEF_CASES_N: usize = 32;
+const MAX_PASSAGE_WEIGHT: u32 = 100;
+const WALL_THICKNESS: u16 = 2;
+const CELL_WIDTH: u16 = 10;
+
+/// Run the check with random seeds(or the seed given by environment), the seed seeds both the random settings
+/// picked by the check and the generators, and is printed if the check fails.
+fn check_cases<F: Fn()>(name: &str, check: F) {
+    let seeds: Vec<u64> = match env::var(SEED_ENV) {
+        Ok(seed) => vec![
+            seed.parse()
+                .expect("Seed to reproduce should be an unsigned integer."),
+        ],
+        Err(_) => {
+            let cases_n = env::var(CASES_ENV).map_or(DEF_CASES_N, |cases_n| {
+                cases_n
+                    .parse()
+                    .expect("Number of cases should be an unsigned integer.")
+            });
+            (0..cases_n).map(|_| rand::random()).collect()
+        }
+    };
+    for seed in seeds {
+        rng::set_seed(seed);
+        if panic::catch_unwind(AssertUnwindSafe(&check)).is_err() {
+            panic!(
+                "Property check of {} failed with seed {}, run again with {}={} to reproduce.",
+                name, seed, SEED_ENV, seed
+            );
+        }
+    }
+}
+
+fn generators_2d() -> Vec<(&'static str, Box<dyn Maze2dGenerator>)> {
+    let mut rng = rng::rng();
+    let selection = *[
+        GrowingTreeSelection::Newest,
+        GrowingTreeSelection::Oldest,
+        GrowingTreeSelection::Random,
+    ]
+    .choose(&mut rng)
+    .unwrap();
+    vec![
+        ("Aldous-Broder", Box::new(AldousBroderMazeGenerator)),
+        ("Wilson's", Box::new(WilsonMazeGenerator)),
+        ("Hunt-and-Kill", Box::new(HuntAndKillMazeGenerator)),
+        (
+            "Recursive Backtracker",
+            Box::new(RecursiveBacktrackerMazeGenerator),
+        ),
+        ("Kruskal's", Box::new(KruskalMazeGenerator)),
+        ("Prim's", Box::new(PrimMazeGenerator)),
+        (
+            "Growing Tree",
+            Box::new(GrowingTreeMazeGenerator::new(GrowingTreeStrategy::new(
+                selection,
+            ))),
+        ),
+        (
+            "Weighted Prim's",
+            Box::new(WeightedPrimMazeGenerator::new(RandomWeight::uniform(
+                MAX_PASSAGE_WEIGHT,
+            ))),
+        ),
+        (
+            "Weighted Kruskal's",
+            Box::new(WeightedKruskalMazeGenerator::new(RandomWeight::uniform(
+                MAX_PASSAGE_WEIGHT,
+            ))),
+        ),
+        ("Origin Shift", Box::new(OriginShiftMazeGenerator)),
+    ]
+}
+
+fn layer_generators() -> Vec<(&'static str, Box<dyn LayerMazeGenerator>)> {
+    let mut rng = rng::rng();
+    let all_dirs = [
+        DiagonalDirection::Northeast,
+        DiagonalDirection::Southeast,
+        DiagonalDirection::Southwest,
+        DiagonalDirection::Northwest,
+    ];
+    vec![
+        ("Eller's", Box::new(EllerMazeGenerator)),
+        (
+            "Binary Tree",
+            Box::new(BTreeMazeGenerator::new(*all_dirs.choose(&mut rng).unwrap())),
+        ),
+        (
+            "Sidewinder",
+            Box::new(SidewinderMazeGenerator::new(
+                *all_dirs.choose(&mut rng).unwrap(),
+            )),
+        ),
+    ]
+}
+
+/// Mazes made by all the generators supporting grids without layers.
+fn mazes_2d<G: Grid2d + Clone>(grid: &G) -> Vec<(&'static str, G)> {
+    generators_2d()
+        .into_iter()
+        .map(|(name, generator)| {
+            let mut maze_grid = grid.clone();
+            generator.generate_2d(&mut maze_grid).unwrap();
+            (name, maze_grid)
+        })
+        .collect()
+}
+
+/// Mazes made by all the generators supporting grids with layers.
+fn layer_mazes<G: LayerGrid + Clone>(grid: &G) -> Vec<(&'static str, G)> {
+    let mut mazes = mazes_2d(grid);
+    let mut maze_grid = grid.clone();
+    // Rooms bigger than one cell have loops inside, so only the perfect mazes are made here.
+    RegionDivisionMazeGenerator::new(1)
+        .generate_2d(&mut maze_grid)
+        .unwrap();
+    mazes.push(("Recursive Division", maze_grid));
+    mazes.extend(layer_generators().into_iter().map(|(name, generator)| {
+        let mut maze_grid = grid.clone();
+        generator.generate_layer(&mut maze_grid).unwrap();
+        (name, maze_grid)
+    }));
+    mazes
+}
+
+/// Mazes made by dividing the grid into rooms of more than one cell, which have loops inside the rooms.
+fn room_mazes<G: Grid2d + Clone>(grid: &G) -> Vec<(&'static str, G)> {
+    let mut maze_grid = grid.clone();
+    RegionDivisionMazeGenerator::new(rng::rng().random_range(2..6))
+        .generate_2d(&mut maze_grid)
+        .unwrap();
+    vec![("Recursive Division(rooms)", maze_grid)]
+}
+
+/// Random connected mask made by a random walk, the walk only moves between neighbors in the triangular grid if
+/// asked, so the mask is connected there too.
+fn random_mask(width: usize, height: usize, is_tri: bool) -> RectMask {
+    let mut rng = rng::rng();
+    let mut mask = RectMask::new(width, height);
+    let mut pos = RectPosition::new(rng.random_range(0..height), rng.random_range(0..width));
+    for _ in 0..(width * height * 2) {
+        mask.set_flag(&pos, true);
+        // The triangle with angle up has the neighbor to the south, and the one with angle down to the north.
+        let is_angle_up = (pos.row + pos.col).is_multiple_of(2);
+        let neighbors: Vec<_> = RectDirection::all_dirs()
+            .iter()
+            .filter(|dir| match dir {
+                RectDirection::North => !is_tri || !is_angle_up,
+                RectDirection::South => !is_tri || is_angle_up,
+                _ => true,
+            })
+            .filter_map(|dir| pos.neighbor(*dir))
+            .filter(|neighbor| neighbor.row < height && neighbor.col < width)
+            .collect();
+        // The mask of one cell has no neighbor to walk to.
+        if let Some(neighbor) = neighbors.choose(&mut rng) {
+            pos = *neighbor;
+        }
+    }
+
+    mask
+}
+
+fn assert_maze<M: BinaryMaze>(
+    name: &str,
+    maze: &M,
+    grid: impl Fn(&M) -> &dyn Grid2d,
+    cells_n: usize,
+    paint: impl Fn(&M) -> (i32, i32),
+    pic_size: (i32, i32),
+) {
+    let report = ValidationReport::new(grid(maze));
+    assert_eq!(report.violations, [], "{} made an invalid maze.", name);
+    assert_eq!(report.cells_n, cells_n, "{} didn't cover all cells.", name);
+
+    let json = serde_json::to_string(maze).unwrap();
+    let loaded_maze: M = serde_json::from_str(&json).unwrap();
+    assert_eq!(serde_json::to_string(&loaded_maze).unwrap(), json);
+    assert_eq!(ValidationReport::new(grid(&loaded_maze)), report);
+    let binary = SavedMaze::new(MazeHeader::new(M::KIND), loaded_maze)
+        .to_binary(false)
+        .unwrap();
+    let loaded_maze = SavedMaze::<M>::from_binary(&binary).unwrap().maze;
+    assert_eq!(
+        serde_json::to_string(&loaded_maze).unwrap(),
+        json,
+        "Binary of {} isn't lossless.",
+        name
+    );
+
+    // The picture of maze takes the same size as the grid with all walls, whatever the passages and the mask are.
+    assert_eq!(paint(maze), pic_size, "Picture of {} has wrong size.", name);
+}
+
+/// Check the maze with rooms, the passages inside rooms make loops, but there should be no other violation.
+fn assert_room_maze(name: &str, grid: &dyn Grid2d, cells_n: usize) {
+    let report = ValidationReport::new(grid);
+    assert!(
+        report
+            .violations
+            .iter()
+            .all(|violation| matches!(violation, Violation::Loop { .. })),
+        "{} made an invalid maze with rooms: {:?}.",
+        name,
+        report.violations
+    );
+    assert_eq!(report.cells_n, cells_n, "{} didn't cover all cells.", name);
+}
+
+fn pic_size(painter: &dyn MazePaint) -> (i32, i32) {
+    let surface = painter.paint().unwrap();
+    (surface.width(), surface.height())
+}
+
+/// Size of picture, and check the walls reach every side of it, so there's no blank margin around the maze.
+fn tight_pic_size(name: &str, painter: &dyn MazePaint) -> (usize, usize) {
+    let mut surface = painter.paint().unwrap();
+    let (pixels, width, height) = show::surface_pixels(&mut surface).unwrap();
+    let (min_x, min_y, max_x, max_y) = pixels
+        .iter()
+        .enumerate()
+        .filter(|(_, pixel)| *pixel & 0xFF_FFFF != 0xFF_FFFF)
+        .map(|(ind, _)| (ind % width, ind / width))
+        .fold(
+            (usize::MAX, usize::MAX, 0, 0),
+            |(min_x, min_y, max_x, max_y), (x, y)| {
+                (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+            },
+        );
+    let margin = usize::from(WALL_THICKNESS);
+    assert!(
+        min_x < margin && min_y < margin && max_x + margin >= width && max_y + margin >= height,
+        "Picture({} x {}) of {} has blank margin, walls are in ({}, {}) - ({}, {}).",
+        width,
+        height,
+        name,
+        min_x,
+        min_y,
+        max_x,
+        max_y
+    );
+    (width, height)
+}
+
+/// Check the length of picture grows by the same step(give or take a pixel rounded) for every cell added.
+fn assert_linear_growth(name: &str, lengths: &[usize]) {
+    let steps: Vec<_> = lengths
+        .windows(2)
+        .map(|pair| pair[1] as i64 - pair[0] as i64)
+        .collect();
+    assert!(
+        steps
+            .iter()
+            .all(|step| *step >= i64::from(CELL_WIDTH) / 2 && (step - steps[0]).abs() <= 1),
+        "Picture of {} doesn't grow linearly: {:?}.",
+        name,
+        lengths
+    );
+}
+
+/// Check pictures of grids in different sizes, the width only changes with the width of grid and so does the height.
+fn assert_pic_sizes(name: &str, pic_size: impl Fn(usize, usize) -> (usize, usize)) {
+    let sizes = [1, 2, 3, 4, 5];
+    let wider_sizes: Vec<_> = sizes.iter().map(|width| pic_size(*width, 3)).collect();
+    let higher_sizes: Vec<_> = sizes.iter().map(|height| pic_size(3, *height)).collect();
+    assert!(
+        wider_sizes.iter().all(|size| size.1 == wider_sizes[0].1),
+        "Picture height of {} changes with its width.",
+        name
+    );
+    assert!(
+        higher_sizes.iter().all(|size| size.0 == higher_sizes[0].0),
+        "Picture width of {} changes with its height.",
+        name
+    );
+    assert_linear_growth(
+        name,
+        &wider_sizes.iter().map(|size| size.0).collect::<Vec<_>>(),
+    );
+    assert_linear_growth(
+        name,
+        &higher_sizes.iter().map(|size| size.1).collect::<Vec<_>>(),
+    );
+}
+
+#[test]
+fn test_rect_mazes() {
+    check_cases("rectangular mazes", || {
+        let mut rng = rng::rng();
+        let (width, height) = (rng.random_range(1..12), rng.random_range(1..12));
+        let paint = |maze: &RectMaze| {
+            pic_size(&RectMazePainter::new(
+                maze,
+                usize::from(WALL_THICKNESS),
+                usize::from(CELL_WIDTH),
+            ))
+        };
+        let grid = RectGrid::<NoMask>::new(width, height);
+        let expected_pic_size = paint(&RectMaze::NoMask(grid.clone()));
+        let mut mazes: Vec<_> = layer_mazes(&grid)
+            .into_iter()
+            .map(|(name, grid)| (name, RectMaze::NoMask(grid)))
+            .collect();
+        mazes.push((
+            "Recursive Division(rectangular)",
+            RecursiveDivisionMazeGenerator::new(1, 1)
+                .generate(grid.clone())
+                .unwrap(),
+        ));
+        for (name, maze) in mazes.iter() {
+            assert_maze(
+                name,
+                maze,
+                RectMaze::grid,
+                width * height,
+                paint,
+                expected_pic_size,
+            );
+        }
+
+        let mask = random_mask(width, height, false);
+        let masked_grid = RectGrid::<WithMask>::new(&mask);
+        for (name, grid) in layer_mazes(&masked_grid) {
+            let maze = RectMaze::WithMask(grid);
+            assert_maze(
+                name,
+                &maze,
+                RectMaze::grid,
+                mask.cells_n(),
+                paint,
+                expected_pic_size,
+            );
+        }
+
+        let (room_rows_n, room_cols_n) = (rng.random_range(2..5), rng.random_range(2..5));
+        let maze = RecursiveDivisionMazeGenerator::new(room_rows_n, room_cols_n)
+            .generate(grid.clone())
+            .unwrap();
+        assert_room_maze(
+            "Recursive Division(rectangular rooms)",
+            maze.grid(),
+            width * height,
+        );
+        for (name, grid) in room_mazes(&grid) {
+            assert_room_maze(name, &grid, width * height);
+        }
+        for (name, grid) in room_mazes(&masked_grid) {
+            assert_room_maze(name, &grid, mask.cells_n());
+        }
+    });
+}
+
+#[test]
+fn test_hexa_mazes() {
+    check_cases("hexagonal mazes", || {
+        let mut rng = rng::rng();
+        let (width, height) = (rng.random_range(1..12), rng.random_range(1..12));
+        let paint =
+            |maze: &HexaMaze| pic_size(&HexaMazePainter::new(maze, CELL_WIDTH, WALL_THICKNESS));
+        let grid = HexaGrid::<NoMask>::new(width, height);
+        let expected_pic_size = paint(&HexaMaze::NoMask(grid.clone()));
+        for (name, grid) in layer_mazes(&grid) {
+            let maze = HexaMaze::NoMask(grid);
+            assert_maze(
+                name,
+                &maze,
+                HexaMaze::grid,
+                width * height,
+                paint,
+                expected_pic_size,
+            );
+        }
+
+        let mask = random_mask(width, height, false);
+        let masked_grid = HexaGrid::<WithMask>::new(&mask);
+        for (name, grid) in layer_mazes(&masked_grid) {
+            let maze = HexaMaze::WithMask(grid);
+            assert_maze(
+                name,
+                &maze,
+                HexaMaze::grid,
+                mask.cells_n(),
+                paint,
+                expected_pic_size,
+            );
+        }
+
+        for (name, grid) in room_mazes(&grid) {
+            assert_room_maze(name, &grid, width * height);
+        }
+        for (name, grid) in room_mazes(&masked_grid) {
+            assert_room_maze(name, &grid, mask.cells_n());
+        }
+    });
+}
+
+#[test]
+fn test_tri_mazes() {
+    check_cases("triangular mazes", || {
+        let mut rng = rng::rng();
+        let (width, height) = (rng.random_range(1..12), rng.random_range(1..12));
+        let paint =
+            |maze: &TriMaze| pic_size(&TriMazePainter::new(maze, CELL_WIDTH, WALL_THICKNESS));
+        let expected_pic_size = paint(&TriMaze::new(TriGrid::new(width, height)));
+        let mask = random_mask(width, height, true);
+        let mut grids = vec![(TriGrid::with_mask(&mask), mask.cells_n())];
+        let grid = TriGrid::new(width, height);
+        // A single column of triangles is cut below every cell with angle down.
+        if width == 1 && height > 2 {
+            assert!(matches!(
+                WilsonMazeGenerator.generate_2d(&mut grid.clone()),
+                Err(gene::Error::DisconnectedGrid)
+            ));
+        } else {
+            grids.push((grid, width * height));
+        }
+        for (grid, cells_n) in grids {
+            for (name, grid) in room_mazes(&grid) {
+                assert_room_maze(name, &grid, cells_n);
+            }
+            for (name, grid) in layer_mazes(&grid) {
+                let maze = TriMaze::new(grid);
+                assert_maze(
+                    name,
+                    &maze,
+                    TriMaze::grid,
+                    cells_n,
+                    paint,
+                    expected_pic_size,
+                );
+            }
+        }
+    });
+}
+
+#[test]
+fn test_circ_mazes() {
+    check_cases("circular mazes", || {
+        let rings_n = rng::rng().random_range(1..8);
+        let grid = CircGrid::new(rings_n);
+        let cells_n = grid.cells_n();
+        let paint = |maze: &CircMaze| {
+            pic_size(&CircMazePainter::new(
+                maze,
+                usize::from(CELL_WIDTH),
+                usize::from(WALL_THICKNESS),
+            ))
+        };
+        let expected_pic_size = paint(&CircMaze::new(grid.clone()));
+        for (name, grid) in layer_mazes(&grid) {
+            let maze = CircMaze::new(grid);
+            assert_maze(
+                name,
+                &maze,
+                CircMaze::grid,
+                cells_n,
+                paint,
+                expected_pic_size,
+            );
+        }
+
+        for (name, grid) in room_mazes(&grid) {
+            assert_room_maze(name, &grid, cells_n);
+        }
+    });
+}
+
+#[test]
+fn test_cube_mazes() {
+    check_cases("cube mazes", || {
+        let size = rng::rng().random_range(1..5);
+        let paint = |maze: &CubeMaze| {
+            pic_size(&CubeMazePainter::new(
+                maze,
+                usize::from(WALL_THICKNESS),
+                usize::from(CELL_WIDTH),
+            ))
+        };
+        let grid = CubeGrid::new(size);
+        let expected_pic_size = paint(&CubeMaze::new(grid.clone()));
+        for (name, grid) in mazes_2d(&grid) {
+            let maze = CubeMaze::new(grid);
+            assert_maze(
+                name,
+                &maze,
+                CubeMaze::grid,
+                size * size * 6,
+                paint,
+                expected_pic_size,
+            );
+        }
+    });
+}
+
+#[test]
+fn test_voronoi_mazes() {
+    check_cases("voronoi mazes", || {
+        let mut rng = rng::rng();
+        let (width, height) = (rng.random_range(3.0..8.0), rng.random_range(3.0..8.0));
+        let grid = VoronoiGrid::new(&VoronoiShape::Rect { width, height }, 1.0);
+        let cells_n = grid.cells_n();
+        let paint = |maze: &VoronoiMaze| {
+            pic_size(&VoronoiMazePainter::new(maze, CELL_WIDTH, WALL_THICKNESS))
+        };
+        let expected_pic_size = paint(&VoronoiMaze::new(grid.clone()));
+        for (name, grid) in mazes_2d(&grid) {
+            let maze = VoronoiMaze::new(grid);
+            assert_maze(
+                name,
+                &maze,
+                VoronoiMaze::grid,
+                cells_n,
+                paint,
+                expected_pic_size,
+            );
+        }
+    });
+}
+
+#[test]
+fn test_picture_sizes() {
+    assert_pic_sizes("rectangular maze", |width, height| {
+        let maze = RectMaze::NoMask(RectGrid::<NoMask>::new(width, height));
+        tight_pic_size(
+            "rectangular maze",
+            &RectMazePainter::new(&maze, usize::from(WALL_THICKNESS), usize::from(CELL_WIDTH)),
+        )
+    });
+    assert_pic_sizes("hexagonal maze", |width, height| {
+        let maze = HexaMaze::NoMask(HexaGrid::<NoMask>::new(width, height));
+        tight_pic_size(
+            "hexagonal maze",
+            &HexaMazePainter::new(&maze, CELL_WIDTH, WALL_THICKNESS),
+        )
+    });
+    assert_pic_sizes("triangular maze", |width, height| {
+        let maze = TriMaze::new(TriGrid::new(width, height));
+        tight_pic_size(
+            "triangular maze",
+            &TriMazePainter::new(&maze, CELL_WIDTH, WALL_THICKNESS),
+        )
+    });
+
+    // The circular maze is a disk, and the cube maze is unfolded as a cross, 4 faces wide and 3 faces high.
+    let circ_sizes: Vec<_> = (1..6)
+        .map(|rings_n| {
+            let maze = CircMaze::new(CircGrid::new(rings_n));
+            tight_pic_size(
+                "circular maze",
+                &CircMazePainter::new(&maze, usize::from(CELL_WIDTH), usize::from(WALL_THICKNESS)),
+            )
+        })
+        .collect();
+    assert!(circ_sizes.iter().all(|(width, height)| width == height));
+    assert_linear_growth(
+        "circular maze",
+        &circ_sizes.iter().map(|size| size.0).collect::<Vec<_>>(),
+    );
+    let cube_sizes: Vec<_> = (1..6)
+        .map(|size| {
+            let maze = CubeMaze::new(CubeGrid::new(size));
+            tight_pic_size(
+                "cube maze",
+                &CubeMazePainter::new(&maze, usize::from(WALL_THICKNESS), usize::from(CELL_WIDTH)),
+            )
+        })
+        .collect();
+    let cube_widths: Vec<_> = cube_sizes.iter().map(|size| size.0).collect();
+    let cube_heights: Vec<_> = cube_sizes.iter().map(|size| size.1).collect();
+    assert_linear_growth("cube maze", &cube_widths);
+    assert_linear_growth("cube maze", &cube_heights);
+    assert_eq!(
+        (cube_widths[1] - cube_widths[0]) * 3,
+        (cube_heights[1] - cube_heights[0]) * 4
+    );
+
+    // The sites of Voronoi grid are random, but the picture covers the whole shape.
+    let voronoi_widths: Vec<_> = [3.0, 4.0, 5.0]
+        .into_iter()
+        .map(|width| {
+            let grid = VoronoiGrid::new(&VoronoiShape::Rect { width, height: 3.0 }, 1.0);
+            tight_pic_size(
+                "voronoi maze",
+                &VoronoiMazePainter::new(&VoronoiMaze::new(grid), CELL_WIDTH, WALL_THICKNESS),
+            )
+            .0
+        })
+        .collect();
+    assert_linear_growth("voronoi maze", &voronoi_widths);
+}
+
+#[test]
+fn test_paint_size_overflow() {
+    let assert_overflow = |painter: &dyn MazePaint| {
+        let error = painter.paint().err().unwrap();
+        assert!(matches!(
+            error.downcast_ref::<show::Error>(),
+            Some(show::Error::SizeOverflow(_))
+        ));
+    };
+
+    let mut grid = RectGrid::<NoMask>::new(3, 3);
+    WilsonMazeGenerator.generate_2d(&mut grid).unwrap();
+    let maze = RectMaze::NoMask(grid);
+    assert_overflow(&RectMazePainter::new(&maze, 1, usize::MAX));
+    assert_overflow(&RectMazePainter::new(&maze, 1, usize::MAX / 2));
+    assert_overflow(&RectMazePainter::new(&maze, 1, 1 << 30));
+
+    let mut grid = CircGrid::new(3);
+    WilsonMazeGenerator.generate_2d(&mut grid).unwrap();
+    let maze = CircMaze::new(grid);
+    assert_overflow(&CircMazePainter::new(&maze, usize::MAX, 1));
+    assert_overflow(&CircMazePainter::new(&maze, 1 << 29, 1));
+
+    let mut grid = CubeGrid::new(2);
+    WilsonMazeGenerator.generate_2d(&mut grid).unwrap();
+    let maze = CubeMaze::new(grid);
+    assert_overflow(&CubeMazePainter::new(&maze, 1, usize::MAX / 4));
+    assert_overflow(&CubeMazePainter::new(&maze, 1, 1 << 28));
+
+    // The hexagonal painter takes the maze size in u16, so a wide grid overflows it even without passages.
+    let maze = HexaMaze::NoMask(HexaGrid::<NoMask>::new(70000, 1));
+    assert_overflow(&HexaMazePainter::new(&maze, CELL_WIDTH, WALL_THICKNESS));
+}
+
+#[test]
+fn test_same_seed_same_maze() {
+    let seed = rand::random();
+    let new_maze = || {
+        rng::set_seed(seed);
+        let mut grid = RectGrid::<NoMask>::new(8, 8);
+        WilsonMazeGenerator.generate_2d(&mut grid).unwrap();
+        serde_json::to_string(&RectMaze::NoMask(grid)).unwrap()
+    };
+    assert_eq!(
+        new_maze(),
+        new_maze(),
+        "Seed {} made different mazes.",
+        seed
+    );
+}
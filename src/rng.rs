@@ -0,0 +1,35 @@
+use std::{cell::RefCell, rc::Rc};
+
+use rand::{RngCore, SeedableRng, rngs::StdRng};
+
+thread_local! {
+    static THREAD_RNG: Rc<RefCell<StdRng>> = Rc::new(RefCell::new(StdRng::from_os_rng()));
+}
+
+/// Random number generator shared by grids and generators in the current thread. It's seeded randomly at first, and
+/// can be seeded by `set_seed` so that the same mazes are generated again.
+#[derive(Debug, Clone)]
+pub struct MazeRng(Rc<RefCell<StdRng>>);
+
+impl RngCore for MazeRng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.borrow_mut().next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.borrow_mut().next_u64()
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        self.0.borrow_mut().fill_bytes(dst)
+    }
+}
+
+pub fn rng() -> MazeRng {
+    MazeRng(THREAD_RNG.with(Rc::clone))
+}
+
+/// Seed the random number generator of the current thread.
+pub fn set_seed(seed: u64) {
+    THREAD_RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
@@ -1,4 +1,10 @@
-use std::{cell::RefCell, fs::File, io::Write, path::Path};
+use std::{
+    cell::RefCell,
+    fs::File,
+    io::Write,
+    path::Path,
+    time::{Duration, Instant},
+};
 
 use anyhow::Error as AnyError;
 use clap::ValueEnum;
@@ -6,10 +12,13 @@ use minifb::{Key, ScaleMode, Window, WindowOptions};
 use skia_safe::{ColorSpace, EncodedImageFormat, ImageInfo, Surface, image::CachingHint};
 use thiserror::Error;
 
+pub mod any;
 pub mod circ;
+pub mod cube;
 pub mod hexa;
 pub mod rect;
 pub mod tri;
+pub mod voronoi;
 
 #[derive(Debug, Clone, Error)]
 pub enum Error {
@@ -19,6 +28,8 @@ pub enum Error {
     CanNotCreateSurface,
     #[error("Failed to read pixels from maze image")]
     ReadPixelFailure,
+    #[error("Size of {0} is too large to paint")]
+    SizeOverflow(&'static str),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
@@ -47,28 +58,12 @@ impl<'a, MP: MazePaint> MazePicture<'a, MP> {
     }
 
     pub fn show(&self, wnd_width: usize, wnd_height: usize) -> Result<(), AnyError> {
-        let image = self.surface()?.image_snapshot();
-        let size = image.image_info().bounds().size();
-        let mut pixels = vec![0u32; usize::try_from(size.width * size.height)?];
-        let copy_info = ImageInfo::new_n32(size, image.alpha_type(), ColorSpace::new_srgb());
-        let dst_row_bytes = usize::try_from(size.width)? * u32::BITS as usize / 8;
-        if !image.read_pixels(
-            &copy_info,
-            pixels.as_mut_slice(),
-            dst_row_bytes,
-            (0, 0),
-            CachingHint::Disallow,
-        ) {
-            return Err(Error::ReadPixelFailure.into());
+        let (pixels, pic_width, pic_height) = surface_pixels(&mut self.surface()?)?;
+        let mut window = open_window(pic_width, pic_height, wnd_width, wnd_height)?;
+        while window.is_open() && !window.is_key_down(Key::Escape) {
+            window.update_with_buffer(pixels.as_slice(), pic_width, pic_height)?;
         }
 
-        Self::show_pixels(
-            pixels.as_slice(),
-            usize::try_from(size.width)?,
-            usize::try_from(size.height)?,
-            wnd_width,
-            wnd_height,
-        )?;
         Ok(())
     }
 
@@ -100,38 +95,96 @@ impl<'a, MP: MazePaint> MazePicture<'a, MP> {
         *self.surface_cache.borrow_mut() = Some(surface.clone());
         Ok(surface)
     }
+}
 
-    fn show_pixels(
-        pixels: &[u32],
-        pic_width: usize,
-        pic_height: usize,
-        wnd_width: usize,
-        wnd_height: usize,
-    ) -> Result<(), AnyError> {
-        let wnd_options = WindowOptions {
-            resize: true,
-            scale_mode: if pic_width > wnd_width || pic_height > wnd_height {
-                ScaleMode::AspectRatioStretch
-            } else {
-                ScaleMode::Center
-            },
-            ..Default::default()
-        };
-        let mut window = Window::new(
-            "Maze Show - ESC to exit",
-            wnd_width,
-            wnd_height,
-            wnd_options,
-        )?;
+/// Show pictures painted frame by frame in GUI, the next frame is painted after given interval until the window
+/// is closed.
+pub fn show_animation<F: FnMut() -> Result<Surface, AnyError>>(
+    wnd_width: usize,
+    wnd_height: usize,
+    frame_interval: Duration,
+    mut paint_frame: F,
+) -> Result<(), AnyError> {
+    let (mut pixels, mut pic_width, mut pic_height) = surface_pixels(&mut paint_frame()?)?;
+    let mut window = open_window(pic_width, pic_height, wnd_width, wnd_height)?;
+    let mut last_frame_time = Instant::now();
+    while window.is_open() && !window.is_key_down(Key::Escape) {
+        if last_frame_time.elapsed() >= frame_interval {
+            (pixels, pic_width, pic_height) = surface_pixels(&mut paint_frame()?)?;
+            last_frame_time = Instant::now();
+        }
+        window.update_with_buffer(pixels.as_slice(), pic_width, pic_height)?;
+    }
 
-        window.set_background_color(u8::MAX, u8::MAX, u8::MAX);
-        // Limit to max ~60 fps update rate
-        window.set_target_fps(60);
+    Ok(())
+}
 
-        while window.is_open() && !window.is_key_down(Key::Escape) {
-            window.update_with_buffer(pixels, pic_width, pic_height)?;
-        }
+/// Convert size to the integer type used in painting, fail if it overflows.
+pub(crate) fn paint_size<T: TryFrom<usize>>(name: &'static str, size: usize) -> Result<T, Error> {
+    T::try_from(size).map_err(|_| Error::SizeOverflow(name))
+}
 
-        Ok(())
+/// Length of given number of intervals plus the extra length in painting, fail if it overflows.
+pub(crate) fn paint_length(
+    name: &'static str,
+    interval: usize,
+    count: usize,
+    extra: usize,
+) -> Result<i32, Error> {
+    interval
+        .checked_mul(count)
+        .and_then(|length| length.checked_add(extra))
+        .ok_or(Error::SizeOverflow(name))
+        .and_then(|length| paint_size(name, length))
+}
+
+pub(crate) fn surface_pixels(surface: &mut Surface) -> Result<(Vec<u32>, usize, usize), AnyError> {
+    let image = surface.image_snapshot();
+    let size = image.image_info().bounds().size();
+    let mut pixels = vec![0u32; usize::try_from(size.width * size.height)?];
+    let copy_info = ImageInfo::new_n32(size, image.alpha_type(), ColorSpace::new_srgb());
+    let dst_row_bytes = usize::try_from(size.width)? * u32::BITS as usize / 8;
+    if !image.read_pixels(
+        &copy_info,
+        pixels.as_mut_slice(),
+        dst_row_bytes,
+        (0, 0),
+        CachingHint::Disallow,
+    ) {
+        return Err(Error::ReadPixelFailure.into());
     }
+
+    Ok((
+        pixels,
+        usize::try_from(size.width)?,
+        usize::try_from(size.height)?,
+    ))
+}
+
+fn open_window(
+    pic_width: usize,
+    pic_height: usize,
+    wnd_width: usize,
+    wnd_height: usize,
+) -> Result<Window, AnyError> {
+    let wnd_options = WindowOptions {
+        resize: true,
+        scale_mode: if pic_width > wnd_width || pic_height > wnd_height {
+            ScaleMode::AspectRatioStretch
+        } else {
+            ScaleMode::Center
+        },
+        ..Default::default()
+    };
+    let mut window = Window::new(
+        "Maze Show - ESC to exit",
+        wnd_width,
+        wnd_height,
+        wnd_options,
+    )?;
+
+    window.set_background_color(u8::MAX, u8::MAX, u8::MAX);
+    // Limit to max ~60 fps update rate
+    window.set_target_fps(60);
+    Ok(window)
 }
@@ -0,0 +1,51 @@
+use skia_safe::Surface;
+
+use crate::maze::any::AnyMaze;
+
+use super::{
+    MazePaint, circ::CircMazePainter, cube::CubeMazePainter, hexa::HexaMazePainter,
+    rect::RectMazePainter, tri::TriMazePainter, voronoi::VoronoiMazePainter,
+};
+
+/// Painter of maze of any kind, the cell width is taken as the ring interval by the circular maze, the cell height
+/// by the hexagonal and the triangular maze, and the unit width by the Voronoi maze.
+#[derive(Debug)]
+pub struct AnyMazePainter<'a> {
+    maze: &'a AnyMaze,
+    cell_width: u16,
+    wall_thickness: u16,
+}
+
+impl MazePaint for AnyMazePainter<'_> {
+    fn paint(&self) -> Result<Surface, anyhow::Error> {
+        let (cell_width, wall_thickness) = (self.cell_width, self.wall_thickness);
+        let (cell_width_n, wall_thickness_n) =
+            (usize::from(cell_width), usize::from(wall_thickness));
+        match self.maze {
+            AnyMaze::Rect(maze) => {
+                RectMazePainter::new(maze, wall_thickness_n, cell_width_n).paint()
+            }
+            AnyMaze::Hexa(maze) => HexaMazePainter::new(maze, cell_width, wall_thickness).paint(),
+            AnyMaze::Tri(maze) => TriMazePainter::new(maze, cell_width, wall_thickness).paint(),
+            AnyMaze::Circ(maze) => {
+                CircMazePainter::new(maze, cell_width_n, wall_thickness_n).paint()
+            }
+            AnyMaze::Cube(maze) => {
+                CubeMazePainter::new(maze, wall_thickness_n, cell_width_n).paint()
+            }
+            AnyMaze::Voronoi(maze) => {
+                VoronoiMazePainter::new(maze, cell_width, wall_thickness).paint()
+            }
+        }
+    }
+}
+
+impl<'a> AnyMazePainter<'a> {
+    pub fn new(maze: &'a AnyMaze, cell_width: u16, wall_thickness: u16) -> Self {
+        Self {
+            maze,
+            cell_width,
+            wall_thickness,
+        }
+    }
+}
@@ -3,7 +3,7 @@ use skia_safe::{Color, Paint, PaintStyle, Path, PathDirection, Rect, Surface, su
 
 use crate::maze::circ::{CircMaze, CircPosition};
 
-use super::{Error, MazePaint};
+use super::{Error, MazePaint, paint_length, paint_size};
 
 pub struct CircMazePainter<'a> {
     maze: &'a CircMaze,
@@ -15,26 +15,36 @@ impl MazePaint for CircMazePainter<'_> {
     fn paint(&self) -> Result<Surface, AnyError> {
         let maze = self.maze;
         let rings_n = maze.rings_n();
-        let ring_interval = i32::try_from(self.ring_interval_width + self.wall_thickness)?;
-        let total_radius = ring_interval * i32::try_from(rings_n)?;
-        let mut surface = surfaces::raster_n32_premul((total_radius * 2, total_radius * 2))
-            .ok_or(Error::CanNotCreateSurface)?;
+        let ring_interval = self
+            .ring_interval_width
+            .checked_add(self.wall_thickness)
+            .ok_or(Error::SizeOverflow("ring interval"))?;
+        let total_radius = paint_length("maze radius", ring_interval, rings_n, 0)?;
+        let diameter = total_radius
+            .checked_mul(2)
+            .ok_or(Error::SizeOverflow("maze diameter"))?;
+        let mut surface =
+            surfaces::raster_n32_premul((diameter, diameter)).ok_or(Error::CanNotCreateSurface)?;
         let mut paint = Paint::default();
         paint.set_color(Color::BLACK);
         paint.set_anti_alias(true);
         paint.set_style(PaintStyle::Stroke);
-        paint.set_stroke_width(u16::try_from(self.wall_thickness)?.into());
+        paint.set_stroke_width(paint_size::<u16>("wall thickness", self.wall_thickness)?.into());
 
         let mut path = Path::new();
         surface.canvas().clear(Color::WHITE);
         surface.canvas().save();
         surface.canvas().translate((total_radius, total_radius));
-        let mut cur_radius = f32::from(u16::try_from(self.ring_interval_width)?)
-            + f32::from(u16::try_from(self.wall_thickness)?) / 2.0;
-        let ring_interval = f32::from(u16::try_from(ring_interval)?);
+        let mut cur_radius =
+            f32::from(paint_size::<u16>(
+                "ring interval",
+                self.ring_interval_width,
+            )?) + f32::from(paint_size::<u16>("wall thickness", self.wall_thickness)?) / 2.0;
+        let ring_interval = f32::from(paint_size::<u16>("ring interval", ring_interval)?);
         for ring in 1..rings_n {
             let ring_cells_n = maze.ring_cells_n(ring);
-            let cell_angle_interval = 360.0 / f32::from(u16::try_from(ring_cells_n)?);
+            let cell_angle_interval =
+                360.0 / f32::from(paint_size::<u16>("ring cells", ring_cells_n)?);
             let mut cur_angle = 0f32;
             for cell in 0..ring_cells_n {
                 let pos = CircPosition::new(ring, cell);
@@ -0,0 +1,101 @@
+use anyhow::Error as AnyError;
+use skia_safe::{Color, Paint, PaintStyle, Path, Surface, surfaces};
+
+use crate::maze::{
+    cube::{CubeFace, CubeMaze, CubePosition},
+    rect::RectDirection,
+};
+
+use super::{Error, MazePaint, paint_length, paint_size};
+
+/// Painter of the cube maze, which paints the unfolded net of the cube(a cross of 4 x 3 faces).
+pub struct CubeMazePainter<'a> {
+    maze: &'a CubeMaze,
+    wall_thickness: usize,
+    cell_width: usize,
+}
+
+impl MazePaint for CubeMazePainter<'_> {
+    fn paint(&self) -> Result<Surface, AnyError> {
+        let maze = self.maze;
+        let size = maze.size();
+        let wall_thickness: i32 = paint_size("wall thickness", self.wall_thickness)?;
+        let stroke_offset = wall_thickness / 2;
+        let cell_interval = self
+            .cell_width
+            .checked_add(self.wall_thickness)
+            .ok_or(Error::SizeOverflow("cell interval"))?;
+        let face_width = cell_interval
+            .checked_mul(size)
+            .ok_or(Error::SizeOverflow("face width"))?;
+        let canvas_width = paint_length("maze width", face_width, 4, self.wall_thickness)?;
+        let canvas_height = paint_length("maze height", face_width, 3, self.wall_thickness)?;
+        let cell_interval: i32 = paint_size("cell interval", cell_interval)?;
+        let face_width: i32 = paint_size("face width", face_width)?;
+        let mut surface = surfaces::raster_n32_premul((canvas_width, canvas_height))
+            .ok_or(Error::CanNotCreateSurface)?;
+        let mut paint = Paint::default();
+        paint.set_color(Color::BLACK);
+        paint.set_anti_alias(false);
+        paint.set_style(PaintStyle::Stroke);
+        paint.set_stroke_width(paint_size::<u16>("wall thickness", self.wall_thickness)?.into());
+        surface.canvas().clear(Color::WHITE);
+
+        let mut path = Path::new();
+        // Add the wall from (x, y) with the length of a cell, along the horizontal or vertical direction.
+        let mut add_wall = |x: i32, y: i32, is_horz: bool| {
+            if is_horz {
+                path.move_to((x, y + stroke_offset));
+                path.line_to((x + cell_interval + wall_thickness, y + stroke_offset));
+            } else {
+                path.move_to((x + stroke_offset, y));
+                path.line_to((x + stroke_offset, y + cell_interval + wall_thickness));
+            }
+        };
+        for face in CubeFace::all_faces() {
+            let (net_col, net_row) = face.net_pos();
+            let face_x0 = face_width * i32::try_from(net_col)?;
+            let face_y0 = face_width * i32::try_from(net_row)?;
+            for r_ind in 0..size {
+                for c_ind in 0..size {
+                    let pos = CubePosition::new(*face, r_ind, c_ind);
+                    let cell_x0 = face_x0 + cell_interval * i32::try_from(c_ind)?;
+                    let cell_y0 = face_y0 + cell_interval * i32::try_from(r_ind)?;
+                    for dir in RectDirection::all_dirs() {
+                        // The walls inside a face are only added by the cell on the north or west side.
+                        let should_add = match dir {
+                            RectDirection::North | RectDirection::West => true,
+                            RectDirection::South => r_ind == size - 1,
+                            RectDirection::East => c_ind == size - 1,
+                        };
+                        if should_add && !maze.is_connected_to(&pos, *dir) {
+                            match dir {
+                                RectDirection::North => add_wall(cell_x0, cell_y0, true),
+                                RectDirection::South => {
+                                    add_wall(cell_x0, cell_y0 + cell_interval, true)
+                                }
+                                RectDirection::West => add_wall(cell_x0, cell_y0, false),
+                                RectDirection::East => {
+                                    add_wall(cell_x0 + cell_interval, cell_y0, false)
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        surface.canvas().draw_path(&path, &paint);
+
+        Ok(surface)
+    }
+}
+
+impl<'a> CubeMazePainter<'a> {
+    pub fn new(maze: &'a CubeMaze, wall_thickness: usize, cell_width: usize) -> Self {
+        Self {
+            maze,
+            wall_thickness,
+            cell_width,
+        }
+    }
+}
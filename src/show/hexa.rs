@@ -2,7 +2,7 @@ use skia_safe::{Color, Paint, Path, Surface, surfaces};
 
 use crate::maze::hexa::{HexaDirection, HexaMaze, HexaPosition};
 
-use super::{Error, MazePaint};
+use super::{Error, MazePaint, paint_size};
 
 #[derive(Debug)]
 pub struct HexaMazePainter<'a> {
@@ -21,10 +21,12 @@ impl MazePaint for HexaMazePainter<'_> {
         let sqrt_3 = 3f32.sqrt();
         let cell_radius = cell_vert_interval / sqrt_3;
         let pic_width = (wall_thickness / sqrt_3 * 2.0
-            + (3.0 * f32::from(u16::try_from(maze_width)?) + 1.0) / 2.0 * cell_radius)
+            + (3.0 * f32::from(paint_size::<u16>("maze width", maze_width)?) + 1.0) / 2.0
+                * cell_radius)
             .ceil() as i32;
         let pic_height = (wall_thickness
-            + (f32::from(u16::try_from(maze_height)?) + 0.5) * cell_vert_interval)
+            + (f32::from(paint_size::<u16>("maze height", maze_height)?) + 0.5)
+                * cell_vert_interval)
             .ceil() as i32;
         let mut surface = surfaces::raster_n32_premul((pic_width, pic_height))
             .ok_or(Error::CanNotCreateSurface)?;
@@ -4,7 +4,7 @@ use skia_safe::{Color, Paint, PaintStyle, Path, Surface, surfaces};
 
 use crate::maze::rect::{RectDirection, RectMaze, RectPosition};
 
-use super::{Error, MazePaint};
+use super::{Error, MazePaint, paint_length, paint_size};
 
 pub trait CmdBoxCharset {
     fn horz_wall(&self) -> &str;
@@ -137,7 +137,7 @@ impl<T: CmdBoxCharset> Display for RectMazeCmdDisplay<'_, T> {
             ceil.clear();
             body.clear();
             let mut has_west_wall = false;
-            for c_ind in 0..width {
+            for (c_ind, has_vert_wall) in last_row_has_vert_wall.iter_mut().enumerate() {
                 let pos = RectPosition::new(r_ind, c_ind);
                 let is_cell = maze.is_cell(&pos);
                 let has_east_wall = if is_cell {
@@ -146,7 +146,7 @@ impl<T: CmdBoxCharset> Display for RectMazeCmdDisplay<'_, T> {
                     pos.neighbor(RectDirection::North)
                         .is_some_and(|neighbor| maze.is_cell(&neighbor))
                 };
-                let has_north_wall = last_row_has_vert_wall[c_ind];
+                let has_north_wall = *has_vert_wall;
                 let has_south_wall = if is_cell {
                     !maze.is_connected_to(&pos, RectDirection::West)
                 } else {
@@ -168,7 +168,7 @@ impl<T: CmdBoxCharset> Display for RectMazeCmdDisplay<'_, T> {
                 });
                 body.push_str(horz_empty);
 
-                last_row_has_vert_wall[c_ind] = has_south_wall;
+                *has_vert_wall = has_south_wall;
                 has_west_wall = has_east_wall;
             }
 
@@ -195,13 +195,13 @@ impl<T: CmdBoxCharset> Display for RectMazeCmdDisplay<'_, T> {
         // Add the final south border.
         ceil.clear();
         let mut south_row_has_west_wall = false;
-        for c_ind in 0..width {
+        for (c_ind, has_north_wall) in last_row_has_vert_wall.iter().enumerate() {
             let has_east_wall = height
                 .checked_sub(1)
                 .is_some_and(|r_ind| maze.is_cell(&RectPosition::new(r_ind, c_ind)));
             ceil.push_str(charset.select_corner(
                 south_row_has_west_wall,
-                last_row_has_vert_wall[c_ind],
+                *has_north_wall,
                 has_east_wall,
                 false,
             ));
@@ -228,18 +228,23 @@ pub struct RectMazePainter<'a> {
 impl MazePaint for RectMazePainter<'_> {
     fn paint(&self) -> anyhow::Result<Surface, anyhow::Error> {
         let (width, height) = self.maze.size();
-        let wall_thickness = i32::try_from(self.wall_thickness)?;
+        let wall_thickness: i32 = paint_size("wall thickness", self.wall_thickness)?;
         let stroke_offset = wall_thickness / 2;
-        let cell_interval = i32::try_from(self.cell_width + self.wall_thickness)?;
-        let canvas_width = cell_interval * i32::try_from(width)? + wall_thickness;
-        let canvas_height = cell_interval * i32::try_from(height)? + wall_thickness;
+        let cell_interval = self
+            .cell_width
+            .checked_add(self.wall_thickness)
+            .ok_or(Error::SizeOverflow("cell interval"))?;
+        let canvas_width = paint_length("maze width", cell_interval, width, self.wall_thickness)?;
+        let canvas_height =
+            paint_length("maze height", cell_interval, height, self.wall_thickness)?;
+        let cell_interval: i32 = paint_size("cell interval", cell_interval)?;
         let mut surface = surfaces::raster_n32_premul((canvas_width, canvas_height))
             .ok_or(Error::CanNotCreateSurface)?;
         let mut paint = Paint::default();
         paint.set_color(Color::BLACK);
         paint.set_anti_alias(false);
         paint.set_style(PaintStyle::Stroke);
-        paint.set_stroke_width(u16::try_from(self.wall_thickness)?.into());
+        paint.set_stroke_width(paint_size::<u16>("wall thickness", self.wall_thickness)?.into());
         surface.canvas().clear(Color::WHITE);
 
         let mut path = Path::new();
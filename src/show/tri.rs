@@ -2,7 +2,7 @@ use skia_safe::{Color, Paint, Path, Surface, surfaces};
 
 use crate::maze::tri::{TriDirection, TriMaze, TriPosition};
 
-use super::{Error, MazePaint};
+use super::{Error, MazePaint, paint_size};
 
 pub struct TriMazePainter<'a> {
     maze: &'a TriMaze,
@@ -21,10 +21,12 @@ impl MazePaint for TriMazePainter<'_> {
         let cell_horz_interval = cell_vert_interval / sqrt_3;
         let canvas_vert_offset = wall_thickness;
         let canvas_horz_offset = wall_thickness / sqrt_3;
-        let pic_width = (f32::from(u16::try_from(maze_width + 1)?) * cell_horz_interval
+        let pic_width = (f32::from(paint_size::<u16>("maze width", maze_width + 1)?)
+            * cell_horz_interval
             + canvas_horz_offset * 2.0)
             .ceil() as i32;
-        let pic_height = (f32::from(u16::try_from(maze_height)?) * cell_vert_interval
+        let pic_height = (f32::from(paint_size::<u16>("maze height", maze_height)?)
+            * cell_vert_interval
             + canvas_vert_offset * 2.0)
             .ceil() as i32;
         let mut surface = surfaces::raster_n32_premul((pic_width, pic_height))
@@ -0,0 +1,73 @@
+use skia_safe::{Color, Paint, PaintStyle, Path, Surface, surfaces};
+
+use crate::maze::voronoi::{VoronoiMaze, VoronoiPosition};
+
+use super::{Error, MazePaint};
+
+pub struct VoronoiMazePainter<'a> {
+    maze: &'a VoronoiMaze,
+    unit_width: u16,
+    wall_thickness: u16,
+}
+
+impl MazePaint for VoronoiMazePainter<'_> {
+    fn paint(&self) -> Result<Surface, anyhow::Error> {
+        let maze = self.maze;
+        let (maze_width, maze_height) = maze.size();
+        let unit_width = f32::from(self.unit_width);
+        let wall_thickness = f32::from(self.wall_thickness);
+        let pic_width = (maze_width * unit_width + wall_thickness).ceil() as i32;
+        let pic_height = (maze_height * unit_width + wall_thickness).ceil() as i32;
+        let mut surface = surfaces::raster_n32_premul((pic_width, pic_height))
+            .ok_or(Error::CanNotCreateSurface)?;
+        let mut paint = Paint::default();
+        paint.set_color(Color::BLACK);
+        paint.set_anti_alias(true);
+        paint.set_style(PaintStyle::Stroke);
+        paint.set_stroke_width(wall_thickness);
+
+        surface.canvas().clear(Color::WHITE);
+        surface.canvas().save();
+        surface
+            .canvas()
+            .translate((wall_thickness / 2.0, wall_thickness / 2.0));
+        let mut path = Path::new();
+        for site in 0..maze.sites_n() {
+            let pos = VoronoiPosition::new(site);
+            if !maze.is_cell(&pos) {
+                // Walls between a cell and a non-cell are added by the cell.
+                continue;
+            }
+
+            for edge in maze.edge_iter(&pos) {
+                let has_wall = match edge.neighbor {
+                    // The border of the whole grid.
+                    None => true,
+                    Some(neighbor) if !maze.is_cell(&neighbor) => true,
+                    // Add the wall between two cells only once, by the one with the lower index.
+                    Some(neighbor) => {
+                        neighbor.site > site && !maze.is_connected_to(&pos, &neighbor)
+                    }
+                };
+                if has_wall {
+                    path.move_to((edge.start.0 * unit_width, edge.start.1 * unit_width));
+                    path.line_to((edge.end.0 * unit_width, edge.end.1 * unit_width));
+                }
+            }
+        }
+        surface.canvas().draw_path(&path, &paint);
+        surface.canvas().restore();
+
+        Ok(surface)
+    }
+}
+
+impl<'a> VoronoiMazePainter<'a> {
+    pub fn new(maze: &'a VoronoiMaze, unit_width: u16, wall_thickness: u16) -> Self {
+        Self {
+            maze,
+            unit_width,
+            wall_thickness,
+        }
+    }
+}
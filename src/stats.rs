@@ -0,0 +1,386 @@
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, HashMap, HashSet, VecDeque, hash_map::Entry},
+    fmt::Display,
+};
+
+use serde::Serialize;
+
+use crate::maze::{
+    Maze, Position2d,
+    rect::{RectDirection, RectMaze},
+};
+
+/// Metrics of maze, to compare mazes made by different algorithms.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MazeStats {
+    pub cells_n: usize,
+    pub passages_n: usize,
+    /// Count of cells with only one passage.
+    pub dead_ends_n: usize,
+    pub dead_end_ratio: f32,
+    /// Count of cells with more than two passages, grouped by the count of passages.
+    pub junctions_n_by_degree: BTreeMap<usize, usize>,
+    /// Average count of passages in corridors, a corridor is a run of cells between dead ends or junctions.
+    pub average_corridor_length: f32,
+    /// Ratio of cells with exactly two passages, a maze with high river factor flows in long winding corridors
+    /// with few branches.
+    pub river_factor: f32,
+    /// The longest path in maze, taken as the solution.
+    pub solution: Option<SolutionStats>,
+    /// Count of passages in each direction, only for rectangular maze.
+    pub passage_bias: Option<PassageBias>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct SolutionStats {
+    pub start: Position2d,
+    pub goal: Position2d,
+    /// Count of passages from start to goal.
+    pub length: usize,
+    /// Ratio of cells on the solution to all cells.
+    pub cells_ratio: f32,
+    /// Distance between start and goal ignoring walls, divided by the solution length, 1.0 means the straightest.
+    pub directness: f32,
+    /// The reciprocal of directness.
+    pub tortuosity: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub struct PassageBias {
+    pub horizontal_n: usize,
+    pub vertical_n: usize,
+}
+
+impl Display for MazeStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Cells: {}", self.cells_n)?;
+        writeln!(f, "Passages: {}", self.passages_n)?;
+        writeln!(
+            f,
+            "Dead ends: {} ({:.1}%)",
+            self.dead_ends_n,
+            self.dead_end_ratio * 100.0
+        )?;
+        let junctions: Vec<_> = self
+            .junctions_n_by_degree
+            .iter()
+            .map(|(degree, junctions_n)| format!("{} of degree {}", junctions_n, degree))
+            .collect();
+        writeln!(
+            f,
+            "Junctions: {}",
+            if junctions.is_empty() {
+                "none".to_string()
+            } else {
+                junctions.join(", ")
+            }
+        )?;
+        writeln!(
+            f,
+            "Average corridor length: {:.2}",
+            self.average_corridor_length
+        )?;
+        write!(f, "River factor: {:.2}", self.river_factor)?;
+        if let Some(solution) = &self.solution {
+            writeln!(f)?;
+            writeln!(
+                f,
+                "Solution: from ({}, {}) to ({}, {}), {} passages ({:.1}% of cells)",
+                solution.start.0,
+                solution.start.1,
+                solution.goal.0,
+                solution.goal.1,
+                solution.length,
+                solution.cells_ratio * 100.0
+            )?;
+            write!(
+                f,
+                "Directness: {:.2}, tortuosity: {:.2}",
+                solution.directness, solution.tortuosity
+            )?;
+        }
+        if let Some(bias) = &self.passage_bias {
+            writeln!(f)?;
+            write!(
+                f,
+                "Passage bias: {} horizontal, {} vertical",
+                bias.horizontal_n, bias.vertical_n
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl MazeStats {
+    pub fn new<M: Maze + ?Sized>(maze: &M) -> Self {
+        let cells_pos: Vec<_> = maze.cells().collect();
+        let cells_n = cells_pos.len();
+        let degrees: HashMap<_, _> = cells_pos
+            .iter()
+            .map(|pos| (*pos, maze.links(pos).len()))
+            .collect();
+        let passages_n = degrees.values().sum::<usize>() / 2;
+        let dead_ends_n = degrees.values().filter(|degree| **degree == 1).count();
+        let corridor_cells_n = degrees.values().filter(|degree| **degree == 2).count();
+        let mut junctions_n_by_degree = BTreeMap::new();
+        for degree in degrees.values().filter(|degree| **degree > 2) {
+            *junctions_n_by_degree.entry(*degree).or_default() += 1;
+        }
+        let corridors_n = corridors_n(maze, &cells_pos, &degrees);
+
+        Self {
+            cells_n,
+            passages_n,
+            dead_ends_n,
+            dead_end_ratio: ratio(dead_ends_n, cells_n),
+            junctions_n_by_degree,
+            average_corridor_length: ratio(passages_n, corridors_n),
+            river_factor: ratio(corridor_cells_n, cells_n),
+            solution: cells_pos
+                .first()
+                .map(|first_pos| SolutionStats::new(maze, first_pos, cells_n)),
+            passage_bias: None,
+        }
+    }
+
+    pub fn new_rect(maze: &RectMaze) -> Self {
+        let mut bias = PassageBias {
+            horizontal_n: 0,
+            vertical_n: 0,
+        };
+        for (_, dir, _) in maze.passages() {
+            match dir {
+                RectDirection::East | RectDirection::West => bias.horizontal_n += 1,
+                RectDirection::North | RectDirection::South => bias.vertical_n += 1,
+            }
+        }
+
+        Self {
+            passage_bias: Some(bias),
+            ..Self::new(maze)
+        }
+    }
+}
+
+impl SolutionStats {
+    /// Find the longest path by searching the farthest cell twice, which is exact in a perfect maze.
+    fn new<M: Maze + ?Sized>(maze: &M, first_pos: &M::Position, cells_n: usize) -> Self {
+        let farthest = |from: &M::Position| {
+            distances(maze, from, true)
+                .into_iter()
+                .max_by_key(|(pos, distance)| (*distance, Reverse(*pos)))
+                .unwrap_or((*from, 0))
+        };
+        let (start, _) = farthest(first_pos);
+        let (goal, length) = farthest(&start);
+        let grid_distance = distances(maze, &start, false)
+            .get(&goal)
+            .copied()
+            .unwrap_or(0);
+        let directness = if length == 0 {
+            1.0
+        } else {
+            grid_distance as f32 / length as f32
+        };
+
+        Self {
+            start: start.into(),
+            goal: goal.into(),
+            length,
+            cells_ratio: ratio(length + 1, cells_n),
+            directness,
+            tortuosity: if directness > 0.0 {
+                1.0 / directness
+            } else {
+                0.0
+            },
+        }
+    }
+}
+
+fn ratio(part: usize, total: usize) -> f32 {
+    if total == 0 {
+        0.0
+    } else {
+        part as f32 / total as f32
+    }
+}
+
+fn connected_neighbors<M: Maze + ?Sized>(maze: &M, pos: &M::Position) -> Vec<M::Position> {
+    maze.links(pos)
+        .into_iter()
+        .map(|(_, neighbor)| neighbor)
+        .collect()
+}
+
+/// Distances from the given cell to every cell reachable, through passages only or ignoring walls.
+pub(crate) fn distances<M: Maze + ?Sized>(
+    maze: &M,
+    from: &M::Position,
+    through_passages: bool,
+) -> HashMap<M::Position, usize> {
+    let mut distances = HashMap::from([(*from, 0)]);
+    let mut candidates = VecDeque::from([*from]);
+    while let Some(pos) = candidates.pop_front() {
+        let distance = distances[&pos] + 1;
+        for (_, neighbor) in maze.neighbors(&pos) {
+            if (!through_passages || maze.is_connected(&pos, &neighbor))
+                && !distances.contains_key(&neighbor)
+            {
+                distances.insert(neighbor, distance);
+                candidates.push_back(neighbor);
+            }
+        }
+    }
+
+    distances
+}
+
+/// The shortest path from one cell to another through passages, the only path in a perfect maze.
+pub fn shortest_path<M: Maze + ?Sized>(
+    maze: &M,
+    from: &M::Position,
+    to: &M::Position,
+) -> Option<Vec<M::Position>> {
+    let mut last_pos = HashMap::from([(*from, *from)]);
+    let mut candidates = VecDeque::from([*from]);
+    while let Some(pos) = candidates.pop_front() {
+        if pos == *to {
+            let mut path = vec![pos];
+            let mut cur_pos = pos;
+            while cur_pos != *from {
+                cur_pos = last_pos[&cur_pos];
+                path.push(cur_pos);
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        for neighbor in connected_neighbors(maze, &pos) {
+            if let Entry::Vacant(entry) = last_pos.entry(neighbor) {
+                entry.insert(pos);
+                candidates.push_back(neighbor);
+            }
+        }
+    }
+
+    None
+}
+
+/// Count corridors by walking from every dead end or junction, the loops made of only corridor cells are counted too.
+fn corridors_n<M: Maze + ?Sized>(
+    maze: &M,
+    cells_pos: &[M::Position],
+    degrees: &HashMap<M::Position, usize>,
+) -> usize {
+    let mut walked_passages = HashSet::new();
+    let passage = |from: M::Position, to: M::Position| (from.min(to), from.max(to));
+    let mut corridors_n = 0;
+    let start_cells = cells_pos
+        .iter()
+        .filter(|pos| degrees[*pos] != 2)
+        .chain(cells_pos.iter().filter(|pos| degrees[*pos] == 2));
+    for start_pos in start_cells {
+        for neighbor in connected_neighbors(maze, start_pos) {
+            if !walked_passages.insert(passage(*start_pos, neighbor)) {
+                continue;
+            }
+
+            corridors_n += 1;
+            let (mut last_pos, mut cur_pos) = (*start_pos, neighbor);
+            while degrees[&cur_pos] == 2 {
+                let Some(next_pos) = connected_neighbors(maze, &cur_pos)
+                    .into_iter()
+                    .find(|pos| *pos != last_pos)
+                else {
+                    break;
+                };
+                if !walked_passages.insert(passage(cur_pos, next_pos)) {
+                    break;
+                }
+                (last_pos, cur_pos) = (cur_pos, next_pos);
+            }
+        }
+    }
+
+    corridors_n
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        gene::{Maze2dGenerator, WilsonMazeGenerator},
+        maze::{
+            Grid2d, NoMask, Position2d,
+            hexa::{HexaGrid, HexaMaze},
+            rect::{RectGrid, RectMaze},
+        },
+    };
+
+    use super::{MazeStats, PassageBias};
+
+    #[test]
+    fn test_stats_of_rect_maze() {
+        // +---+---+---+
+        // |           |
+        // +---+   +---+
+        // |           |
+        // +---+---+---+
+        let mut maze = RectMaze::NoMask(RectGrid::<NoMask>::new(3, 2));
+        let grid = maze.grid_mut();
+        for (from, to) in [
+            ((0, 0), (0, 1)),
+            ((0, 1), (0, 2)),
+            ((0, 1), (1, 1)),
+            ((1, 0), (1, 1)),
+            ((1, 1), (1, 2)),
+        ] {
+            assert!(grid.connect_to(&Position2d(from.0, from.1), &Position2d(to.0, to.1)));
+        }
+
+        let stats = MazeStats::new_rect(&maze);
+        assert_eq!(stats.cells_n, 6);
+        assert_eq!(stats.passages_n, 5);
+        assert_eq!(stats.dead_ends_n, 4);
+        assert_eq!(stats.junctions_n_by_degree, BTreeMap::from([(3, 2)]));
+        assert_eq!(stats.average_corridor_length, 1.0);
+        assert_eq!(stats.river_factor, 0.0);
+        assert_eq!(
+            stats.passage_bias,
+            Some(PassageBias {
+                horizontal_n: 4,
+                vertical_n: 1
+            })
+        );
+        let solution = stats.solution.unwrap();
+        assert_eq!(solution.start, Position2d(1, 0));
+        assert_eq!(solution.goal, Position2d(0, 0));
+        assert_eq!(solution.length, 3);
+        assert_eq!(solution.directness, 1.0 / 3.0);
+        assert_eq!(solution.tortuosity, 3.0);
+
+        // Open the south-west corner, the two corridors between junctions become one.
+        maze.grid_mut()
+            .disconnect_from(&Position2d(1, 0), &Position2d(1, 1));
+        maze.grid_mut()
+            .connect_to(&Position2d(0, 0), &Position2d(1, 0));
+        let stats = MazeStats::new(maze.grid());
+        assert_eq!(stats.dead_ends_n, 3);
+        assert_eq!(stats.junctions_n_by_degree, BTreeMap::from([(3, 1)]));
+        assert_eq!(stats.average_corridor_length, 5.0 / 3.0);
+        assert_eq!(stats.river_factor, 2.0 / 6.0);
+        assert_eq!(stats.passage_bias, None);
+    }
+
+    #[test]
+    fn test_stats_of_maze_same_as_grid() {
+        let mut grid = HexaGrid::<NoMask>::new(6, 5);
+        WilsonMazeGenerator.generate_2d(&mut grid).unwrap();
+        let grid_stats = MazeStats::new(&grid as &dyn Grid2d);
+        assert_eq!(MazeStats::new(&HexaMaze::NoMask(grid)), grid_stats);
+    }
+}
@@ -0,0 +1,783 @@
+use std::{fmt::Write as FmtWrite, fs, path::Path};
+
+use anyhow::Error as AnyError;
+use clap::{Args, ValueEnum};
+use image::{ImageReader, LumaA};
+use serde_json::json;
+use thiserror::Error;
+
+use crate::maze::{
+    NoMask, Position2d, WithMask,
+    rect::{RectDirection, RectGrid, RectMask, RectMaze, RectPosition},
+};
+
+const DEF_CORRIDOR_WIDTH: usize = 1;
+const DEF_WALL_WIDTH: usize = 1;
+const DEF_TILE_SIZE: usize = 16;
+// Characters only used by the ascii box charset, the others are in unicode.
+const ASCII_BOX_CHARS: &str = "+-|";
+const ASCII_HORZ_WIDTH: usize = 3;
+const UNICODE_HORZ_WIDTH: usize = 1;
+// Global tile IDs in Tiled maps, 0 is the empty tile.
+const FLOOR_GID: u32 = 1;
+const WALL_GID: u32 = 2;
+
+#[derive(Debug, Clone, Error)]
+pub enum Error {
+    #[error(
+        "Inconsistent row width in given tile grid, expect {expected_width} tiles, given {this_width}."
+    )]
+    InconsistentRow {
+        this_width: usize,
+        expected_width: usize,
+    },
+    #[error("Invalid tile '{0}' in given tile grid, the tile should be 0(floor) or 1(wall).")]
+    InvalidTile(char),
+    #[error(
+        "Tile grid of {width} x {height} can't be divided into cells and walls of the given widths."
+    )]
+    InvalidSize { width: usize, height: usize },
+    #[error(
+        "Width of maze text({0} characters) doesn't fit the box characters, every cell takes {1} characters in a row."
+    )]
+    InvalidTextWidth(usize, usize),
+    #[error("Tiles of the {0} at row {1}, column {2} should be all floors or all walls.")]
+    MixedTiles(&'static str, usize, usize),
+    #[error("Passage at row {0}, column {1} leads to a wall cell.")]
+    PassageToWall(usize, usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, ValueEnum)]
+pub enum TileMapFormat {
+    /// Tiled map file format(XML)
+    TMX,
+    /// Tiled map file format in JSON
+    JSON,
+    /// Plain grid of tiles, a line per row, 0 for the floor and 1 for the wall
+    Grid,
+}
+
+/// Settings to convert between maze and tile map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Args)]
+pub struct TileSettings {
+    /// Width of the corridor(the cell) in tiles
+    #[arg(long, default_value_t = DEF_CORRIDOR_WIDTH)]
+    pub corridor_width: usize,
+    /// Width of the wall in tiles
+    #[arg(long, default_value_t = DEF_WALL_WIDTH)]
+    pub wall_width: usize,
+    /// Size of the tile in pixels, used by Tiled maps
+    #[arg(long, default_value_t = DEF_TILE_SIZE)]
+    pub tile_size: usize,
+}
+
+impl Default for TileSettings {
+    fn default() -> Self {
+        Self {
+            corridor_width: DEF_CORRIDOR_WIDTH,
+            wall_width: DEF_WALL_WIDTH,
+            tile_size: DEF_TILE_SIZE,
+        }
+    }
+}
+
+impl TileSettings {
+    fn cell_interval(&self) -> usize {
+        self.corridor_width + self.wall_width
+    }
+
+    /// The first tile of the cell(or the wall before it if it's negative) in a row or column.
+    fn cell_start(&self, ind: usize) -> usize {
+        self.wall_width + ind * self.cell_interval()
+    }
+}
+
+/// Map of floor and wall tiles, the cells of rectangular maze are the square areas of floor, and the walls between
+/// them are the stripes of wall.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileMap {
+    width: usize,
+    height: usize,
+    walls: Vec<bool>,
+    settings: TileSettings,
+    start: Option<RectPosition>,
+    goal: Option<RectPosition>,
+}
+
+impl TileMap {
+    pub fn new(
+        maze: &RectMaze,
+        settings: &TileSettings,
+        start: Option<Position2d>,
+        goal: Option<Position2d>,
+    ) -> Self {
+        let (maze_width, maze_height) = maze.size();
+        let width = settings.cell_start(maze_width);
+        let height = settings.cell_start(maze_height);
+        let mut map = Self {
+            width,
+            height,
+            walls: vec![true; width * height],
+            settings: *settings,
+            start: start.map(RectPosition::from),
+            goal: goal.map(RectPosition::from),
+        };
+        for r in 0..maze_height {
+            for c in 0..maze_width {
+                let pos = RectPosition::new(r, c);
+                if !maze.is_cell(&pos) {
+                    continue;
+                }
+
+                let (x0, y0) = (settings.cell_start(c), settings.cell_start(r));
+                let corridor_width = settings.corridor_width;
+                map.set_floor(x0, y0, corridor_width, corridor_width);
+                // Only the passages to the east and the south, so every passage is cleared once.
+                if maze.is_connected_to(&pos, RectDirection::East) {
+                    map.set_floor(x0 + corridor_width, y0, settings.wall_width, corridor_width);
+                }
+                if maze.is_connected_to(&pos, RectDirection::South) {
+                    map.set_floor(x0, y0 + corridor_width, corridor_width, settings.wall_width);
+                }
+            }
+        }
+
+        map
+    }
+
+    /// Read tile map from the plain grid, a line per row, 0 for the floor and 1 for the wall.
+    pub fn from_grid_text(text: &str, settings: &TileSettings) -> Result<Self, Error> {
+        let mut width = None;
+        let mut walls = Vec::new();
+        let mut height = 0;
+        for line in text
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+        {
+            let this_width = line.chars().count();
+            let expected_width = *width.get_or_insert(this_width);
+            if this_width != expected_width {
+                return Err(Error::InconsistentRow {
+                    this_width,
+                    expected_width,
+                });
+            }
+
+            for tile in line.chars() {
+                walls.push(match tile {
+                    '0' => false,
+                    '1' => true,
+                    other => return Err(Error::InvalidTile(other)),
+                });
+            }
+            height += 1;
+        }
+
+        Ok(Self {
+            width: width.unwrap_or(0),
+            height,
+            walls,
+            settings: *settings,
+            start: None,
+            goal: None,
+        })
+    }
+
+    pub fn try_from_grid_file<P: AsRef<Path>>(
+        path: P,
+        settings: &TileSettings,
+    ) -> Result<Self, AnyError> {
+        Ok(Self::from_grid_text(&fs::read_to_string(path)?, settings)?)
+    }
+
+    /// Read tile map from the maze drawn in box characters(ascii or unicode, as `RectMazeCmdDisplay` shows), every
+    /// corner, wall and cell is a tile. The cells out of the maze(masked out) are filled with walls.
+    pub fn from_box_text(text: &str) -> Result<Self, Error> {
+        let mut lines = text
+            .lines()
+            .map(|line| line.trim_end().chars().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        let is_ascii = lines.iter().flatten().any(|c| ASCII_BOX_CHARS.contains(*c));
+        let horz_width = if is_ascii {
+            ASCII_HORZ_WIDTH
+        } else {
+            UNICODE_HORZ_WIDTH
+        };
+        // Editors may trim the spaces at the end of lines, so the width is the longest line.
+        let chars_width = lines.iter().map(Vec::len).max().unwrap_or(0);
+        let interval = horz_width + 1;
+        if chars_width == 0 || (chars_width - 1) % interval != 0 {
+            return Err(Error::InvalidTextWidth(chars_width, interval));
+        }
+
+        let width = (chars_width - 1) / interval * 2 + 1;
+        let height = lines.len();
+        let mut walls = Vec::with_capacity(width * height);
+        for line in &lines {
+            for x in 0..width {
+                // Corners and vertical walls take a character, horizontal walls and cells take the horizontal width.
+                let (start, len) = if x % 2 == 0 {
+                    (x / 2 * interval, 1)
+                } else {
+                    (x / 2 * interval + 1, horz_width)
+                };
+                walls.push(
+                    line.iter()
+                        .skip(start)
+                        .take(len)
+                        .any(|c| !c.is_whitespace()),
+                );
+            }
+        }
+
+        let mut map = Self {
+            width,
+            height,
+            walls,
+            settings: TileSettings::default(),
+            start: None,
+            goal: None,
+        };
+        map.fill_outer_floors();
+        Ok(map)
+    }
+
+    pub fn try_from_box_text_file<P: AsRef<Path>>(path: P) -> Result<Self, AnyError> {
+        Ok(Self::from_box_text(&fs::read_to_string(path)?)?)
+    }
+
+    /// Read tile map from the block bitmap, every pixel is a tile, and the dark opaque pixel is the wall.
+    pub fn try_from_image_file<P: AsRef<Path>>(
+        path: P,
+        settings: &TileSettings,
+    ) -> Result<Self, AnyError> {
+        let image = ImageReader::open(path)?.decode()?.into_luma_alpha8();
+        let width = usize::try_from(image.width())?;
+        let height = usize::try_from(image.height())?;
+        let walls = image
+            .pixels()
+            .map(|&LumaA([luma, alpha])| luma < 0x80 && alpha >= 0x80)
+            .collect::<Vec<_>>();
+        let mut map = Self {
+            width,
+            height,
+            walls,
+            settings: *settings,
+            start: None,
+            goal: None,
+        };
+        map.fill_outer_floors();
+        Ok(map)
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    pub fn is_wall(&self, x: usize, y: usize) -> bool {
+        x >= self.width || y >= self.height || self.walls[y * self.width + x]
+    }
+
+    /// Rebuild the rectangular maze, the cells whose areas are walls are masked out.
+    pub fn to_rect_maze(&self) -> Result<RectMaze, Error> {
+        let settings = &self.settings;
+        let interval = settings.cell_interval();
+        let invalid_size = Error::InvalidSize {
+            width: self.width,
+            height: self.height,
+        };
+        if settings.corridor_width == 0
+            || self.width < settings.wall_width
+            || self.height < settings.wall_width
+            || !(self.width - settings.wall_width).is_multiple_of(interval)
+            || !(self.height - settings.wall_width).is_multiple_of(interval)
+        {
+            return Err(invalid_size);
+        }
+
+        let (maze_width, maze_height) = (
+            (self.width - settings.wall_width) / interval,
+            (self.height - settings.wall_width) / interval,
+        );
+        let corridor_width = settings.corridor_width;
+        let mut mask = RectMask::new(maze_width, maze_height);
+        let mut is_masked = false;
+        for r in 0..maze_height {
+            for c in 0..maze_width {
+                let area = (settings.cell_start(c), settings.cell_start(r));
+                let is_floor = self
+                    .is_area_floor(area, (corridor_width, corridor_width))
+                    .ok_or(Error::MixedTiles("cell", r, c))?;
+                mask.set_flag(&RectPosition::new(r, c), is_floor);
+                is_masked |= !is_floor;
+            }
+        }
+
+        let mut maze = if is_masked {
+            RectMaze::WithMask(RectGrid::<WithMask>::new(&mask))
+        } else {
+            RectMaze::NoMask(RectGrid::<NoMask>::new(maze_width, maze_height))
+        };
+        for r in 0..maze_height {
+            for c in 0..maze_width {
+                let (x0, y0) = (settings.cell_start(c), settings.cell_start(r));
+                let walls = [
+                    (
+                        RectDirection::East,
+                        c + 1 < maze_width,
+                        (x0 + corridor_width, y0),
+                        (settings.wall_width, corridor_width),
+                    ),
+                    (
+                        RectDirection::South,
+                        r + 1 < maze_height,
+                        (x0, y0 + corridor_width),
+                        (corridor_width, settings.wall_width),
+                    ),
+                ];
+                for (dir, has_neighbor, area, area_size) in walls {
+                    if !has_neighbor {
+                        continue;
+                    }
+
+                    let is_passage = self
+                        .is_area_floor(area, area_size)
+                        .ok_or(Error::MixedTiles("wall", r, c))?;
+                    if is_passage {
+                        let pos = RectPosition::new(r, c);
+                        let neighbor = pos.neighbor(dir).unwrap();
+                        if !maze.is_cell(&pos) || !maze.is_cell(&neighbor) {
+                            return Err(Error::PassageToWall(r, c));
+                        }
+                        maze.grid_mut().connect_to(&pos.into(), &neighbor.into());
+                    }
+                }
+            }
+        }
+
+        Ok(maze)
+    }
+
+    /// Plain grid of tiles, a line per row, 0 for the floor and 1 for the wall.
+    pub fn to_grid_text(&self) -> String {
+        let mut text = String::with_capacity((self.width + 1) * self.height);
+        for row in self.walls.chunks(self.width.max(1)) {
+            text.extend(row.iter().map(|is_wall| if *is_wall { '1' } else { '0' }));
+            text.push('\n');
+        }
+
+        text
+    }
+
+    /// Map in Tiled TMX format, with a layer of floor and wall tiles, and the start and the goal as point objects.
+    pub fn to_tmx(&self) -> String {
+        let tile_size = self.settings.tile_size;
+        let mut tmx = String::new();
+        writeln!(tmx, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>").unwrap();
+        writeln!(
+            tmx,
+            "<map version=\"1.10\" orientation=\"orthogonal\" renderorder=\"right-down\" width=\"{}\" height=\"{}\" \
+             tilewidth=\"{}\" tileheight=\"{}\" infinite=\"0\" nextlayerid=\"3\" nextobjectid=\"3\">",
+            self.width, self.height, tile_size, tile_size
+        )
+        .unwrap();
+        writeln!(
+            tmx,
+            " <tileset firstgid=\"{}\" name=\"maze\" tilewidth=\"{}\" tileheight=\"{}\" tilecount=\"2\" columns=\"0\">",
+            FLOOR_GID, tile_size, tile_size
+        )
+        .unwrap();
+        for (id, name) in ["floor", "wall"].iter().enumerate() {
+            writeln!(
+                tmx,
+                "  <tile id=\"{}\" type=\"{}\"><properties><property name=\"wall\" type=\"bool\" value=\"{}\"/></properties></tile>",
+                id,
+                name,
+                id == 1
+            )
+            .unwrap();
+        }
+        writeln!(tmx, " </tileset>").unwrap();
+        writeln!(
+            tmx,
+            " <layer id=\"1\" name=\"maze\" width=\"{}\" height=\"{}\">",
+            self.width, self.height
+        )
+        .unwrap();
+        writeln!(tmx, "  <data encoding=\"csv\">").unwrap();
+        let rows = self
+            .gids()
+            .chunks(self.width.max(1))
+            .map(|row| {
+                row.iter()
+                    .map(|gid| gid.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect::<Vec<_>>();
+        writeln!(tmx, "{}", rows.join(",\n")).unwrap();
+        writeln!(tmx, "  </data>").unwrap();
+        writeln!(tmx, " </layer>").unwrap();
+        writeln!(tmx, " <objectgroup id=\"2\" name=\"endpoints\">").unwrap();
+        for (id, name, (x, y)) in self.endpoint_objects() {
+            writeln!(
+                tmx,
+                "  <object id=\"{}\" name=\"{}\" x=\"{}\" y=\"{}\"><point/></object>",
+                id, name, x, y
+            )
+            .unwrap();
+        }
+        writeln!(tmx, " </objectgroup>").unwrap();
+        writeln!(tmx, "</map>").unwrap();
+
+        tmx
+    }
+
+    /// Map in Tiled JSON format, the same content as the TMX format.
+    pub fn to_tiled_json(&self) -> String {
+        let tile_size = self.settings.tile_size;
+        let tiles = ["floor", "wall"]
+            .iter()
+            .enumerate()
+            .map(|(id, name)| {
+                json!({
+                    "id": id,
+                    "type": name,
+                    "properties": [{"name": "wall", "type": "bool", "value": id == 1}],
+                })
+            })
+            .collect::<Vec<_>>();
+        let objects = self
+            .endpoint_objects()
+            .into_iter()
+            .map(|(id, name, (x, y))| {
+                json!({
+                    "id": id,
+                    "name": name,
+                    "type": "",
+                    "x": x,
+                    "y": y,
+                    "width": 0,
+                    "height": 0,
+                    "rotation": 0,
+                    "point": true,
+                    "visible": true,
+                })
+            })
+            .collect::<Vec<_>>();
+        let map = json!({
+            "type": "map",
+            "version": "1.10",
+            "orientation": "orthogonal",
+            "renderorder": "right-down",
+            "infinite": false,
+            "width": self.width,
+            "height": self.height,
+            "tilewidth": tile_size,
+            "tileheight": tile_size,
+            "nextlayerid": 3,
+            "nextobjectid": 3,
+            "tilesets": [{
+                "firstgid": FLOOR_GID,
+                "name": "maze",
+                "tilewidth": tile_size,
+                "tileheight": tile_size,
+                "tilecount": 2,
+                "columns": 0,
+                "tiles": tiles,
+            }],
+            "layers": [
+                {
+                    "id": 1,
+                    "name": "maze",
+                    "type": "tilelayer",
+                    "x": 0,
+                    "y": 0,
+                    "width": self.width,
+                    "height": self.height,
+                    "opacity": 1,
+                    "visible": true,
+                    "data": self.gids(),
+                },
+                {
+                    "id": 2,
+                    "name": "endpoints",
+                    "type": "objectgroup",
+                    "draworder": "topdown",
+                    "x": 0,
+                    "y": 0,
+                    "opacity": 1,
+                    "visible": true,
+                    "objects": objects,
+                },
+            ],
+        });
+
+        serde_json::to_string_pretty(&map).expect("Tile map should be serialized to json.")
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P, format: TileMapFormat) -> Result<(), AnyError> {
+        let text = match format {
+            TileMapFormat::TMX => self.to_tmx(),
+            TileMapFormat::JSON => self.to_tiled_json(),
+            TileMapFormat::Grid => self.to_grid_text(),
+        };
+        fs::write(path, text)?;
+        Ok(())
+    }
+
+    /// Fill the floors out of the maze with walls, they are the masked out cells which aren't enclosed by walls.
+    /// The floors reachable from the border are outside, and so are the enclosed areas apart from the largest one.
+    fn fill_outer_floors(&mut self) {
+        let (width, height) = (self.width, self.height);
+        let mut area_inds = vec![None; self.walls.len()];
+        let mut areas_size = Vec::new();
+        for start_ind in 0..self.walls.len() {
+            if self.walls[start_ind] || area_inds[start_ind].is_some() {
+                continue;
+            }
+
+            let area_ind = areas_size.len();
+            let mut is_outer = false;
+            let mut size = 0;
+            let mut visit_stack = vec![start_ind];
+            area_inds[start_ind] = Some(area_ind);
+            while let Some(ind) = visit_stack.pop() {
+                let (x, y) = (ind % width, ind / width);
+                size += 1;
+                is_outer |= x == 0 || y == 0 || x + 1 == width || y + 1 == height;
+                let neighbors = [
+                    (x > 0).then(|| ind - 1),
+                    (x + 1 < width).then(|| ind + 1),
+                    (y > 0).then(|| ind - width),
+                    (y + 1 < height).then(|| ind + width),
+                ];
+                for neighbor in neighbors.into_iter().flatten() {
+                    if !self.walls[neighbor] && area_inds[neighbor].is_none() {
+                        area_inds[neighbor] = Some(area_ind);
+                        visit_stack.push(neighbor);
+                    }
+                }
+            }
+            areas_size.push(if is_outer { 0 } else { size });
+        }
+
+        let maze_area_ind = (0..areas_size.len())
+            .filter(|ind| areas_size[*ind] > 0)
+            .max_by_key(|ind| areas_size[*ind]);
+        for (is_wall, area_ind) in self.walls.iter_mut().zip(area_inds) {
+            if area_ind.is_some() && area_ind != maze_area_ind {
+                *is_wall = true;
+            }
+        }
+    }
+
+    fn set_floor(&mut self, x0: usize, y0: usize, width: usize, height: usize) {
+        for y in y0..(y0 + height) {
+            for x in x0..(x0 + width) {
+                self.walls[y * self.width + x] = false;
+            }
+        }
+    }
+
+    /// Whether the area is all floor, none if it's mixed.
+    fn is_area_floor(
+        &self,
+        (x0, y0): (usize, usize),
+        (width, height): (usize, usize),
+    ) -> Option<bool> {
+        let mut tiles = (y0..(y0 + height)).flat_map(|y| (x0..(x0 + width)).map(move |x| (x, y)));
+        let is_wall = tiles.next().is_some_and(|(x, y)| self.is_wall(x, y));
+        tiles
+            .all(|(x, y)| self.is_wall(x, y) == is_wall)
+            .then_some(!is_wall)
+    }
+
+    fn gids(&self) -> Vec<u32> {
+        self.walls
+            .iter()
+            .map(|is_wall| if *is_wall { WALL_GID } else { FLOOR_GID })
+            .collect()
+    }
+
+    /// The start and the goal with their object IDs, at the center of their cells in pixels.
+    fn endpoint_objects(&self) -> Vec<(usize, &'static str, (f32, f32))> {
+        let settings = &self.settings;
+        let center = |ind| {
+            (settings.cell_start(ind) as f32 + settings.corridor_width as f32 / 2.0)
+                * settings.tile_size as f32
+        };
+        [(1, "start", self.start), (2, "goal", self.goal)]
+            .into_iter()
+            .filter_map(|(id, name, pos)| {
+                pos.map(|pos| (id, name, (center(pos.col), center(pos.row))))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs, process};
+
+    use image::{Rgba, RgbaImage};
+
+    use crate::{
+        gene::{Maze2dGenerator, WilsonMazeGenerator},
+        maze::{
+            NoMask, Position2d, WithMask,
+            rect::{RectGrid, RectMask, RectMaze, RectPosition},
+        },
+        show::rect::{AsciiBoxCharset, RectMazeCmdDisplay, UnicodeBoxCharset},
+    };
+
+    use super::{Error, TileMap, TileSettings};
+
+    fn same_maze(maze: &RectMaze, other: &RectMaze) -> bool {
+        serde_json::to_value(maze).unwrap() == serde_json::to_value(other).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mut grid = RectGrid::<NoMask>::new(2, 2);
+        WilsonMazeGenerator.generate_2d(&mut grid).unwrap();
+        let maze = RectMaze::NoMask(grid);
+        let settings = TileSettings::default();
+        let map = TileMap::new(&maze, &settings, Some(Position2d(0, 0)), None);
+        assert_eq!(map.size(), (5, 5));
+        let text = map.to_grid_text();
+        // The borders and the center pillar are walls, and 3 of the 4 walls inside are passages.
+        assert_eq!(text.matches('0').count(), 7);
+        assert!(text.starts_with("11111\n10"));
+        let loaded = TileMap::from_grid_text(&text, &settings)
+            .unwrap()
+            .to_rect_maze()
+            .unwrap();
+        assert!(same_maze(&loaded, &maze));
+
+        let mut mask = RectMask::new(4, 3);
+        for r in 0..3 {
+            for c in 0..4 {
+                mask.set_flag(&RectPosition::new(r, c), (r, c) != (1, 0));
+            }
+        }
+        let mut grid = RectGrid::<WithMask>::new(&mask);
+        WilsonMazeGenerator.generate_2d(&mut grid).unwrap();
+        let maze = RectMaze::WithMask(grid);
+        let settings = TileSettings {
+            corridor_width: 3,
+            wall_width: 2,
+            ..TileSettings::default()
+        };
+        let map = TileMap::new(&maze, &settings, None, Some(Position2d(2, 3)));
+        assert_eq!(map.size(), (22, 17));
+        assert!(map.is_wall(2, 7));
+        let loaded = TileMap::from_grid_text(&map.to_grid_text(), &settings)
+            .unwrap()
+            .to_rect_maze()
+            .unwrap();
+        assert!(same_maze(&loaded, &maze));
+
+        let tmx = map.to_tmx();
+        assert!(tmx.contains("width=\"22\" height=\"17\""));
+        assert!(tmx.contains("name=\"goal\" x=\"296\" y=\"216\""));
+        let json: serde_json::Value = serde_json::from_str(&map.to_tiled_json()).unwrap();
+        assert_eq!(json["layers"][0]["data"].as_array().unwrap().len(), 22 * 17);
+        assert_eq!(json["layers"][1]["objects"][0]["name"], "goal");
+    }
+
+    #[test]
+    fn test_box_text() {
+        let mut mask = RectMask::new(5, 4);
+        for r in 0..4 {
+            for c in 0..5 {
+                mask.set_flag(&RectPosition::new(r, c), !matches!((r, c), (0, 0) | (2, 2)));
+            }
+        }
+        let mut grid = RectGrid::<WithMask>::new(&mask);
+        WilsonMazeGenerator.generate_2d(&mut grid).unwrap();
+        let maze = RectMaze::WithMask(grid);
+        let ascii_text = RectMazeCmdDisplay(&maze, AsciiBoxCharset).to_string();
+        let unicode_text = RectMazeCmdDisplay(&maze, UnicodeBoxCharset).to_string();
+        let trimmed_text = ascii_text
+            .lines()
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n");
+        for text in [ascii_text, unicode_text, trimmed_text] {
+            let loaded = TileMap::from_box_text(&text)
+                .unwrap()
+                .to_rect_maze()
+                .unwrap();
+            assert!(same_maze(&loaded, &maze));
+        }
+
+        assert!(matches!(
+            TileMap::from_box_text("+---+-\n|   |\n"),
+            Err(Error::InvalidTextWidth(6, 4))
+        ));
+    }
+
+    #[test]
+    fn test_bitmap() {
+        let mut mask = RectMask::new(4, 3);
+        for r in 0..3 {
+            for c in 0..4 {
+                mask.set_flag(&RectPosition::new(r, c), (r, c) != (0, 3));
+            }
+        }
+        let mut grid = RectGrid::<WithMask>::new(&mask);
+        WilsonMazeGenerator.generate_2d(&mut grid).unwrap();
+        let maze = RectMaze::WithMask(grid);
+        let map = TileMap::new(&maze, &TileSettings::default(), None, None);
+        let (width, height) = map.size();
+        // Paint the masked out cell as a transparent floor, only filling the outer floors turns it back into a wall.
+        let image = RgbaImage::from_fn(
+            u32::try_from(width).unwrap(),
+            u32::try_from(height).unwrap(),
+            |x, y| {
+                let (x, y) = (x as usize, y as usize);
+                if !map.is_wall(x, y) {
+                    Rgba([0xff, 0xff, 0xff, 0xff])
+                } else if (x, y) == (7, 1) {
+                    Rgba([0xff, 0xff, 0xff, 0])
+                } else {
+                    Rgba([0x10, 0x10, 0x10, 0xff])
+                }
+            },
+        );
+        let path = env::temp_dir().join(format!("tile_map_bitmap_{}.png", process::id()));
+        image.save(&path).unwrap();
+        let loaded = RectMaze::try_from_image_file(&path);
+        fs::remove_file(&path).unwrap();
+        assert!(same_maze(&loaded.unwrap(), &maze));
+    }
+
+    #[test]
+    fn test_invalid_grid() {
+        let settings = TileSettings::default();
+        assert!(matches!(
+            TileMap::from_grid_text("111\n11\n", &settings),
+            Err(Error::InconsistentRow { .. })
+        ));
+        assert!(matches!(
+            TileMap::from_grid_text("1x1\n", &settings),
+            Err(Error::InvalidTile('x'))
+        ));
+        let map = TileMap::from_grid_text("1111\n1001\n1111\n", &settings).unwrap();
+        assert!(matches!(map.to_rect_maze(), Err(Error::InvalidSize { .. })));
+        let map = TileMap::from_grid_text("11111\n10001\n11111\n", &settings).unwrap();
+        assert!(map.to_rect_maze().is_ok());
+        let map = TileMap::from_grid_text("11111\n10101\n10011\n11111\n", &settings).unwrap();
+        assert!(matches!(map.to_rect_maze(), Err(Error::InvalidSize { .. })));
+        let map = TileMap::from_grid_text("11111\n10011\n11111\n", &settings).unwrap();
+        assert!(matches!(
+            map.to_rect_maze(),
+            Err(Error::PassageToWall(0, 0))
+        ));
+    }
+}
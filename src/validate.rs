@@ -0,0 +1,317 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::{
+    gene::Union,
+    maze::{Grid2d, Position2d, StrayPassage},
+};
+
+/// Broken invariant of a perfect maze, found by validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum Violation {
+    /// A neighbor given by grid isn't a cell.
+    NeighborNotCell {
+        pos: Position2d,
+        neighbor: Position2d,
+    },
+    /// The neighbor doesn't take the cell as its neighbor.
+    AsymmetricNeighbor {
+        pos: Position2d,
+        neighbor: Position2d,
+    },
+    /// The cell is connected to its neighbor, but not the other way around.
+    AsymmetricPassage {
+        pos: Position2d,
+        neighbor: Position2d,
+    },
+    Stray(StrayPassage),
+    /// The cell can't be reached from the first cell.
+    Unreachable {
+        pos: Position2d,
+    },
+    /// The passage joins two cells already connected by other passages, so makes a loop.
+    Loop {
+        from: Position2d,
+        to: Position2d,
+    },
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let pos_str = |pos: &Position2d| format!("({}, {})", pos.0, pos.1);
+        match self {
+            Violation::NeighborNotCell { pos, neighbor } => write!(
+                f,
+                "Neighbor {} of cell {} isn't a cell.",
+                pos_str(neighbor),
+                pos_str(pos)
+            ),
+            Violation::AsymmetricNeighbor { pos, neighbor } => write!(
+                f,
+                "Cell {} is neighbor of {}, but not the other way around.",
+                pos_str(neighbor),
+                pos_str(pos)
+            ),
+            Violation::AsymmetricPassage { pos, neighbor } => write!(
+                f,
+                "Cell {} is connected to {}, but not the other way around.",
+                pos_str(pos),
+                pos_str(neighbor)
+            ),
+            Violation::Stray(StrayPassage { from, to }) => write!(
+                f,
+                "Stray passage from {} to {}.",
+                pos_str(from),
+                to.as_ref()
+                    .map(pos_str)
+                    .unwrap_or_else(|| "outside of grid".to_string())
+            ),
+            Violation::Unreachable { pos } => write!(f, "Cell {} is unreachable.", pos_str(pos)),
+            Violation::Loop { from, to } => write!(
+                f,
+                "Passage between {} and {} makes a loop.",
+                pos_str(from),
+                pos_str(to)
+            ),
+        }
+    }
+}
+
+/// Result of checking a maze is a spanning tree over its cells, with all the violations found.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ValidationReport {
+    pub cells_n: usize,
+    pub violations: Vec<Violation>,
+}
+
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_valid() {
+            return write!(f, "Valid perfect maze of {} cells.", self.cells_n);
+        }
+
+        write!(
+            f,
+            "Invalid maze of {} cells, found {} violation(s):",
+            self.cells_n,
+            self.violations.len()
+        )?;
+        for violation in self.violations.iter() {
+            write!(f, "\n{}", violation)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl ValidationReport {
+    pub fn new(grid: &dyn Grid2d) -> Self {
+        let cells_pos_set = grid.all_cells_pos_set();
+        let mut cells_pos: Vec<_> = cells_pos_set.iter().copied().collect();
+        cells_pos.sort();
+        let mut violations: Vec<_> = grid
+            .stray_passages()
+            .into_iter()
+            .map(Violation::Stray)
+            .collect();
+
+        // Merge cells by passages, so a passage between cells already merged makes a loop.
+        let mut union: Union<_> = cells_pos.iter().copied().collect();
+        let (mut neighbors, mut neighbor_neighbors) = (Vec::new(), Vec::new());
+        for pos in cells_pos.iter() {
+            neighbors.clear();
+            grid.append_neighbors(pos, &mut neighbors);
+            for neighbor in neighbors.iter() {
+                if !cells_pos_set.contains(neighbor) {
+                    violations.push(Violation::NeighborNotCell {
+                        pos: *pos,
+                        neighbor: *neighbor,
+                    });
+                    continue;
+                }
+
+                neighbor_neighbors.clear();
+                grid.append_neighbors(neighbor, &mut neighbor_neighbors);
+                if !neighbor_neighbors.contains(pos) {
+                    violations.push(Violation::AsymmetricNeighbor {
+                        pos: *pos,
+                        neighbor: *neighbor,
+                    });
+                }
+
+                let is_connected = grid.is_connected_to(pos, neighbor);
+                if is_connected != grid.is_connected_to(neighbor, pos) {
+                    if is_connected {
+                        violations.push(Violation::AsymmetricPassage {
+                            pos: *pos,
+                            neighbor: *neighbor,
+                        });
+                    }
+                } else if is_connected && pos < neighbor && !union.merge(pos, neighbor) {
+                    violations.push(Violation::Loop {
+                        from: *pos,
+                        to: *neighbor,
+                    });
+                }
+            }
+        }
+
+        if let Some(first_pos) = cells_pos.first() {
+            violations.extend(
+                cells_pos
+                    .iter()
+                    .filter(|pos| union.is_union(first_pos, pos) != Some(true))
+                    .map(|pos| Violation::Unreachable { pos: *pos }),
+            );
+        }
+
+        Self {
+            cells_n: cells_pos.len(),
+            violations,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        gene::{
+            AldousBroderMazeGenerator, BTreeMazeGenerator, DiagonalDirection, EllerMazeGenerator,
+            GrowingTreeMazeGenerator, GrowingTreeStrategy, HuntAndKillMazeGenerator,
+            KruskalMazeGenerator, LayerMazeGenerator, Maze2dGenerator, OriginShiftMazeGenerator,
+            PrimMazeGenerator, RecursiveBacktrackerMazeGenerator, RegionDivisionMazeGenerator,
+            SidewinderMazeGenerator, WilsonMazeGenerator,
+        },
+        maze::{
+            Grid2d, LayerGrid, NoMask, Position2d, StrayPassage, WithMask,
+            circ::CircGrid,
+            cube::CubeGrid,
+            hexa::HexaGrid,
+            rect::{RectGrid, RectMask, RectPosition},
+            tri::TriGrid,
+            voronoi::{VoronoiGrid, VoronoiShape},
+        },
+    };
+
+    use super::{ValidationReport, Violation};
+
+    #[test]
+    fn test_generated_mazes_are_valid() {
+        let mut mask = RectMask::new(6, 5);
+        for r in 0..5 {
+            for c in 0..6 {
+                mask.set_flag(&RectPosition::new(r, c), true);
+            }
+        }
+        mask.set_flag(&RectPosition::new(1, 1), false);
+        mask.set_flag(&RectPosition::new(3, 4), false);
+        let layer_grids: Vec<Box<dyn Fn() -> Box<dyn LayerGrid>>> = vec![
+            Box::new(|| Box::new(RectGrid::<NoMask>::new(6, 5))),
+            Box::new(|| Box::new(RectGrid::<WithMask>::new(&mask))),
+            Box::new(|| Box::new(HexaGrid::<NoMask>::new(6, 5))),
+            Box::new(|| Box::new(HexaGrid::<WithMask>::new(&mask))),
+            Box::new(|| Box::new(TriGrid::new(6, 5))),
+            Box::new(|| Box::new(TriGrid::with_mask(&mask))),
+            Box::new(|| Box::new(CircGrid::new(4))),
+        ];
+        let other_grids: Vec<Box<dyn Fn() -> Box<dyn Grid2d>>> = vec![
+            Box::new(|| Box::new(CubeGrid::new(3))),
+            Box::new(|| Box::new(VoronoiGrid::new(&VoronoiShape::Circle { radius: 4.0 }, 1.0))),
+        ];
+        let generators: Vec<Box<dyn Maze2dGenerator>> = vec![
+            Box::new(AldousBroderMazeGenerator),
+            Box::new(WilsonMazeGenerator),
+            Box::new(HuntAndKillMazeGenerator),
+            Box::new(RecursiveBacktrackerMazeGenerator),
+            Box::new(KruskalMazeGenerator),
+            Box::new(PrimMazeGenerator),
+            Box::new(GrowingTreeMazeGenerator::new(GrowingTreeStrategy::default())),
+            Box::new(OriginShiftMazeGenerator),
+        ];
+        let layer_generators: Vec<Box<dyn LayerMazeGenerator>> = vec![
+            Box::new(EllerMazeGenerator),
+            Box::new(BTreeMazeGenerator::new(DiagonalDirection::Northeast)),
+            Box::new(SidewinderMazeGenerator::new(DiagonalDirection::Southwest)),
+        ];
+
+        for new_grid in layer_grids.iter() {
+            for generator in generators.iter() {
+                let mut grid = new_grid();
+                generator.generate_2d(grid.as_mut()).unwrap();
+                assert_eq!(ValidationReport::new(grid.as_ref()).violations, []);
+            }
+            for generator in layer_generators.iter() {
+                let mut grid = new_grid();
+                generator.generate_layer(grid.as_mut()).unwrap();
+                assert_eq!(ValidationReport::new(grid.as_ref()).violations, []);
+            }
+            let mut grid = new_grid();
+            RegionDivisionMazeGenerator::new(1)
+                .generate_2d(grid.as_mut())
+                .unwrap();
+            assert_eq!(ValidationReport::new(grid.as_ref()).violations, []);
+        }
+        for new_grid in other_grids.iter() {
+            for generator in generators.iter() {
+                let mut grid = new_grid();
+                generator.generate_2d(grid.as_mut()).unwrap();
+                assert_eq!(ValidationReport::new(grid.as_ref()).violations, []);
+            }
+        }
+    }
+
+    #[test]
+    fn test_violations_of_broken_maze() {
+        // +---+---+
+        // |       |
+        // +   +---+
+        // |       |
+        // +---+---+
+        let mut grid = RectGrid::<NoMask>::new(2, 2);
+        for (from, to) in [((0, 0), (0, 1)), ((0, 0), (1, 0)), ((1, 0), (1, 1))] {
+            assert!(Grid2d::connect_to(
+                &mut grid,
+                &Position2d(from.0, from.1),
+                &Position2d(to.0, to.1)
+            ));
+        }
+        let report = ValidationReport::new(&grid);
+        assert!(report.is_valid());
+        assert_eq!(report.cells_n, 4);
+
+        Grid2d::connect_to(&mut grid, &Position2d(0, 1), &Position2d(1, 1));
+        assert_eq!(
+            ValidationReport::new(&grid).violations,
+            [Violation::Loop {
+                from: Position2d(1, 0),
+                to: Position2d(1, 1)
+            }]
+        );
+        Grid2d::disconnect_from(&mut grid, &Position2d(1, 0), &Position2d(0, 0));
+        Grid2d::disconnect_from(&mut grid, &Position2d(1, 0), &Position2d(1, 1));
+        assert_eq!(
+            ValidationReport::new(&grid).violations,
+            [Violation::Unreachable {
+                pos: Position2d(1, 0)
+            }]
+        );
+
+        // A passage to the north of the first cell can only come from a modified file.
+        let mut json = serde_json::to_value(RectGrid::<NoMask>::new(2, 1)).unwrap();
+        json[0]["cells"][0]["n"] = true.into();
+        json[0]["cells"][0]["e"] = true.into();
+        let grid: RectGrid<NoMask> = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            ValidationReport::new(&grid).violations,
+            [Violation::Stray(StrayPassage {
+                from: Position2d(0, 0),
+                to: None
+            })]
+        );
+    }
+}